@@ -0,0 +1,177 @@
+use candid::{CandidType, Deserialize, Principal};
+use ic_cdk::api::caller as ic_caller;
+use ic_cdk::api::time;
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Role {
+    Admin,
+    PropertyVerifier,
+    Appraiser,
+    PropertyManager,
+    ComplianceOfficer,
+    Treasurer,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum AccessControlError {
+    MissingRole { role: Role },
+    AnonymousPrincipal,
+    RoleAlreadyHeld { account: Principal, role: Role },
+    RoleNotHeld { account: Principal, role: Role },
+    LastAdmin,
+}
+
+impl std::fmt::Display for AccessControlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AccessControlError::MissingRole { role } => write!(f, "Caller is missing the {:?} role", role),
+            AccessControlError::AnonymousPrincipal => write!(f, "Cannot grant roles to the anonymous principal"),
+            AccessControlError::RoleAlreadyHeld { role, .. } => write!(f, "Account already holds the {:?} role", role),
+            AccessControlError::RoleNotHeld { role, .. } => write!(f, "Account does not hold the {:?} role", role),
+            AccessControlError::LastAdmin => write!(f, "Cannot revoke the last Admin"),
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum RoleChangeKind {
+    Granted,
+    Revoked,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct RoleChange {
+    pub id: u64,
+    pub account: Principal,
+    pub role: Role,
+    pub kind: RoleChangeKind,
+    pub changed_by: Principal,
+    pub timestamp: u64,
+}
+
+thread_local! {
+    static ROLES: RefCell<HashMap<Principal, BTreeSet<Role>>> = RefCell::new(HashMap::new());
+    // Append-only audit trail of grants and revocations
+    static ROLE_LOG: RefCell<Vec<RoleChange>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Role-based access control for privileged endpoints. Admins pass every role
+/// check, so a fresh deployment is fully operable by the deployer alone.
+pub struct AccessControl;
+
+impl AccessControl {
+    /// Makes `admin` the first Admin. Called once from `init`.
+    pub(crate) fn bootstrap(admin: Principal) {
+        Self::apply(admin, Role::Admin, RoleChangeKind::Granted, admin);
+    }
+
+    pub fn has_role(account: Principal, role: Role) -> bool {
+        ROLES.with(|roles| {
+            roles.borrow()
+                .get(&account)
+                .is_some_and(|held| held.contains(&role) || held.contains(&Role::Admin))
+        })
+    }
+
+    /// Returns the caller if they hold `role` (or Admin).
+    pub fn require(role: Role) -> Result<Principal, AccessControlError> {
+        let caller = ic_caller();
+        if Self::has_role(caller, role) {
+            Ok(caller)
+        } else {
+            Err(AccessControlError::MissingRole { role })
+        }
+    }
+
+    pub fn grant_role(account: Principal, role: Role) -> Result<bool, AccessControlError> {
+        let caller = Self::require(Role::Admin)?;
+
+        if account == Principal::anonymous() {
+            return Err(AccessControlError::AnonymousPrincipal);
+        }
+        if Self::get_roles(account).contains(&role) {
+            return Err(AccessControlError::RoleAlreadyHeld { account, role });
+        }
+
+        Self::apply(account, role, RoleChangeKind::Granted, caller);
+        Ok(true)
+    }
+
+    pub fn revoke_role(account: Principal, role: Role) -> Result<bool, AccessControlError> {
+        let caller = Self::require(Role::Admin)?;
+
+        if !Self::get_roles(account).contains(&role) {
+            return Err(AccessControlError::RoleNotHeld { account, role });
+        }
+        if role == Role::Admin && Self::get_role_members(Role::Admin).len() == 1 {
+            return Err(AccessControlError::LastAdmin);
+        }
+
+        Self::apply(account, role, RoleChangeKind::Revoked, caller);
+        Ok(true)
+    }
+
+    pub fn get_roles(account: Principal) -> Vec<Role> {
+        ROLES.with(|roles| {
+            roles.borrow()
+                .get(&account)
+                .map(|held| held.iter().copied().collect())
+                .unwrap_or_default()
+        })
+    }
+
+    pub fn get_role_members(role: Role) -> Vec<Principal> {
+        ROLES.with(|roles| {
+            roles.borrow()
+                .iter()
+                .filter(|(_, held)| held.contains(&role))
+                .map(|(account, _)| *account)
+                .collect()
+        })
+    }
+
+    pub fn get_audit_log(start: u64, length: u64) -> Vec<RoleChange> {
+        ROLE_LOG.with(|log| {
+            log.borrow()
+                .iter()
+                .skip(start as usize)
+                .take(length as usize)
+                .cloned()
+                .collect()
+        })
+    }
+
+    fn apply(account: Principal, role: Role, kind: RoleChangeKind, changed_by: Principal) {
+        ROLES.with(|roles| {
+            let mut roles = roles.borrow_mut();
+            match kind {
+                RoleChangeKind::Granted => {
+                    roles.entry(account).or_default().insert(role);
+                }
+                RoleChangeKind::Revoked => {
+                    if let Some(held) = roles.get_mut(&account) {
+                        held.remove(&role);
+                        if held.is_empty() {
+                            roles.remove(&account);
+                        }
+                    }
+                }
+            }
+        });
+
+        ROLE_LOG.with(|log| {
+            let mut log = log.borrow_mut();
+            let id = log.len() as u64 + 1;
+            log.push(RoleChange {
+                id,
+                account,
+                role,
+                kind,
+                changed_by,
+                timestamp: time(),
+            });
+        });
+    }
+}
@@ -0,0 +1,529 @@
+use candid::{CandidType, Deserialize, Principal};
+use ic_cdk::api::caller as ic_caller;
+use ic_cdk::api::time;
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+use crate::access_control::{AccessControl, Role};
+use crate::checkpoints::Checkpoints;
+use crate::pause::{CircuitBreaker, Paused, Subsystem};
+use crate::ret_token::{RETToken, TokenError};
+
+const MAX_BATCH_SIZE: usize = 500;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum AirdropMode {
+    // Admin pushes tokens to recipients in batches
+    Push,
+    // Recipients claim with a proof against the root of a sha256 Merkle tree whose
+    // leaves are sha256(principal bytes ++ amount as big-endian u64)
+    MerkleClaim { merkle_root: Vec<u8> },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum CampaignStatus {
+    Active,
+    Reclaimed,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct AirdropCampaign {
+    pub id: u64,
+    pub name: String,
+    pub mode: AirdropMode,
+    // When set, only principals holding RET at this snapshot are eligible
+    pub snapshot_id: Option<u64>,
+    pub budget: u64,
+    pub distributed: u64,
+    pub recipients: u64,
+    pub reclaimed: u64,
+    pub created_by: Principal,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub status: CampaignStatus,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct CampaignReport {
+    pub campaign_id: u64,
+    pub name: String,
+    pub budget: u64,
+    pub distributed: u64,
+    pub remaining: u64,
+    pub recipients: u64,
+    pub reclaimed: u64,
+    pub expired: bool,
+    pub status: CampaignStatus,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct BatchResult {
+    pub distributed: u64,
+    pub recipients: u64,
+    pub skipped: Vec<Principal>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum AirdropError {
+    Unauthorized,
+    ZeroBudget,
+    ExpiryInPast,
+    SnapshotNotFound { snapshot_id: u64 },
+    // Merkle roots are 32-byte sha256 hashes
+    InvalidMerkleRoot,
+    ExceedsAllocation { requested: u64, available: u64 },
+    BatchTooLarge { max: u64 },
+    NotPushCampaign,
+    NotClaimCampaign,
+    CampaignNotFound { campaign_id: u64 },
+    CampaignClosed,
+    CampaignExpired { expired_at: u64 },
+    NotExpired { expires_at: u64 },
+    AlreadyClaimed,
+    NotEligible,
+    InvalidProof,
+    ExceedsBudget { requested: u64, remaining: u64 },
+    Paused { subsystem: Subsystem },
+    Token { error: TokenError },
+}
+
+impl std::fmt::Display for AirdropError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AirdropError::Unauthorized => write!(f, "Not authorized"),
+            AirdropError::ZeroBudget => write!(f, "Campaign budget must be greater than zero"),
+            AirdropError::ExpiryInPast => write!(f, "Campaign expiry must be in the future"),
+            AirdropError::SnapshotNotFound { snapshot_id } => write!(f, "Snapshot {} not found", snapshot_id),
+            AirdropError::InvalidMerkleRoot => write!(f, "Merkle root must be a 32-byte sha256 hash"),
+            AirdropError::ExceedsAllocation { requested, available } => {
+                write!(f, "Budget of {} exceeds the {} unreserved airdrop allocation", requested, available)
+            }
+            AirdropError::BatchTooLarge { max } => write!(f, "Batch exceeds {} recipients", max),
+            AirdropError::NotPushCampaign => write!(f, "Campaign does not use push distribution"),
+            AirdropError::NotClaimCampaign => write!(f, "Campaign does not accept claims"),
+            AirdropError::CampaignNotFound { campaign_id } => write!(f, "Campaign {} not found", campaign_id),
+            AirdropError::CampaignClosed => write!(f, "Campaign is closed"),
+            AirdropError::CampaignExpired { .. } => write!(f, "Campaign has expired"),
+            AirdropError::NotExpired { expires_at } => write!(f, "Campaign runs until {}", expires_at),
+            AirdropError::AlreadyClaimed => write!(f, "Airdrop already claimed"),
+            AirdropError::NotEligible => write!(f, "Not a holder at the campaign snapshot"),
+            AirdropError::InvalidProof => write!(f, "Invalid Merkle proof"),
+            AirdropError::ExceedsBudget { requested, remaining } => {
+                write!(f, "{} exceeds the remaining campaign budget of {}", requested, remaining)
+            }
+            AirdropError::Paused { subsystem } => write!(f, "{}", Paused { subsystem: *subsystem }),
+            AirdropError::Token { error } => write!(f, "{}", error),
+        }
+    }
+}
+
+impl From<Paused> for AirdropError {
+    fn from(paused: Paused) -> Self {
+        AirdropError::Paused { subsystem: paused.subsystem }
+    }
+}
+
+impl From<TokenError> for AirdropError {
+    fn from(error: TokenError) -> Self {
+        AirdropError::Token { error }
+    }
+}
+
+thread_local! {
+    static CAMPAIGNS: RefCell<HashMap<u64, AirdropCampaign>> = RefCell::new(HashMap::new());
+    static CAMPAIGN_CLAIMS: RefCell<HashMap<u64, HashMap<Principal, u64>>> = RefCell::new(HashMap::new());
+    static CAMPAIGN_COUNTER: RefCell<u64> = const { RefCell::new(0) };
+}
+
+pub struct AirdropManager;
+
+impl AirdropManager {
+    pub fn create_campaign(
+        name: String,
+        mode: AirdropMode,
+        snapshot_id: Option<u64>,
+        budget: u64,
+        expires_at: u64,
+    ) -> Result<u64, AirdropError> {
+        let caller = Self::require_treasurer()?;
+
+        if budget == 0 {
+            return Err(AirdropError::ZeroBudget);
+        }
+        if expires_at <= time() {
+            return Err(AirdropError::ExpiryInPast);
+        }
+        if let Some(snapshot_id) = snapshot_id.filter(|id| Checkpoints::get_snapshot(*id).is_none()) {
+            return Err(AirdropError::SnapshotNotFound { snapshot_id });
+        }
+        if let AirdropMode::MerkleClaim { merkle_root } = &mode {
+            if merkle_root.len() != 32 {
+                return Err(AirdropError::InvalidMerkleRoot);
+            }
+        }
+
+        // Budgets are reserved up front so campaigns can never oversubscribe the allocation
+        let available = RETToken::airdrop_pool_remaining() - Self::reserved_budget();
+        if budget > available {
+            return Err(AirdropError::ExceedsAllocation { requested: budget, available });
+        }
+
+        let id = CAMPAIGN_COUNTER.with(|counter| {
+            let mut counter = counter.borrow_mut();
+            *counter += 1;
+            *counter
+        });
+
+        CAMPAIGNS.with(|campaigns| {
+            campaigns.borrow_mut().insert(id, AirdropCampaign {
+                id,
+                name,
+                mode,
+                snapshot_id,
+                budget,
+                distributed: 0,
+                recipients: 0,
+                reclaimed: 0,
+                created_by: caller,
+                created_at: time(),
+                expires_at,
+                status: CampaignStatus::Active,
+            });
+        });
+
+        Ok(id)
+    }
+
+    pub fn distribute_batch(campaign_id: u64, recipients: Vec<(Principal, u64)>) -> Result<BatchResult, AirdropError> {
+        Self::require_treasurer()?;
+        CircuitBreaker::ensure_active(Subsystem::Distributions)?;
+
+        if recipients.len() > MAX_BATCH_SIZE {
+            return Err(AirdropError::BatchTooLarge { max: MAX_BATCH_SIZE as u64 });
+        }
+
+        let campaign = Self::active_campaign(campaign_id)?;
+        if !matches!(campaign.mode, AirdropMode::Push) {
+            return Err(AirdropError::NotPushCampaign);
+        }
+
+        // One allocation per principal, across batches and within this batch
+        let mut seen = HashSet::new();
+        let mut skipped = Vec::new();
+        let mut accepted = Vec::new();
+        for (recipient, amount) in recipients {
+            if amount == 0
+                || Self::has_claimed(campaign_id, recipient)
+                || !Self::is_eligible(&campaign, recipient)
+                || !seen.insert(recipient)
+            {
+                skipped.push(recipient);
+            } else {
+                accepted.push((recipient, amount));
+            }
+        }
+
+        let total: u64 = accepted.iter().map(|(_, amount)| amount).sum();
+        let remaining = campaign.budget - campaign.distributed;
+        if total > remaining {
+            return Err(AirdropError::ExceedsBudget { requested: total, remaining });
+        }
+
+        for (recipient, amount) in &accepted {
+            Self::pay_out(campaign_id, *recipient, *amount)?;
+        }
+
+        Ok(BatchResult {
+            distributed: total,
+            recipients: accepted.len() as u64,
+            skipped,
+        })
+    }
+
+    pub fn claim(campaign_id: u64, amount: u64, proof: Vec<Vec<u8>>) -> Result<u64, AirdropError> {
+        CircuitBreaker::ensure_active(Subsystem::Distributions)?;
+        let caller = ic_caller();
+        let campaign = Self::active_campaign(campaign_id)?;
+
+        let merkle_root = match &campaign.mode {
+            AirdropMode::MerkleClaim { merkle_root } => merkle_root.clone(),
+            AirdropMode::Push => return Err(AirdropError::NotClaimCampaign),
+        };
+
+        if Self::has_claimed(campaign_id, caller) {
+            return Err(AirdropError::AlreadyClaimed);
+        }
+
+        if !Self::is_eligible(&campaign, caller) {
+            return Err(AirdropError::NotEligible);
+        }
+
+        if !verify_proof(&merkle_root, &merkle_leaf(caller, amount), &proof) {
+            return Err(AirdropError::InvalidProof);
+        }
+
+        let remaining = campaign.budget - campaign.distributed;
+        if amount > remaining {
+            return Err(AirdropError::ExceedsBudget { requested: amount, remaining });
+        }
+
+        Self::pay_out(campaign_id, caller, amount)
+    }
+
+    /// Closes an expired campaign and releases its unclaimed budget back to the airdrop pool.
+    pub fn reclaim(campaign_id: u64) -> Result<u64, AirdropError> {
+        Self::require_treasurer()?;
+
+        CAMPAIGNS.with(|campaigns| {
+            let mut campaigns = campaigns.borrow_mut();
+            let campaign = campaigns.get_mut(&campaign_id)
+                .ok_or(AirdropError::CampaignNotFound { campaign_id })?;
+
+            if campaign.status != CampaignStatus::Active {
+                return Err(AirdropError::CampaignClosed);
+            }
+            if time() < campaign.expires_at {
+                return Err(AirdropError::NotExpired { expires_at: campaign.expires_at });
+            }
+
+            campaign.reclaimed = campaign.budget - campaign.distributed;
+            campaign.status = CampaignStatus::Reclaimed;
+
+            Ok(campaign.reclaimed)
+        })
+    }
+
+    /// Ad-hoc admin airdrop outside of any campaign. Duplicate recipients are rejected.
+    pub fn airdrop(recipients: Vec<(Principal, u64)>) -> Result<bool, TokenError> {
+        AccessControl::require(Role::Treasurer).map_err(|_| TokenError::Unauthorized)?;
+        RETToken::get_metadata().ok_or(TokenError::NotInitialized)?;
+        CircuitBreaker::ensure_active(Subsystem::Distributions)?;
+
+        let mut seen = HashSet::new();
+        if let Some((recipient, _)) = recipients.iter().find(|(recipient, _)| !seen.insert(*recipient)) {
+            return Err(TokenError::DuplicateRecipient { recipient: *recipient });
+        }
+
+        let total_amount: u64 = recipients.iter().map(|(_, amount)| amount).sum();
+        let available = RETToken::airdrop_pool_remaining() - Self::reserved_budget();
+        if total_amount > available {
+            return Err(TokenError::InsufficientBalance { required: total_amount, available });
+        }
+
+        for (recipient, amount) in recipients {
+            RETToken::credit_airdrop(recipient, amount, None)?;
+        }
+
+        Ok(true)
+    }
+
+    pub fn get_campaign(campaign_id: u64) -> Option<AirdropCampaign> {
+        CAMPAIGNS.with(|campaigns| campaigns.borrow().get(&campaign_id).cloned())
+    }
+
+    pub fn get_campaigns() -> Vec<AirdropCampaign> {
+        CAMPAIGNS.with(|campaigns| {
+            let mut campaigns: Vec<AirdropCampaign> = campaigns.borrow().values().cloned().collect();
+            campaigns.sort_by_key(|campaign| campaign.id);
+            campaigns
+        })
+    }
+
+    pub fn get_report(campaign_id: u64) -> Option<CampaignReport> {
+        Self::get_campaign(campaign_id).map(|campaign| CampaignReport {
+            campaign_id: campaign.id,
+            name: campaign.name,
+            budget: campaign.budget,
+            distributed: campaign.distributed,
+            remaining: campaign.budget - campaign.distributed - campaign.reclaimed,
+            recipients: campaign.recipients,
+            reclaimed: campaign.reclaimed,
+            expired: time() >= campaign.expires_at,
+            status: campaign.status,
+        })
+    }
+
+    pub fn get_claims(campaign_id: u64, start: u64, length: u64) -> Vec<(Principal, u64)> {
+        CAMPAIGN_CLAIMS.with(|claims| {
+            let claims = claims.borrow();
+            let mut entries: Vec<(Principal, u64)> = claims
+                .get(&campaign_id)
+                .map(|claims| claims.iter().map(|(p, a)| (*p, *a)).collect())
+                .unwrap_or_default();
+            entries.sort_by_key(|(principal, _)| *principal);
+            entries.into_iter()
+                .skip(start as usize)
+                .take(length as usize)
+                .collect()
+        })
+    }
+
+    pub fn has_claimed(campaign_id: u64, principal: Principal) -> bool {
+        CAMPAIGN_CLAIMS.with(|claims| {
+            claims.borrow()
+                .get(&campaign_id)
+                .map(|claims| claims.contains_key(&principal))
+                .unwrap_or(false)
+        })
+    }
+
+    fn pay_out(campaign_id: u64, recipient: Principal, amount: u64) -> Result<u64, AirdropError> {
+        let memo = format!("airdrop:{}", campaign_id).into_bytes();
+        let tx_id = RETToken::credit_airdrop(recipient, amount, Some(memo))?;
+
+        CAMPAIGN_CLAIMS.with(|claims| {
+            claims.borrow_mut()
+                .entry(campaign_id)
+                .or_default()
+                .insert(recipient, amount);
+        });
+
+        CAMPAIGNS.with(|campaigns| {
+            if let Some(campaign) = campaigns.borrow_mut().get_mut(&campaign_id) {
+                campaign.distributed += amount;
+                campaign.recipients += 1;
+            }
+        });
+
+        Ok(tx_id)
+    }
+
+    fn is_eligible(campaign: &AirdropCampaign, principal: Principal) -> bool {
+        match campaign.snapshot_id {
+            Some(snapshot_id) => Checkpoints::holdings_at_snapshot(snapshot_id, principal)
+                .map(|holdings| holdings > 0)
+                .unwrap_or(false),
+            None => true,
+        }
+    }
+
+    fn active_campaign(campaign_id: u64) -> Result<AirdropCampaign, AirdropError> {
+        let campaign = Self::get_campaign(campaign_id).ok_or(AirdropError::CampaignNotFound { campaign_id })?;
+
+        if campaign.status != CampaignStatus::Active {
+            return Err(AirdropError::CampaignClosed);
+        }
+        if time() >= campaign.expires_at {
+            return Err(AirdropError::CampaignExpired { expired_at: campaign.expires_at });
+        }
+
+        Ok(campaign)
+    }
+
+    // Budget held back for active campaigns but not yet paid out
+    fn reserved_budget() -> u64 {
+        CAMPAIGNS.with(|campaigns| {
+            campaigns.borrow()
+                .values()
+                .filter(|campaign| campaign.status == CampaignStatus::Active)
+                .map(|campaign| campaign.budget - campaign.distributed)
+                .sum()
+        })
+    }
+
+    fn require_treasurer() -> Result<Principal, AirdropError> {
+        let caller = AccessControl::require(Role::Treasurer).map_err(|_| AirdropError::Unauthorized)?;
+        RETToken::get_metadata().ok_or(TokenError::NotInitialized)?;
+        Ok(caller)
+    }
+}
+
+fn merkle_leaf(principal: Principal, amount: u64) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(principal.as_slice());
+    hasher.update(amount.to_be_bytes());
+    hasher.finalize().to_vec()
+}
+
+// Pairs are hashed in sorted order, so proofs don't need to encode left/right positions
+fn verify_proof(root: &[u8], leaf: &[u8], proof: &[Vec<u8>]) -> bool {
+    let computed = proof.iter().fold(leaf.to_vec(), |node, sibling| {
+        let mut hasher = Sha256::new();
+        if node.as_slice() <= sibling.as_slice() {
+            hasher.update(&node);
+            hasher.update(sibling);
+        } else {
+            hasher.update(sibling);
+            hasher.update(&node);
+        }
+        hasher.finalize().to_vec()
+    });
+    computed == root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash_pair(a: &[u8], b: &[u8]) -> Vec<u8> {
+        let (first, second) = if a <= b { (a, b) } else { (b, a) };
+        let mut hasher = Sha256::new();
+        hasher.update(first);
+        hasher.update(second);
+        hasher.finalize().to_vec()
+    }
+
+    fn principal(id: u8) -> Principal {
+        Principal::from_slice(&[id; 29])
+    }
+
+    // Four-leaf tree: root = H(H(l0, l1), H(l2, l3))
+    fn tree() -> (Vec<Vec<u8>>, Vec<u8>) {
+        let leaves: Vec<Vec<u8>> = (1..=4).map(|id| merkle_leaf(principal(id), id as u64 * 100)).collect();
+        let left = hash_pair(&leaves[0], &leaves[1]);
+        let right = hash_pair(&leaves[2], &leaves[3]);
+        let root = hash_pair(&left, &right);
+        (leaves, root)
+    }
+
+    #[test]
+    fn test_verify_proof_accepts_every_leaf() {
+        let (leaves, root) = tree();
+        let left = hash_pair(&leaves[0], &leaves[1]);
+        let right = hash_pair(&leaves[2], &leaves[3]);
+        assert!(verify_proof(&root, &leaves[0], &[leaves[1].clone(), right.clone()]));
+        assert!(verify_proof(&root, &leaves[1], &[leaves[0].clone(), right.clone()]));
+        assert!(verify_proof(&root, &leaves[2], &[leaves[3].clone(), left.clone()]));
+        assert!(verify_proof(&root, &leaves[3], &[leaves[2].clone(), left]));
+    }
+
+    #[test]
+    fn test_verify_proof_rejects_wrong_amount_or_principal() {
+        let (leaves, root) = tree();
+        let right = hash_pair(&leaves[2], &leaves[3]);
+        let proof = vec![leaves[1].clone(), right];
+        assert!(!verify_proof(&root, &merkle_leaf(principal(1), 101), &proof));
+        assert!(!verify_proof(&root, &merkle_leaf(principal(9), 100), &proof));
+    }
+
+    #[test]
+    fn test_verify_proof_rejects_bad_proofs() {
+        let (leaves, root) = tree();
+        let right = hash_pair(&leaves[2], &leaves[3]);
+        // Truncated, reordered and tampered proofs
+        assert!(!verify_proof(&root, &leaves[0], &[leaves[1].clone()]));
+        assert!(!verify_proof(&root, &leaves[0], &[right.clone(), leaves[1].clone()]));
+        let mut tampered = right.clone();
+        tampered[0] ^= 1;
+        assert!(!verify_proof(&root, &leaves[0], &[leaves[1].clone(), tampered]));
+        // An inner node is not a leaf
+        let left = hash_pair(&leaves[0], &leaves[1]);
+        assert!(!verify_proof(&root, &left, &[leaves[0].clone(), right]));
+    }
+
+    #[test]
+    fn test_verify_proof_single_leaf_tree() {
+        let leaf = merkle_leaf(principal(1), 5);
+        assert!(verify_proof(&leaf, &leaf, &[]));
+        assert!(!verify_proof(&leaf, &merkle_leaf(principal(1), 6), &[]));
+    }
+
+    #[test]
+    fn test_merkle_leaf_binds_principal_and_amount() {
+        assert_eq!(merkle_leaf(principal(1), 100), merkle_leaf(principal(1), 100));
+        assert_ne!(merkle_leaf(principal(1), 100), merkle_leaf(principal(1), 200));
+        assert_ne!(merkle_leaf(principal(1), 100), merkle_leaf(principal(2), 100));
+        assert_eq!(merkle_leaf(principal(1), 100).len(), 32);
+    }
+}
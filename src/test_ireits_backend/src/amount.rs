@@ -0,0 +1,297 @@
+use candid::{CandidType, Deserialize};
+use std::cmp::Ordering;
+use std::fmt;
+
+use crate::types::TokenType;
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Currency {
+    USD,
+    RET,
+    ICP,
+}
+
+impl Currency {
+    pub fn decimals(self) -> u8 {
+        match self {
+            Currency::USD => 2,
+            Currency::RET | Currency::ICP => 8,
+        }
+    }
+
+    // The ledger token an amount in this currency is paid with, if any
+    pub fn token_type(self) -> Option<TokenType> {
+        match self {
+            Currency::USD => None,
+            Currency::RET => Some(TokenType::RET),
+            Currency::ICP => Some(TokenType::ICP),
+        }
+    }
+}
+
+/// Fixed-point money. Arithmetic is checked and only combines amounts of the same
+/// currency. Rounding is always explicit:
+/// - fees use `mul_div` with `HalfUp`;
+/// - payouts use `split_pro_rata`, which rounds every part down and hands the leftover
+///   units to the largest remainders, so the parts always sum to the total.
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Amount {
+    // Integer count of the smallest unit, 10^-decimals of the currency
+    pub units: u64,
+    pub currency: Currency,
+    pub decimals: u8,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum RoundingMode {
+    Down,
+    Up,
+    HalfUp,
+    // Banker's rounding: ties go to the even neighbour
+    HalfEven,
+}
+
+impl Amount {
+    pub fn new(units: u64, currency: Currency) -> Self {
+        Amount { units, currency, decimals: currency.decimals() }
+    }
+
+    pub fn zero(currency: Currency) -> Self {
+        Self::new(0, currency)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.units == 0
+    }
+
+    /// Amounts arrive over candid with their decimals spelled out; they must be the currency's.
+    pub fn validate(self) -> Result<Self, String> {
+        if self.decimals != self.currency.decimals() {
+            return Err(format!(
+                "{:?} amounts have {} decimals, not {}",
+                self.currency,
+                self.currency.decimals(),
+                self.decimals
+            ));
+        }
+        Ok(self)
+    }
+
+    pub fn validate_positive(self) -> Result<Self, String> {
+        let amount = self.validate()?;
+        if amount.is_zero() {
+            return Err("Amount must be greater than zero".to_string());
+        }
+        Ok(amount)
+    }
+
+    pub fn ensure_currency(self, currency: Currency) -> Result<Self, String> {
+        if self.currency != currency {
+            return Err(format!("Expected an amount in {:?}, got {:?}", currency, self.currency));
+        }
+        Ok(self)
+    }
+
+    pub fn checked_add(self, other: Amount) -> Result<Amount, String> {
+        self.ensure_same(other)?;
+        let units = self.units.checked_add(other.units).ok_or("Amount overflow")?;
+        Ok(Amount { units, ..self })
+    }
+
+    pub fn checked_sub(self, other: Amount) -> Result<Amount, String> {
+        self.ensure_same(other)?;
+        let units = self.units.checked_sub(other.units).ok_or("Amount underflow")?;
+        Ok(Amount { units, ..self })
+    }
+
+    pub fn checked_cmp(self, other: Amount) -> Result<Ordering, String> {
+        self.ensure_same(other)?;
+        Ok(self.units.cmp(&other.units))
+    }
+
+    /// `self * numerator / denominator`, e.g. a basis-point fee with a denominator of 10000.
+    pub fn mul_div(self, numerator: u64, denominator: u64, mode: RoundingMode) -> Result<Amount, String> {
+        if denominator == 0 {
+            return Err("Division by zero".to_string());
+        }
+        let units = div_round(self.units as u128 * numerator as u128, denominator as u128, mode);
+        let units = u64::try_from(units).map_err(|_| "Amount overflow".to_string())?;
+        Ok(Amount { units, ..self })
+    }
+
+    /// Splits the amount in proportion to `weights`; see `pro_rata` for the rounding rule.
+    pub fn split_pro_rata(self, weights: &[u64]) -> Result<Vec<Amount>, String> {
+        Ok(pro_rata(self.units, weights)?
+            .into_iter()
+            .map(|units| Amount { units, ..self })
+            .collect())
+    }
+
+    fn ensure_same(self, other: Amount) -> Result<(), String> {
+        if self.currency != other.currency || self.decimals != other.decimals {
+            return Err(format!("Currency mismatch: {:?} and {:?}", self.currency, other.currency));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match 10u64.checked_pow(self.decimals as u32) {
+            Some(scale) if self.decimals > 0 => write!(
+                f,
+                "{}.{:0width$} {:?}",
+                self.units / scale,
+                self.units % scale,
+                self.currency,
+                width = self.decimals as usize
+            ),
+            _ => write!(f, "{} {:?}", self.units, self.currency),
+        }
+    }
+}
+
+/// Largest-remainder split of `total` units by `weights`. Each part is rounded down, then
+/// the leftover units go one each to the parts with the largest remainders, earlier parts
+/// first on ties. The parts always sum to `total`.
+pub fn pro_rata(total: u64, weights: &[u64]) -> Result<Vec<u64>, String> {
+    let total_weight: u128 = weights.iter().map(|weight| *weight as u128).sum();
+    if total_weight == 0 {
+        return Err("Weights must not all be zero".to_string());
+    }
+
+    let mut parts: Vec<u64> = Vec::with_capacity(weights.len());
+    let mut remainders: Vec<(u128, usize)> = Vec::with_capacity(weights.len());
+    for (index, weight) in weights.iter().enumerate() {
+        let exact = total as u128 * *weight as u128;
+        parts.push((exact / total_weight) as u64);
+        remainders.push((exact % total_weight, index));
+    }
+
+    let leftover = total - parts.iter().sum::<u64>();
+    remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    for (_, index) in remainders.into_iter().take(leftover as usize) {
+        parts[index] += 1;
+    }
+    Ok(parts)
+}
+
+fn div_round(numerator: u128, denominator: u128, mode: RoundingMode) -> u128 {
+    let quotient = numerator / denominator;
+    let remainder = numerator % denominator;
+    if remainder == 0 {
+        return quotient;
+    }
+
+    let round_up = match mode {
+        RoundingMode::Down => false,
+        RoundingMode::Up => true,
+        RoundingMode::HalfUp => remainder * 2 >= denominator,
+        RoundingMode::HalfEven => {
+            remainder * 2 > denominator || (remainder * 2 == denominator && quotient % 2 == 1)
+        }
+    };
+    if round_up {
+        quotient + 1
+    } else {
+        quotient
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ret(units: u64) -> Amount {
+        Amount::new(units, Currency::RET)
+    }
+
+    #[test]
+    fn test_pro_rata_hands_leftover_to_largest_remainders() {
+        // 100 / 3 = 33.33 each; the single leftover unit goes to the first part on the tie
+        assert_eq!(pro_rata(100, &[1, 1, 1]).unwrap(), vec![34, 33, 33]);
+        // Exact parts 10 * 5/8 = 6.25 and 10 * 3/8 = 3.75; the leftover goes to the larger remainder
+        assert_eq!(pro_rata(10, &[5, 3]).unwrap(), vec![6, 4]);
+    }
+
+    #[test]
+    fn test_pro_rata_parts_sum_to_total() {
+        let weights = [3_333, 3_333, 3_334, 1, 0, 7];
+        for total in [0, 1, 7, 999, 1_000_003, u64::MAX] {
+            let parts = pro_rata(total, &weights).unwrap();
+            assert_eq!(parts.len(), weights.len());
+            assert_eq!(parts.iter().map(|part| *part as u128).sum::<u128>(), total as u128);
+        }
+    }
+
+    #[test]
+    fn test_pro_rata_zero_weights() {
+        assert!(pro_rata(100, &[0, 0]).is_err());
+        assert!(pro_rata(100, &[]).is_err());
+        // A zero weight among others never receives anything, leftover included
+        assert_eq!(pro_rata(5, &[0, 1, 1]).unwrap(), vec![0, 3, 2]);
+    }
+
+    #[test]
+    fn test_pro_rata_does_not_overflow() {
+        assert_eq!(pro_rata(u64::MAX, &[u64::MAX, u64::MAX]).unwrap(), vec![u64::MAX / 2 + 1, u64::MAX / 2]);
+        assert_eq!(pro_rata(u64::MAX, &[1, u64::MAX - 1]).unwrap(), vec![1, u64::MAX - 1]);
+    }
+
+    #[test]
+    fn test_split_pro_rata_keeps_currency() {
+        let parts = ret(10).split_pro_rata(&[1, 1, 1]).unwrap();
+        assert_eq!(parts, vec![ret(4), ret(3), ret(3)]);
+    }
+
+    #[test]
+    fn test_mul_div_rounding_modes() {
+        // 10.5 units
+        let half = (ret(21), 1, 2);
+        // 10.4 and 10.6 units
+        let below = (ret(52), 1, 5);
+        let above = (ret(53), 1, 5);
+        // 11.5 units
+        let odd_half = (ret(23), 1, 2);
+
+        let cases = [
+            (RoundingMode::Down, [10, 10, 10, 11]),
+            (RoundingMode::Up, [11, 11, 11, 12]),
+            (RoundingMode::HalfUp, [11, 10, 11, 12]),
+            (RoundingMode::HalfEven, [10, 10, 11, 12]),
+        ];
+        for (mode, expected) in cases {
+            let actual: Vec<u64> = [half, below, above, odd_half].iter()
+                .map(|(amount, numerator, denominator)| amount.mul_div(*numerator, *denominator, mode).unwrap().units)
+                .collect();
+            assert_eq!(actual, expected, "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn test_mul_div_exact_results_ignore_mode() {
+        for mode in [RoundingMode::Down, RoundingMode::Up, RoundingMode::HalfUp, RoundingMode::HalfEven] {
+            assert_eq!(ret(10_000).mul_div(100, 10_000, mode).unwrap(), ret(100));
+            assert_eq!(ret(0).mul_div(7, 3, mode).unwrap(), ret(0));
+        }
+    }
+
+    #[test]
+    fn test_mul_div_overflow_and_division_by_zero() {
+        // The intermediate product is widened, so only a result above u64::MAX fails
+        assert_eq!(ret(u64::MAX).mul_div(u64::MAX, u64::MAX, RoundingMode::Down).unwrap(), ret(u64::MAX));
+        assert!(ret(u64::MAX).mul_div(2, 1, RoundingMode::Down).is_err());
+        assert!(ret(u64::MAX).mul_div(u64::MAX, u64::MAX - 1, RoundingMode::Up).is_err());
+        assert!(ret(1).mul_div(1, 0, RoundingMode::Down).is_err());
+    }
+
+    #[test]
+    fn test_checked_arithmetic_rejects_mixed_currencies() {
+        let usd = Amount::new(1, Currency::USD);
+        assert!(ret(1).checked_add(usd).is_err());
+        assert!(ret(1).checked_cmp(usd).is_err());
+        assert!(ret(u64::MAX).checked_add(ret(1)).is_err());
+        assert!(ret(0).checked_sub(ret(1)).is_err());
+    }
+}
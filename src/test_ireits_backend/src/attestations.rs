@@ -0,0 +1,157 @@
+use candid::{CandidType, Deserialize, Principal};
+use ic_cdk::api::time;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::access_control::{AccessControl, Role};
+use crate::document_versions::DocumentVersions;
+use crate::{Document, DocumentType, Property, PropertyError};
+
+// Documents that must be verified before a property can be tokenized
+pub const TOKENIZATION_DOCUMENTS: [DocumentType; 3] = [DocumentType::Deed, DocumentType::Title, DocumentType::Inspection];
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum AttestationVerdict {
+    Approved,
+    Rejected,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Attestation {
+    pub id: u64,
+    pub property_id: u64,
+    pub document_id: u64,
+    // Hash of the document as attested
+    pub document_hash: String,
+    pub verifier: Principal,
+    pub verdict: AttestationVerdict,
+    pub notes: Option<String>,
+    pub timestamp: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum VerificationStatus {
+    Pending { approvals: u8, required: u8 },
+    Verified,
+    Rejected,
+}
+
+thread_local! {
+    static ATTESTATIONS: RefCell<HashMap<(u64, u64), Vec<Attestation>>> = RefCell::new(HashMap::new());
+    static ATTESTATION_COUNTER: RefCell<u64> = const { RefCell::new(0) };
+    // Distinct approvals needed per document type; one when unset
+    static REQUIRED_ATTESTATIONS: RefCell<HashMap<DocumentType, u8>> = RefCell::new(HashMap::new());
+}
+
+/// Verifier attestations on property documents. A document is verified once enough
+/// distinct verifiers approve it and none has rejected it.
+pub struct DocumentAttestations;
+
+impl DocumentAttestations {
+    pub fn attest(
+        property: &Property,
+        document_id: u64,
+        verdict: AttestationVerdict,
+        notes: Option<String>,
+    ) -> Result<u64, PropertyError> {
+        let verifier = AccessControl::require(Role::PropertyVerifier).map_err(|_| PropertyError::Unauthorized)?;
+        let document = property.documents.iter()
+            .find(|document| document.id == document_id)
+            .ok_or(PropertyError::DocumentNotFound { property_id: property.id, document_id })?;
+        DocumentVersions::ensure_current(document, time())?;
+
+        let has_notes = notes.as_deref().is_some_and(|notes| !notes.trim().is_empty());
+        if verdict == AttestationVerdict::Rejected && !has_notes {
+            return Err(PropertyError::ReasonRequired);
+        }
+
+        let key = (property.id, document_id);
+        let already_attested = ATTESTATIONS.with(|attestations| {
+            attestations.borrow()
+                .get(&key)
+                .is_some_and(|existing| existing.iter().any(|attestation| attestation.verifier == verifier))
+        });
+        if already_attested {
+            return Err(PropertyError::AlreadyAttested { document_id });
+        }
+
+        let id = ATTESTATION_COUNTER.with(|counter| {
+            let mut counter = counter.borrow_mut();
+            *counter += 1;
+            *counter
+        });
+
+        ATTESTATIONS.with(|attestations| {
+            attestations.borrow_mut().entry(key).or_default().push(Attestation {
+                id,
+                property_id: property.id,
+                document_id,
+                document_hash: document.hash.clone(),
+                verifier,
+                verdict,
+                notes,
+                timestamp: time(),
+            });
+        });
+
+        Ok(id)
+    }
+
+    pub fn get_attestations(property_id: u64, document_id: u64) -> Vec<Attestation> {
+        ATTESTATIONS.with(|attestations| {
+            attestations.borrow().get(&(property_id, document_id)).cloned().unwrap_or_default()
+        })
+    }
+
+    pub fn status(property_id: u64, document: &Document) -> VerificationStatus {
+        let attestations = Self::get_attestations(property_id, document.id);
+        if attestations.iter().any(|attestation| attestation.verdict == AttestationVerdict::Rejected) {
+            return VerificationStatus::Rejected;
+        }
+
+        // No rejections, so every attestation is an approval
+        let approvals = attestations.len().min(u8::MAX as usize) as u8;
+        let required = Self::required_attestations(document.doc_type);
+        if approvals >= required {
+            VerificationStatus::Verified
+        } else {
+            VerificationStatus::Pending { approvals, required }
+        }
+    }
+
+    /// Fails unless every listed document type has a current, verified document.
+    pub fn ensure_verified(property: &Property, doc_types: &[DocumentType]) -> Result<(), PropertyError> {
+        let now = time();
+        let missing: Vec<DocumentType> = doc_types.iter()
+            .filter(|doc_type| {
+                !property.documents.iter().any(|document| {
+                    document.doc_type == **doc_type
+                        && DocumentVersions::is_current(document, now)
+                        && Self::status(property.id, document) == VerificationStatus::Verified
+                })
+            })
+            .copied()
+            .collect();
+
+        if !missing.is_empty() {
+            return Err(PropertyError::UnverifiedDocuments { doc_types: missing });
+        }
+        Ok(())
+    }
+
+    pub fn required_attestations(doc_type: DocumentType) -> u8 {
+        REQUIRED_ATTESTATIONS.with(|required| required.borrow().get(&doc_type).copied().unwrap_or(1))
+    }
+
+    pub fn set_required_attestations(doc_type: DocumentType, count: u8) -> Result<bool, PropertyError> {
+        AccessControl::require(Role::Admin).map_err(|_| PropertyError::Unauthorized)?;
+        if count == 0 {
+            return Err(PropertyError::InvalidInput { reason: "At least one attestation is required".to_string() });
+        }
+
+        REQUIRED_ATTESTATIONS.with(|required| {
+            required.borrow_mut().insert(doc_type, count);
+        });
+        Ok(true)
+    }
+}
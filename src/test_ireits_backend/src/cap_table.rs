@@ -0,0 +1,473 @@
+use candid::{CandidType, Deserialize, Principal};
+use ic_cdk::api::time;
+use ic_stable_structures::storable::Blob;
+use ic_stable_structures::StableBTreeMap;
+use sha2::{Digest, Sha256};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+use crate::access_control::{AccessControl, Role};
+use crate::amount::Amount;
+use crate::memory::{self, Memory, CAP_TABLE_LINKS};
+use crate::{Property, PropertyError};
+
+const LINK_TTL: u64 = 15 * 60 * 1_000_000_000; // 15 minutes
+
+// Hex SHA-256 link token
+type LinkKey = Blob<64>;
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum ShareMovementKind {
+    // Fractionalization; replaces the whole register
+    Issuance,
+    Transfer,
+    Sale { price: Amount }, // In RET
+    Forced,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ShareMovement {
+    pub property_id: u64,
+    pub kind: ShareMovementKind,
+    pub from: Option<Principal>,
+    pub to: Principal,
+    pub amount: u16,
+    pub timestamp: u64,
+    // Issuance batch, numbered from 1 per property; None for other movements
+    pub issuance: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct CapTableEntry {
+    pub holder: Principal,
+    pub shares: u64, // Basis points
+    // Of the shares outstanding, rounded down
+    pub ownership_bps: u64,
+    // Earliest lot still held
+    pub acquired_at: u64,
+    // RET paid for the shares still held; unpriced transfers carry the sender's basis over
+    pub cost_basis: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct CapTable {
+    pub property_id: u64,
+    pub as_of: u64,
+    pub total_shares: u64,
+    pub holder_count: u64,
+    pub entries: Vec<CapTableEntry>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+// Shares acquired in one movement, consumed first-in first-out
+#[derive(Clone, Debug)]
+struct Lot {
+    amount: u64,
+    acquired_at: u64,
+    cost_basis: u64,
+}
+
+thread_local! {
+    // Every share movement per property, in order; the register is rebuilt from these
+    static MOVEMENTS: RefCell<HashMap<u64, Vec<ShareMovement>>> = RefCell::new(HashMap::new());
+    // Link token to (property id, expiry) for HTTP exports
+    static LINKS: RefCell<StableBTreeMap<LinkKey, (u64, u64), Memory>> = RefCell::new(
+        StableBTreeMap::init(memory::get(CAP_TABLE_LINKS))
+    );
+}
+
+/// Shareholder register per property, reconstructed from the share movement journal so
+/// that any past date can be queried. Only the property owner, its manager and compliance
+/// officers can read it.
+pub struct CapTables;
+
+impl CapTables {
+    pub fn ensure_can_view(property: &Property, account: Principal) -> Result<(), PropertyError> {
+        let allowed = property.owner == account
+            || property.manager == Some(account)
+            || AccessControl::has_role(account, Role::ComplianceOfficer);
+        if !allowed {
+            return Err(PropertyError::Unauthorized);
+        }
+        Ok(())
+    }
+
+    /// Issues a short-lived link token for the HTTP export, where requests are anonymous.
+    /// `seed` must come from `raw_rand`.
+    pub fn create_link(property: &Property, account: Principal, seed: Vec<u8>) -> Result<String, PropertyError> {
+        Self::ensure_can_view(property, account)?;
+
+        let now = time();
+        let token: String = Sha256::digest(&seed).iter().map(|byte| format!("{:02x}", byte)).collect();
+        LINKS.with(|links| {
+            let mut links = links.borrow_mut();
+            let expired: Vec<LinkKey> = links.iter()
+                .filter(|(_, (_, expires_at))| *expires_at <= now)
+                .map(|(key, _)| key)
+                .collect();
+            for key in expired {
+                links.remove(&key);
+            }
+            links.insert(Self::link_key(&token).expect("hex digests fit the link key"), (property.id, now + LINK_TTL));
+        });
+
+        Ok(token)
+    }
+
+    /// Checks an anonymous HTTP export against a live link token for the property.
+    pub fn http_readable(property_id: u64, token: Option<&str>) -> bool {
+        let now = time();
+        token.and_then(Self::link_key).is_some_and(|key| {
+            LINKS.with(|links| {
+                links.borrow().get(&key).is_some_and(|(linked, expires_at)| linked == property_id && expires_at > now)
+            })
+        })
+    }
+
+    fn link_key(token: &str) -> Option<LinkKey> {
+        LinkKey::try_from(token.as_bytes()).ok()
+    }
+
+    pub(crate) fn record_issuance(property_id: u64, shares: &[(Principal, u16)]) {
+        let timestamp = time();
+        MOVEMENTS.with(|movements| {
+            let mut movements = movements.borrow_mut();
+            let journal = movements.entry(property_id).or_default();
+            let issuance = journal.iter()
+                .rev()
+                .find_map(|movement| movement.issuance)
+                .map_or(1, |last| last + 1);
+            for (holder, amount) in shares {
+                journal.push(ShareMovement {
+                    property_id,
+                    kind: ShareMovementKind::Issuance,
+                    from: None,
+                    to: *holder,
+                    amount: *amount,
+                    timestamp,
+                    issuance: Some(issuance),
+                });
+            }
+        });
+    }
+
+    pub(crate) fn record_movement(property_id: u64, kind: ShareMovementKind, from: Principal, to: Principal, amount: u16) {
+        MOVEMENTS.with(|movements| {
+            movements.borrow_mut()
+                .entry(property_id)
+                .or_default()
+                .push(ShareMovement {
+                    property_id,
+                    kind,
+                    from: Some(from),
+                    to,
+                    amount,
+                    timestamp: time(),
+                    issuance: None,
+                });
+        });
+    }
+
+    pub fn get_movements(property_id: u64, start: u64, length: u64) -> Vec<ShareMovement> {
+        MOVEMENTS.with(|movements| {
+            movements.borrow()
+                .get(&property_id)
+                .map(|journal| {
+                    journal.iter()
+                        .skip(start as usize)
+                        .take(length as usize)
+                        .cloned()
+                        .collect()
+                })
+                .unwrap_or_default()
+        })
+    }
+
+    /// Register as of `as_of` (now if None), largest holders first, paged.
+    pub fn get_cap_table(property_id: u64, as_of: Option<u64>, start: u64, length: u64) -> CapTable {
+        let mut table = Self::build(property_id, as_of.unwrap_or_else(time));
+        table.entries = table.entries.into_iter()
+            .skip(start as usize)
+            .take(length as usize)
+            .collect();
+        table
+    }
+
+    pub fn export(property_id: u64, as_of: Option<u64>, format: ExportFormat) -> String {
+        let table = Self::build(property_id, as_of.unwrap_or_else(time));
+        match format {
+            ExportFormat::Csv => Self::to_csv(&table),
+            ExportFormat::Json => Self::to_json(&table),
+        }
+    }
+
+    fn build(property_id: u64, as_of: u64) -> CapTable {
+        let journal = MOVEMENTS.with(|movements| {
+            movements.borrow().get(&property_id).cloned().unwrap_or_default()
+        });
+
+        let mut lots: BTreeMap<Principal, VecDeque<Lot>> = BTreeMap::new();
+        let mut last_issuance = None;
+        for movement in journal.iter().take_while(|movement| movement.timestamp <= as_of) {
+            if movement.kind == ShareMovementKind::Issuance {
+                // A new issuance batch replaces the previous register
+                if last_issuance != movement.issuance {
+                    lots.clear();
+                    last_issuance = movement.issuance;
+                }
+                lots.entry(movement.to).or_default().push_back(Lot {
+                    amount: movement.amount as u64,
+                    acquired_at: movement.timestamp,
+                    cost_basis: 0,
+                });
+                continue;
+            }
+
+            let from = match movement.from {
+                Some(from) => from,
+                None => continue,
+            };
+            let carried = Self::take_lots(lots.entry(from).or_default(), movement.amount as u64);
+            if lots.get(&from).is_some_and(|held| held.is_empty()) {
+                lots.remove(&from);
+            }
+
+            let cost_basis = match movement.kind {
+                ShareMovementKind::Sale { price } => price.units,
+                _ => carried,
+            };
+            lots.entry(movement.to).or_default().push_back(Lot {
+                amount: movement.amount as u64,
+                acquired_at: movement.timestamp,
+                cost_basis,
+            });
+        }
+
+        let total_shares: u64 = lots.values().flatten().map(|lot| lot.amount).sum();
+        let mut entries: Vec<CapTableEntry> = lots.into_iter()
+            .map(|(holder, held)| {
+                let shares: u64 = held.iter().map(|lot| lot.amount).sum();
+                CapTableEntry {
+                    holder,
+                    shares,
+                    ownership_bps: (shares * 10_000).checked_div(total_shares).unwrap_or(0),
+                    acquired_at: held.iter().map(|lot| lot.acquired_at).min().unwrap_or(0),
+                    cost_basis: held.iter().map(|lot| lot.cost_basis).sum(),
+                }
+            })
+            .collect();
+        entries.sort_by(|a, b| b.shares.cmp(&a.shares).then(a.acquired_at.cmp(&b.acquired_at)));
+
+        CapTable {
+            property_id,
+            as_of,
+            total_shares,
+            holder_count: entries.len() as u64,
+            entries,
+        }
+    }
+
+    // Removes `amount` from the oldest lots and returns the cost basis that went with it
+    fn take_lots(held: &mut VecDeque<Lot>, mut amount: u64) -> u64 {
+        let mut cost_basis = 0;
+        while amount > 0 {
+            let lot = match held.front_mut() {
+                Some(lot) => lot,
+                None => break,
+            };
+            let taken = amount.min(lot.amount);
+            let basis = (lot.cost_basis as u128 * taken as u128 / lot.amount as u128) as u64;
+            cost_basis += basis;
+            lot.cost_basis -= basis;
+            lot.amount -= taken;
+            amount -= taken;
+            if lot.amount == 0 {
+                held.pop_front();
+            }
+        }
+        cost_basis
+    }
+
+    fn to_csv(table: &CapTable) -> String {
+        let mut csv = String::from("holder,shares_bps,ownership_bps,acquired_at,cost_basis\n");
+        for entry in &table.entries {
+            csv.push_str(&format!(
+                "{},{},{},{},{}\n",
+                entry.holder, entry.shares, entry.ownership_bps, entry.acquired_at, entry.cost_basis
+            ));
+        }
+        csv
+    }
+
+    fn to_json(table: &CapTable) -> String {
+        let entries: Vec<serde_json::Value> = table.entries.iter()
+            .map(|entry| serde_json::json!({
+                "holder": entry.holder.to_text(),
+                "shares_bps": entry.shares,
+                "ownership_bps": entry.ownership_bps,
+                "acquired_at": entry.acquired_at,
+                "cost_basis": entry.cost_basis,
+            }))
+            .collect();
+
+        serde_json::json!({
+            "property_id": table.property_id,
+            "as_of": table.as_of,
+            "total_shares": table.total_shares,
+            "holder_count": table.holder_count,
+            "entries": entries,
+        })
+        .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amount::Currency;
+
+    const PROPERTY: u64 = 1;
+
+    fn holder(id: u8) -> Principal {
+        Principal::from_slice(&[id; 29])
+    }
+
+    fn lot(amount: u64, acquired_at: u64, cost_basis: u64) -> Lot {
+        Lot { amount, acquired_at, cost_basis }
+    }
+
+    fn push(kind: ShareMovementKind, from: Option<Principal>, to: Principal, amount: u16, timestamp: u64) {
+        MOVEMENTS.with(|movements| {
+            movements.borrow_mut().entry(PROPERTY).or_default().push(ShareMovement {
+                property_id: PROPERTY,
+                kind,
+                from,
+                to,
+                amount,
+                timestamp,
+                issuance: None,
+            });
+        });
+    }
+
+    fn issue(batch: u64, to: Principal, amount: u16, timestamp: u64) {
+        MOVEMENTS.with(|movements| {
+            movements.borrow_mut().entry(PROPERTY).or_default().push(ShareMovement {
+                property_id: PROPERTY,
+                kind: ShareMovementKind::Issuance,
+                from: None,
+                to,
+                amount,
+                timestamp,
+                issuance: Some(batch),
+            });
+        });
+    }
+
+    fn sale(units: u64) -> ShareMovementKind {
+        ShareMovementKind::Sale { price: Amount::new(units, Currency::RET) }
+    }
+
+    fn entry(table: &CapTable, account: Principal) -> Option<&CapTableEntry> {
+        table.entries.iter().find(|entry| entry.holder == account)
+    }
+
+    #[test]
+    fn test_take_lots_consumes_oldest_first() {
+        let mut held = VecDeque::from(vec![lot(100, 1, 1_000), lot(100, 2, 3_000)]);
+        // All of the first lot and a quarter of the second
+        assert_eq!(CapTables::take_lots(&mut held, 125), 1_000 + 750);
+        assert_eq!(held.len(), 1);
+        assert_eq!((held[0].amount, held[0].acquired_at, held[0].cost_basis), (75, 2, 2_250));
+    }
+
+    #[test]
+    fn test_take_lots_keeps_total_basis_when_rounding() {
+        let mut held = VecDeque::from(vec![lot(3, 1, 10)]);
+        let taken: u64 = (0..3).map(|_| CapTables::take_lots(&mut held, 1)).sum();
+        // Rounded-down shares leave the remainder on the last unit
+        assert_eq!(taken, 10);
+        assert!(held.is_empty());
+    }
+
+    #[test]
+    fn test_take_lots_stops_when_empty() {
+        let mut held = VecDeque::from(vec![lot(10, 1, 500)]);
+        assert_eq!(CapTables::take_lots(&mut held, 50), 500);
+        assert!(held.is_empty());
+        assert_eq!(CapTables::take_lots(&mut held, 1), 0);
+    }
+
+    #[test]
+    fn test_build_carries_fifo_basis_through_transfers() {
+        let (alice, bob, carol) = (holder(1), holder(2), holder(3));
+        issue(1, alice, 10_000, 1);
+        // Bob buys twice at different prices, then gives part of his holding to Carol
+        push(sale(2_000), Some(alice), bob, 1_000, 2);
+        push(sale(6_000), Some(alice), bob, 2_000, 3);
+        push(ShareMovementKind::Transfer, Some(bob), carol, 1_500, 4);
+
+        let table = CapTables::build(PROPERTY, u64::MAX);
+        assert_eq!(table.total_shares, 10_000);
+        assert_eq!(table.holder_count, 3);
+
+        // Carol gets the whole first lot and a quarter of the second, with their basis
+        let carol_entry = entry(&table, carol).unwrap();
+        assert_eq!((carol_entry.shares, carol_entry.cost_basis, carol_entry.acquired_at), (1_500, 2_000 + 1_500, 4));
+        let bob_entry = entry(&table, bob).unwrap();
+        assert_eq!((bob_entry.shares, bob_entry.cost_basis, bob_entry.acquired_at), (1_500, 4_500, 3));
+        let alice_entry = entry(&table, alice).unwrap();
+        assert_eq!((alice_entry.shares, alice_entry.cost_basis, alice_entry.ownership_bps), (7_000, 0, 7_000));
+    }
+
+    #[test]
+    fn test_build_as_of_and_reissuance() {
+        let (alice, bob) = (holder(1), holder(2));
+        issue(1, alice, 10_000, 1);
+        push(sale(5_000), Some(alice), bob, 5_000, 2);
+        // A later issuance batch replaces the register
+        issue(2, alice, 6_000, 3);
+        issue(2, bob, 4_000, 3);
+
+        let before = CapTables::build(PROPERTY, 2);
+        assert_eq!(entry(&before, bob).unwrap().cost_basis, 5_000);
+        assert_eq!(entry(&before, alice).unwrap().shares, 5_000);
+
+        let after = CapTables::build(PROPERTY, 3);
+        assert_eq!(after.total_shares, 10_000);
+        assert_eq!(entry(&after, bob).unwrap().cost_basis, 0);
+        assert_eq!(entry(&after, alice).unwrap().shares, 6_000);
+        assert_eq!(after.entries[0].holder, alice);
+    }
+
+    #[test]
+    fn test_build_separates_issuances_in_the_same_round() {
+        let (alice, bob) = (holder(1), holder(2));
+        // Both batches share a timestamp; only the sequence number tells them apart
+        issue(1, alice, 10_000, 5);
+        issue(2, alice, 3_000, 5);
+        issue(2, bob, 7_000, 5);
+
+        let table = CapTables::build(PROPERTY, u64::MAX);
+        assert_eq!(table.total_shares, 10_000);
+        assert_eq!(entry(&table, alice).unwrap().shares, 3_000);
+        assert_eq!(entry(&table, bob).unwrap().shares, 7_000);
+    }
+
+    #[test]
+    fn test_build_drops_holders_who_sold_out() {
+        let (alice, bob) = (holder(1), holder(2));
+        issue(1, alice, 10_000, 1);
+        push(sale(9_000), Some(alice), bob, 10_000, 2);
+
+        let table = CapTables::build(PROPERTY, u64::MAX);
+        assert_eq!(table.holder_count, 1);
+        assert!(entry(&table, alice).is_none());
+        assert_eq!(entry(&table, bob).unwrap().ownership_bps, 10_000);
+    }
+}
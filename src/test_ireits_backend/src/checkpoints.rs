@@ -0,0 +1,186 @@
+use candid::{CandidType, Deserialize, Principal};
+use ic_cdk::api::time;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::access_control::{AccessControl, Role};
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Checkpoint {
+    pub timestamp: u64,
+    pub balance: u64,
+    pub staked: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct SupplyCheckpoint {
+    pub timestamp: u64,
+    pub total_supply: u64,
+    pub total_staked: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Snapshot {
+    pub id: u64,
+    pub name: String,
+    pub timestamp: u64,
+    pub created_by: Principal,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum SnapshotError {
+    Unauthorized,
+    SnapshotNotFound { snapshot_id: u64 },
+}
+
+impl std::fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::Unauthorized => write!(f, "Not authorized"),
+            SnapshotError::SnapshotNotFound { snapshot_id } => write!(f, "Snapshot {} not found", snapshot_id),
+        }
+    }
+}
+
+thread_local! {
+    // Per-account history, ordered by timestamp with at most one entry per timestamp
+    static ACCOUNT_CHECKPOINTS: RefCell<HashMap<Principal, Vec<Checkpoint>>> = RefCell::new(HashMap::new());
+    static SUPPLY_CHECKPOINTS: RefCell<Vec<SupplyCheckpoint>> = const { RefCell::new(Vec::new()) };
+    static SNAPSHOTS: RefCell<BTreeMap<u64, Snapshot>> = const { RefCell::new(BTreeMap::new()) };
+    static SNAPSHOT_COUNTER: RefCell<u64> = const { RefCell::new(0) };
+}
+
+/// Checkpointed RET balance history in the style of ERC20Votes: every balance change
+/// appends a checkpoint, so historical values are a binary search away.
+pub struct Checkpoints;
+
+impl Checkpoints {
+    pub(crate) fn write_account(account: Principal, balance: u64, staked: u64) {
+        let timestamp = time();
+        ACCOUNT_CHECKPOINTS.with(|checkpoints| {
+            let mut checkpoints = checkpoints.borrow_mut();
+            let history = checkpoints.entry(account).or_default();
+            match history.last_mut() {
+                Some(last) if last.timestamp == timestamp => {
+                    last.balance = balance;
+                    last.staked = staked;
+                }
+                _ => history.push(Checkpoint {
+                    timestamp,
+                    balance,
+                    staked,
+                }),
+            }
+        });
+    }
+
+    pub(crate) fn write_supply(total_supply: u64, total_staked: u64) {
+        let timestamp = time();
+        SUPPLY_CHECKPOINTS.with(|checkpoints| {
+            let mut checkpoints = checkpoints.borrow_mut();
+            match checkpoints.last_mut() {
+                Some(last) if last.timestamp == timestamp => {
+                    last.total_supply = total_supply;
+                    last.total_staked = total_staked;
+                }
+                _ => checkpoints.push(SupplyCheckpoint {
+                    timestamp,
+                    total_supply,
+                    total_staked,
+                }),
+            }
+        });
+    }
+
+    fn account_at(account: Principal, timestamp: u64) -> Option<Checkpoint> {
+        ACCOUNT_CHECKPOINTS.with(|checkpoints| {
+            let checkpoints = checkpoints.borrow();
+            let history = checkpoints.get(&account)?;
+            let index = history.partition_point(|checkpoint| checkpoint.timestamp <= timestamp);
+            index.checked_sub(1).map(|index| history[index].clone())
+        })
+    }
+
+    fn supply_at(timestamp: u64) -> Option<SupplyCheckpoint> {
+        SUPPLY_CHECKPOINTS.with(|checkpoints| {
+            let checkpoints = checkpoints.borrow();
+            let index = checkpoints.partition_point(|checkpoint| checkpoint.timestamp <= timestamp);
+            index.checked_sub(1).map(|index| checkpoints[index].clone())
+        })
+    }
+
+    /// Liquid balance held by `account` at `timestamp`.
+    pub fn balance_at(account: Principal, timestamp: u64) -> u64 {
+        Self::account_at(account, timestamp).map(|checkpoint| checkpoint.balance).unwrap_or(0)
+    }
+
+    pub fn staked_at(account: Principal, timestamp: u64) -> u64 {
+        Self::account_at(account, timestamp).map(|checkpoint| checkpoint.staked).unwrap_or(0)
+    }
+
+    pub fn total_supply_at(timestamp: u64) -> u64 {
+        Self::supply_at(timestamp).map(|checkpoint| checkpoint.total_supply).unwrap_or(0)
+    }
+
+    pub fn total_staked_at(timestamp: u64) -> u64 {
+        Self::supply_at(timestamp).map(|checkpoint| checkpoint.total_staked).unwrap_or(0)
+    }
+
+    pub fn get_account_history(account: Principal) -> Vec<Checkpoint> {
+        ACCOUNT_CHECKPOINTS.with(|checkpoints| {
+            checkpoints.borrow().get(&account).cloned().unwrap_or_default()
+        })
+    }
+
+    pub fn take_snapshot(name: String) -> Result<u64, SnapshotError> {
+        let caller = AccessControl::require(Role::Admin).map_err(|_| SnapshotError::Unauthorized)?;
+
+        Ok(Self::create_snapshot(name, caller))
+    }
+
+    /// Names the current point in time so other modules can refer back to it.
+    pub(crate) fn create_snapshot(name: String, created_by: Principal) -> u64 {
+        let id = SNAPSHOT_COUNTER.with(|counter| {
+            let mut counter = counter.borrow_mut();
+            *counter += 1;
+            *counter
+        });
+
+        SNAPSHOTS.with(|snapshots| {
+            snapshots.borrow_mut().insert(id, Snapshot {
+                id,
+                name,
+                timestamp: time(),
+                created_by,
+            });
+        });
+
+        id
+    }
+
+    pub fn get_snapshot(snapshot_id: u64) -> Option<Snapshot> {
+        SNAPSHOTS.with(|snapshots| snapshots.borrow().get(&snapshot_id).cloned())
+    }
+
+    pub fn get_snapshots() -> Vec<Snapshot> {
+        SNAPSHOTS.with(|snapshots| snapshots.borrow().values().cloned().collect())
+    }
+
+    pub fn balance_at_snapshot(snapshot_id: u64, account: Principal) -> Result<u64, SnapshotError> {
+        let snapshot = Self::get_snapshot(snapshot_id).ok_or(SnapshotError::SnapshotNotFound { snapshot_id })?;
+        Ok(Self::balance_at(account, snapshot.timestamp))
+    }
+
+    /// Liquid plus staked balance at the snapshot.
+    pub fn holdings_at_snapshot(snapshot_id: u64, account: Principal) -> Result<u64, SnapshotError> {
+        let snapshot = Self::get_snapshot(snapshot_id).ok_or(SnapshotError::SnapshotNotFound { snapshot_id })?;
+        Ok(Self::account_at(account, snapshot.timestamp)
+            .map(|checkpoint| checkpoint.balance + checkpoint.staked)
+            .unwrap_or(0))
+    }
+
+    pub fn total_supply_at_snapshot(snapshot_id: u64) -> Result<u64, SnapshotError> {
+        let snapshot = Self::get_snapshot(snapshot_id).ok_or(SnapshotError::SnapshotNotFound { snapshot_id })?;
+        Ok(Self::total_supply_at(snapshot.timestamp))
+    }
+}
@@ -0,0 +1,282 @@
+use candid::{CandidType, Deserialize, Principal};
+use ic_cdk::api::time;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::access_control::{AccessControl, Role};
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum KycStatus {
+    Pending,
+    Verified,
+    Rejected,
+    Revoked,
+}
+
+// Ordered from least to most qualified, so levels can be compared
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AccreditationLevel {
+    Retail,
+    Accredited,
+    Qualified,
+    Institutional,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct InvestorRecord {
+    pub principal: Principal,
+    pub kyc_status: KycStatus,
+    // ISO 3166-1 alpha-2 country code
+    pub jurisdiction: String,
+    pub accreditation: AccreditationLevel,
+    pub expires_at: u64,
+    pub updated_by: Principal,
+    pub updated_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ComplianceAction {
+    RetTransfer,
+    Icrc7Transfer,
+    Purchase,
+    ShareAllocation,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum ComplianceRule {
+    RequireKyc,
+    BlockedJurisdictions { jurisdictions: Vec<String> },
+    MinAccreditation { level: AccreditationLevel },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct RuleConfig {
+    pub id: u64,
+    pub rule: ComplianceRule,
+    pub actions: Vec<ComplianceAction>,
+    pub enabled: bool,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum ComplianceRejection {
+    NotRegistered { principal: Principal },
+    KycNotVerified { principal: Principal, status: KycStatus },
+    KycExpired { principal: Principal, expired_at: u64 },
+    JurisdictionBlocked { principal: Principal, jurisdiction: String },
+    InsufficientAccreditation {
+        principal: Principal,
+        required: AccreditationLevel,
+        actual: AccreditationLevel,
+    },
+}
+
+impl std::fmt::Display for ComplianceRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ComplianceRejection::NotRegistered { principal } => {
+                write!(f, "{} is not registered with compliance", principal)
+            }
+            ComplianceRejection::KycNotVerified { principal, status } => {
+                write!(f, "KYC for {} is {:?}", principal, status)
+            }
+            ComplianceRejection::KycExpired { principal, .. } => write!(f, "KYC for {} has expired", principal),
+            ComplianceRejection::JurisdictionBlocked { principal, jurisdiction } => {
+                write!(f, "Jurisdiction {} of {} is blocked", jurisdiction, principal)
+            }
+            ComplianceRejection::InsufficientAccreditation { principal, required, .. } => {
+                write!(f, "{} must be at least {:?}", principal, required)
+            }
+        }
+    }
+}
+
+// Failures of the registry and rule administration endpoints
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum ComplianceError {
+    Unauthorized,
+    InvalidJurisdiction { jurisdiction: String },
+    NoActions,
+    RuleNotFound { rule_id: u64 },
+}
+
+impl std::fmt::Display for ComplianceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ComplianceError::Unauthorized => write!(f, "Not authorized"),
+            ComplianceError::InvalidJurisdiction { jurisdiction } => {
+                write!(f, "Jurisdiction {:?} is not a two-letter country code", jurisdiction)
+            }
+            ComplianceError::NoActions => write!(f, "A rule must apply to at least one action"),
+            ComplianceError::RuleNotFound { rule_id } => write!(f, "Rule {} not found", rule_id),
+        }
+    }
+}
+
+thread_local! {
+    static INVESTORS: RefCell<HashMap<Principal, InvestorRecord>> = RefCell::new(HashMap::new());
+    // Evaluated in id order; the first failing rule is reported
+    static RULES: RefCell<BTreeMap<u64, RuleConfig>> = const { RefCell::new(BTreeMap::new()) };
+    static RULE_COUNTER: RefCell<u64> = const { RefCell::new(0) };
+}
+
+/// KYC/AML registry and rule engine. With no rules configured every action passes,
+/// so deployments opt in to enforcement by adding rules.
+pub struct Compliance;
+
+impl Compliance {
+    pub fn set_investor(
+        principal: Principal,
+        kyc_status: KycStatus,
+        jurisdiction: String,
+        accreditation: AccreditationLevel,
+        expires_at: u64,
+    ) -> Result<bool, ComplianceError> {
+        let caller = AccessControl::require(Role::ComplianceOfficer).map_err(|_| ComplianceError::Unauthorized)?;
+
+        let jurisdiction = jurisdiction.trim().to_uppercase();
+        if jurisdiction.len() != 2 || !jurisdiction.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(ComplianceError::InvalidJurisdiction { jurisdiction });
+        }
+
+        INVESTORS.with(|investors| {
+            investors.borrow_mut().insert(principal, InvestorRecord {
+                principal,
+                kyc_status,
+                jurisdiction,
+                accreditation,
+                expires_at,
+                updated_by: caller,
+                updated_at: time(),
+            });
+        });
+
+        Ok(true)
+    }
+
+    pub fn get_investor(principal: Principal) -> Option<InvestorRecord> {
+        INVESTORS.with(|investors| investors.borrow().get(&principal).cloned())
+    }
+
+    pub fn add_rule(rule: ComplianceRule, actions: Vec<ComplianceAction>) -> Result<u64, ComplianceError> {
+        AccessControl::require(Role::ComplianceOfficer).map_err(|_| ComplianceError::Unauthorized)?;
+
+        if actions.is_empty() {
+            return Err(ComplianceError::NoActions);
+        }
+
+        let rule = match rule {
+            ComplianceRule::BlockedJurisdictions { jurisdictions } => ComplianceRule::BlockedJurisdictions {
+                jurisdictions: jurisdictions.iter().map(|code| code.trim().to_uppercase()).collect(),
+            },
+            rule => rule,
+        };
+
+        let id = RULE_COUNTER.with(|counter| {
+            let mut counter = counter.borrow_mut();
+            *counter += 1;
+            *counter
+        });
+
+        RULES.with(|rules| {
+            rules.borrow_mut().insert(id, RuleConfig {
+                id,
+                rule,
+                actions,
+                enabled: true,
+            });
+        });
+
+        Ok(id)
+    }
+
+    pub fn set_rule_enabled(rule_id: u64, enabled: bool) -> Result<bool, ComplianceError> {
+        AccessControl::require(Role::ComplianceOfficer).map_err(|_| ComplianceError::Unauthorized)?;
+
+        RULES.with(|rules| {
+            let mut rules = rules.borrow_mut();
+            let config = rules.get_mut(&rule_id).ok_or(ComplianceError::RuleNotFound { rule_id })?;
+            config.enabled = enabled;
+            Ok(true)
+        })
+    }
+
+    pub fn remove_rule(rule_id: u64) -> Result<bool, ComplianceError> {
+        AccessControl::require(Role::ComplianceOfficer).map_err(|_| ComplianceError::Unauthorized)?;
+
+        RULES.with(|rules| {
+            rules.borrow_mut()
+                .remove(&rule_id)
+                .map(|_| true)
+                .ok_or(ComplianceError::RuleNotFound { rule_id })
+        })
+    }
+
+    pub fn get_rules() -> Vec<RuleConfig> {
+        RULES.with(|rules| rules.borrow().values().cloned().collect())
+    }
+
+    /// Runs every enabled rule for `action` against each party. The canister's own
+    /// account (e.g. the governance treasury) is exempt.
+    pub fn check(action: ComplianceAction, parties: &[Principal]) -> Result<(), ComplianceRejection> {
+        let rules: Vec<ComplianceRule> = RULES.with(|rules| {
+            rules.borrow()
+                .values()
+                .filter(|config| config.enabled && config.actions.contains(&action))
+                .map(|config| config.rule.clone())
+                .collect()
+        });
+        if rules.is_empty() {
+            return Ok(());
+        }
+
+        let canister = ic_cdk::api::id();
+        for party in parties.iter().filter(|party| **party != canister) {
+            for rule in &rules {
+                Self::evaluate(rule, *party)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn evaluate(rule: &ComplianceRule, principal: Principal) -> Result<(), ComplianceRejection> {
+        let record = Self::get_investor(principal)
+            .ok_or(ComplianceRejection::NotRegistered { principal })?;
+
+        match rule {
+            ComplianceRule::RequireKyc => {
+                if record.kyc_status != KycStatus::Verified {
+                    return Err(ComplianceRejection::KycNotVerified {
+                        principal,
+                        status: record.kyc_status,
+                    });
+                }
+                if time() >= record.expires_at {
+                    return Err(ComplianceRejection::KycExpired {
+                        principal,
+                        expired_at: record.expires_at,
+                    });
+                }
+            }
+            ComplianceRule::BlockedJurisdictions { jurisdictions } => {
+                if jurisdictions.contains(&record.jurisdiction) {
+                    return Err(ComplianceRejection::JurisdictionBlocked {
+                        principal,
+                        jurisdiction: record.jurisdiction,
+                    });
+                }
+            }
+            ComplianceRule::MinAccreditation { level } => {
+                if record.accreditation < *level {
+                    return Err(ComplianceRejection::InsufficientAccreditation {
+                        principal,
+                        required: *level,
+                        actual: record.accreditation,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,200 @@
+use candid::{CandidType, Deserialize, Principal};
+use ic_cdk::api::caller as ic_caller;
+use ic_cdk::api::time;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::checkpoints::Checkpoints;
+use crate::governance::GovernanceError;
+use crate::marketplace::Marketplace;
+use crate::ret_token::RETToken;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DelegationScope {
+    // Staked RET voting power in platform governance
+    Ret,
+    // Share voting power in one property's proposals
+    Property { property_id: u64 },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct DelegationChange {
+    pub timestamp: u64,
+    // None once the delegation is revoked
+    pub delegate: Option<Principal>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct DelegateSummary {
+    pub delegate: Principal,
+    pub delegated_power: u64,
+    pub delegators: u64,
+}
+
+thread_local! {
+    // Delegation history per (scope, delegator), ordered by timestamp
+    static DELEGATIONS: RefCell<HashMap<(DelegationScope, Principal), Vec<DelegationChange>>> = RefCell::new(HashMap::new());
+}
+
+/// Single-hop delegation: power delegated to a principal is not passed on if that
+/// principal delegates in turn. Delegating hands over all of the delegator's power.
+pub struct Delegation;
+
+impl Delegation {
+    pub fn delegate(scope: DelegationScope, delegate: Principal) -> Result<bool, GovernanceError> {
+        let caller = ic_caller();
+
+        if delegate == caller {
+            return Err(GovernanceError::SelfDelegation);
+        }
+        if delegate == Principal::anonymous() {
+            return Err(GovernanceError::AnonymousDelegate);
+        }
+        if let DelegationScope::Property { property_id } = scope {
+            let is_shareholder = Marketplace::get_property_shares(property_id)
+                .map(|shares| shares.iter().any(|share| share.owner == caller))
+                .unwrap_or(false);
+            if !is_shareholder {
+                return Err(GovernanceError::NotShareholder);
+            }
+        }
+        if Self::current_delegate(scope, caller) == Some(delegate) {
+            return Err(GovernanceError::AlreadyDelegated { delegate });
+        }
+
+        Self::record(scope, caller, Some(delegate));
+        Ok(true)
+    }
+
+    pub fn revoke(scope: DelegationScope) -> Result<bool, GovernanceError> {
+        let caller = ic_caller();
+
+        if Self::current_delegate(scope, caller).is_none() {
+            return Err(GovernanceError::NoDelegation);
+        }
+
+        Self::record(scope, caller, None);
+        Ok(true)
+    }
+
+    pub fn current_delegate(scope: DelegationScope, delegator: Principal) -> Option<Principal> {
+        Self::delegate_at(scope, delegator, time())
+    }
+
+    pub fn delegate_at(scope: DelegationScope, delegator: Principal, timestamp: u64) -> Option<Principal> {
+        DELEGATIONS.with(|delegations| {
+            let delegations = delegations.borrow();
+            let history = delegations.get(&(scope, delegator))?;
+            let index = history.partition_point(|change| change.timestamp <= timestamp);
+            index.checked_sub(1).and_then(|index| history[index].delegate)
+        })
+    }
+
+    pub fn get_history(scope: DelegationScope, delegator: Principal) -> Vec<DelegationChange> {
+        DELEGATIONS.with(|delegations| {
+            delegations.borrow().get(&(scope, delegator)).cloned().unwrap_or_default()
+        })
+    }
+
+    /// Principals whose `scope` delegation pointed at `delegate` at `timestamp`.
+    pub fn delegators_at(scope: DelegationScope, delegate: Principal, timestamp: u64) -> Vec<Principal> {
+        let candidates: Vec<Principal> = DELEGATIONS.with(|delegations| {
+            delegations.borrow()
+                .keys()
+                .filter(|(key_scope, _)| *key_scope == scope)
+                .map(|(_, delegator)| *delegator)
+                .collect()
+        });
+
+        candidates.into_iter()
+            .filter(|delegator| Self::delegate_at(scope, *delegator, timestamp) == Some(delegate))
+            .collect()
+    }
+
+    /// Staked RET voting power at `timestamp`: own stake unless delegated away,
+    /// plus the stake of everyone delegating to `voter`.
+    pub fn ret_voting_power(voter: Principal, timestamp: u64) -> u64 {
+        let own = match Self::delegate_at(DelegationScope::Ret, voter, timestamp) {
+            Some(_) => 0,
+            None => Checkpoints::staked_at(voter, timestamp),
+        };
+        let delegated: u64 = Self::delegators_at(DelegationScope::Ret, voter, timestamp)
+            .into_iter()
+            .map(|delegator| Checkpoints::staked_at(delegator, timestamp))
+            .sum();
+
+        own + delegated
+    }
+
+    /// Moves each holder's weight to their delegate as of now. Used to freeze the
+    /// electorate of a property proposal at creation.
+    pub fn apply_property_delegations(property_id: u64, holdings: HashMap<Principal, u64>) -> HashMap<Principal, u64> {
+        let scope = DelegationScope::Property { property_id };
+        let mut effective: HashMap<Principal, u64> = HashMap::new();
+        for (holder, weight) in holdings {
+            let voter = Self::current_delegate(scope, holder).unwrap_or(holder);
+            *effective.entry(voter).or_default() += weight;
+        }
+        effective
+    }
+
+    /// Delegates ranked by the power currently delegated to them.
+    pub fn get_leaderboard(scope: DelegationScope, limit: u64) -> Vec<DelegateSummary> {
+        let now = time();
+        let current: Vec<(Principal, Principal)> = DELEGATIONS.with(|delegations| {
+            delegations.borrow()
+                .keys()
+                .filter(|(key_scope, _)| *key_scope == scope)
+                .filter_map(|(_, delegator)| {
+                    Self::delegate_at(scope, *delegator, now).map(|delegate| (*delegator, delegate))
+                })
+                .collect()
+        });
+
+        let shares: HashMap<Principal, u64> = match scope {
+            DelegationScope::Property { property_id } => {
+                let mut shares = HashMap::new();
+                for share in Marketplace::get_property_shares(property_id).unwrap_or_default() {
+                    *shares.entry(share.owner).or_default() += share.share_percentage as u64;
+                }
+                shares
+            }
+            DelegationScope::Ret => HashMap::new(),
+        };
+
+        let mut summaries: HashMap<Principal, DelegateSummary> = HashMap::new();
+        for (delegator, delegate) in current {
+            let power = match scope {
+                DelegationScope::Ret => RETToken::staked_balance_of(delegator),
+                DelegationScope::Property { .. } => shares.get(&delegator).copied().unwrap_or(0),
+            };
+            let summary = summaries.entry(delegate).or_insert(DelegateSummary {
+                delegate,
+                delegated_power: 0,
+                delegators: 0,
+            });
+            summary.delegated_power += power;
+            summary.delegators += 1;
+        }
+
+        let mut leaderboard: Vec<DelegateSummary> = summaries.into_values().collect();
+        leaderboard.sort_by(|a, b| b.delegated_power.cmp(&a.delegated_power).then(b.delegators.cmp(&a.delegators)));
+        leaderboard.truncate(limit as usize);
+        leaderboard
+    }
+
+    fn record(scope: DelegationScope, delegator: Principal, delegate: Option<Principal>) {
+        let timestamp = time();
+        DELEGATIONS.with(|delegations| {
+            let mut delegations = delegations.borrow_mut();
+            let history = delegations.entry((scope, delegator)).or_default();
+            match history.last_mut() {
+                Some(last) if last.timestamp == timestamp => last.delegate = delegate,
+                _ => history.push(DelegationChange {
+                    timestamp,
+                    delegate,
+                }),
+            }
+        });
+    }
+}
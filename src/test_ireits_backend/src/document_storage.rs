@@ -0,0 +1,433 @@
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_cdk::api::caller as ic_caller;
+use ic_cdk::api::time;
+use ic_stable_structures::storable::Blob;
+use ic_stable_structures::{BoundedStorable, StableBTreeMap, StableCell, StableLog, Storable};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::cell::RefCell;
+
+use crate::access_control::{AccessControl, Role};
+use crate::marketplace::Marketplace;
+use crate::memory::{
+    self, Memory, DOCUMENT_CHUNK_DATA, DOCUMENT_CHUNK_INDEX, DOCUMENT_CHUNK_POSITIONS, DOCUMENT_LINKS,
+    DOCUMENT_RECORDS, DOCUMENT_UPLOADS, DOCUMENT_UPLOAD_CHUNKS, DOCUMENT_UPLOAD_COUNTER,
+};
+use crate::{Property, PropertyError};
+
+// Keeps each chunk inside the 2 MB ingress and response limits
+pub const MAX_CHUNK_SIZE: usize = 1_900_000;
+pub const MAX_DOCUMENT_SIZE: u64 = 64 * 1024 * 1024;
+const LINK_TTL: u64 = 15 * 60 * 1_000_000_000; // 15 minutes
+// Encoded size bound for upload and document records; content types come from the allow list
+const MAX_RECORD_SIZE: u32 = 1024;
+
+// Only inert formats; nothing the browser would execute from the canister's origin
+const ALLOWED_CONTENT_TYPES: [&str; 6] = [
+    "application/pdf",
+    "image/png",
+    "image/jpeg",
+    "image/webp",
+    "text/plain",
+    "text/csv",
+];
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum DocumentAccess {
+    Public,
+    // Property owner, manager, verifiers and anyone holding fractional shares
+    Shareholders,
+    // Property owner, manager and verifiers
+    Owner,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct DocumentUpload {
+    pub id: u64,
+    pub property_id: u64,
+    pub document_id: u64,
+    pub uploader: Principal,
+    pub content_type: String,
+    pub access: DocumentAccess,
+    pub total_size: u64,
+    pub received: u64,
+    pub chunk_count: u64,
+    pub started_at: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct StoredDocument {
+    pub property_id: u64,
+    pub document_id: u64,
+    pub content_type: String,
+    pub access: DocumentAccess,
+    pub size: u64,
+    pub sha256: String,
+    pub chunk_count: u64,
+    pub uploaded_by: Principal,
+    pub uploaded_at: u64,
+}
+
+impl Storable for DocumentUpload {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).expect("failed to encode document upload"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).expect("failed to decode document upload")
+    }
+}
+
+impl BoundedStorable for DocumentUpload {
+    const MAX_SIZE: u32 = MAX_RECORD_SIZE;
+    const IS_FIXED_SIZE: bool = false;
+}
+
+impl Storable for StoredDocument {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).expect("failed to encode stored document"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).expect("failed to decode stored document")
+    }
+}
+
+impl BoundedStorable for StoredDocument {
+    const MAX_SIZE: u32 = MAX_RECORD_SIZE;
+    const IS_FIXED_SIZE: bool = false;
+}
+
+// Hex SHA-256 link token
+type LinkKey = Blob<64>;
+
+// (property id, document id)
+type DocumentKey = (u64, u64);
+
+thread_local! {
+    // Append-only; chunks of abandoned or rejected uploads are never reclaimed
+    static CHUNKS: RefCell<StableLog<Vec<u8>, Memory, Memory>> = RefCell::new(
+        StableLog::init(memory::get(DOCUMENT_CHUNK_INDEX), memory::get(DOCUMENT_CHUNK_DATA))
+            .expect("failed to initialize document chunk log")
+    );
+    static UPLOADS: RefCell<StableBTreeMap<u64, DocumentUpload, Memory>> = RefCell::new(
+        StableBTreeMap::init(memory::get(DOCUMENT_UPLOADS))
+    );
+    // (upload id, chunk index) to position in the chunk log
+    static UPLOAD_CHUNKS: RefCell<StableBTreeMap<(u64, u64), u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(memory::get(DOCUMENT_UPLOAD_CHUNKS))
+    );
+    static UPLOAD_COUNTER: RefCell<StableCell<u64, Memory>> = RefCell::new(
+        StableCell::init(memory::get(DOCUMENT_UPLOAD_COUNTER), 0)
+            .expect("failed to initialize document upload counter")
+    );
+    static DOCUMENTS: RefCell<StableBTreeMap<DocumentKey, StoredDocument, Memory>> = RefCell::new(
+        StableBTreeMap::init(memory::get(DOCUMENT_RECORDS))
+    );
+    // (document, chunk index) to position in the chunk log
+    static CHUNK_POSITIONS: RefCell<StableBTreeMap<(DocumentKey, u64), u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(memory::get(DOCUMENT_CHUNK_POSITIONS))
+    );
+    // Link token to (document, expiry)
+    static LINKS: RefCell<StableBTreeMap<LinkKey, (DocumentKey, u64), Memory>> = RefCell::new(
+        StableBTreeMap::init(memory::get(DOCUMENT_LINKS))
+    );
+}
+
+/// Document bytes and their index in stable memory. The owner uploads a file in chunks
+/// against the SHA-256 declared by `add_document`; it is only kept if the digest matches.
+pub struct DocumentStore;
+
+impl DocumentStore {
+    pub fn begin_upload(
+        property: &Property,
+        document_id: u64,
+        content_type: String,
+        total_size: u64,
+        access: DocumentAccess,
+    ) -> Result<u64, PropertyError> {
+        let caller = ic_caller();
+        if property.owner != caller {
+            return Err(PropertyError::NotOwner { property_id: property.id, owner: property.owner });
+        }
+
+        let document = property.documents.iter()
+            .find(|document| document.id == document_id)
+            .ok_or(PropertyError::DocumentNotFound { property_id: property.id, document_id })?;
+        if document.revocation.is_some() {
+            return Err(PropertyError::DocumentRevoked { document_id });
+        }
+        Self::parse_hash(&document.hash).map_err(|reason| PropertyError::InvalidInput { reason })?;
+
+        if Self::get_document(property.id, document_id).is_some() {
+            return Err(PropertyError::InvalidInput { reason: "Document file already uploaded".to_string() });
+        }
+        if !ALLOWED_CONTENT_TYPES.contains(&content_type.as_str()) {
+            return Err(PropertyError::InvalidInput { reason: format!("Unsupported content type {}", content_type) });
+        }
+        if total_size == 0 || total_size > MAX_DOCUMENT_SIZE {
+            return Err(PropertyError::InvalidInput {
+                reason: format!("Document size must be between 1 and {} bytes", MAX_DOCUMENT_SIZE),
+            });
+        }
+
+        let id = UPLOAD_COUNTER.with(|counter| {
+            let mut counter = counter.borrow_mut();
+            let id = *counter.get() + 1;
+            counter.set(id).expect("failed to persist document upload counter");
+            id
+        });
+
+        UPLOADS.with(|uploads| {
+            uploads.borrow_mut().insert(id, DocumentUpload {
+                id,
+                property_id: property.id,
+                document_id,
+                uploader: caller,
+                content_type,
+                access,
+                total_size,
+                received: 0,
+                chunk_count: 0,
+                started_at: time(),
+            });
+        });
+
+        Ok(id)
+    }
+
+    /// Chunks must arrive in order; `index` guards against duplicates and gaps on retry.
+    pub fn upload_chunk(upload_id: u64, index: u64, bytes: Vec<u8>) -> Result<u64, PropertyError> {
+        let upload = Self::own_upload(upload_id)?;
+
+        if index != upload.chunk_count {
+            return Err(PropertyError::InvalidInput { reason: format!("Expected chunk {}", upload.chunk_count) });
+        }
+        if bytes.is_empty() || bytes.len() > MAX_CHUNK_SIZE {
+            return Err(PropertyError::InvalidInput {
+                reason: format!("Chunks must be between 1 and {} bytes", MAX_CHUNK_SIZE),
+            });
+        }
+        if upload.received + bytes.len() as u64 > upload.total_size {
+            return Err(PropertyError::InvalidInput { reason: "Chunk exceeds the declared document size".to_string() });
+        }
+
+        let position = CHUNKS.with(|chunks| chunks.borrow().append(&bytes))
+            .map_err(|_| PropertyError::StorageFull)?;
+
+        UPLOAD_CHUNKS.with(|chunks| chunks.borrow_mut().insert((upload_id, index), position));
+        let received = upload.received + bytes.len() as u64;
+        UPLOADS.with(|uploads| {
+            uploads.borrow_mut().insert(upload_id, DocumentUpload { received, chunk_count: index + 1, ..upload });
+        });
+
+        Ok(received)
+    }
+
+    /// Hashes the uploaded bytes and keeps the file only if they match the declared hash.
+    /// The upload is closed either way. Ownership and revocation are checked again, since
+    /// either may have changed while chunks were arriving.
+    pub fn finish_upload(property: &Property, upload_id: u64) -> Result<StoredDocument, PropertyError> {
+        let upload = Self::own_upload(upload_id)?;
+        UPLOADS.with(|uploads| uploads.borrow_mut().remove(&upload_id));
+        let positions: Vec<u64> = UPLOAD_CHUNKS.with(|chunks| {
+            let mut chunks = chunks.borrow_mut();
+            (0..upload.chunk_count)
+                .filter_map(|index| chunks.remove(&(upload_id, index)))
+                .collect()
+        });
+
+        if upload.received != upload.total_size {
+            return Err(PropertyError::InvalidInput {
+                reason: format!("Received {} of {} bytes", upload.received, upload.total_size),
+            });
+        }
+
+        if property.owner != upload.uploader {
+            return Err(PropertyError::NotOwner { property_id: property.id, owner: property.owner });
+        }
+
+        let document_id = upload.document_id;
+        let document = property.documents.iter()
+            .find(|document| document.id == document_id)
+            .ok_or(PropertyError::DocumentNotFound { property_id: property.id, document_id })?;
+        if document.revocation.is_some() {
+            return Err(PropertyError::DocumentRevoked { document_id });
+        }
+        let expected = Self::parse_hash(&document.hash).map_err(|reason| PropertyError::InvalidInput { reason })?;
+
+        let mut hasher = Sha256::new();
+        for (index, position) in positions.iter().enumerate() {
+            let chunk = Self::read_chunk(*position)
+                .ok_or(PropertyError::ChunkNotFound { document_id, index: index as u64 })?;
+            hasher.update(chunk);
+        }
+        let actual = Self::to_hex(&hasher.finalize());
+        if actual != expected {
+            return Err(PropertyError::InvalidInput {
+                reason: format!("SHA-256 mismatch: declared {}, uploaded {}", expected, actual),
+            });
+        }
+
+        let record = StoredDocument {
+            property_id: upload.property_id,
+            document_id: upload.document_id,
+            content_type: upload.content_type,
+            access: upload.access,
+            size: upload.total_size,
+            sha256: actual,
+            chunk_count: upload.chunk_count,
+            uploaded_by: upload.uploader,
+            uploaded_at: time(),
+        };
+
+        let key = (upload.property_id, upload.document_id);
+        CHUNK_POSITIONS.with(|chunks| {
+            let mut chunks = chunks.borrow_mut();
+            for (index, position) in positions.into_iter().enumerate() {
+                chunks.insert((key, index as u64), position);
+            }
+        });
+        DOCUMENTS.with(|documents| documents.borrow_mut().insert(key, record.clone()));
+
+        Ok(record)
+    }
+
+    pub fn get_upload(upload_id: u64) -> Option<DocumentUpload> {
+        UPLOADS.with(|uploads| uploads.borrow().get(&upload_id))
+    }
+
+    pub fn get_document(property_id: u64, document_id: u64) -> Option<StoredDocument> {
+        DOCUMENTS.with(|documents| documents.borrow().get(&(property_id, document_id)))
+    }
+
+    pub fn set_access(property: &Property, document_id: u64, access: DocumentAccess) -> Result<bool, PropertyError> {
+        if property.owner != ic_caller() {
+            return Err(PropertyError::NotOwner { property_id: property.id, owner: property.owner });
+        }
+
+        let record = Self::get_document(property.id, document_id)
+            .ok_or(PropertyError::DocumentNotFound { property_id: property.id, document_id })?;
+        DOCUMENTS.with(|documents| {
+            documents.borrow_mut().insert((property.id, document_id), StoredDocument { access, ..record });
+        });
+        Ok(true)
+    }
+
+    pub fn can_read(property: &Property, access: DocumentAccess, account: Principal) -> bool {
+        let is_insider = property.owner == account
+            || property.manager == Some(account)
+            || AccessControl::has_role(account, Role::PropertyVerifier);
+
+        match access {
+            DocumentAccess::Public => true,
+            DocumentAccess::Owner => is_insider,
+            DocumentAccess::Shareholders => {
+                is_insider
+                    || Marketplace::get_property_shares(property.id)
+                        .is_some_and(|shares| shares.iter().any(|share| share.owner == account))
+            }
+        }
+    }
+
+    /// Reads one chunk for an authenticated caller.
+    pub fn read_chunk_as(
+        property: &Property,
+        document_id: u64,
+        index: u64,
+        account: Principal,
+    ) -> Result<Vec<u8>, PropertyError> {
+        Self::readable_record(property, document_id, account)?;
+        Self::chunk(property.id, document_id, index)
+    }
+
+    /// Issues a short-lived link token so restricted files can be fetched over HTTP,
+    /// where requests are anonymous. `seed` must come from `raw_rand`.
+    pub fn create_link(
+        property: &Property,
+        document_id: u64,
+        account: Principal,
+        seed: Vec<u8>,
+    ) -> Result<String, PropertyError> {
+        Self::readable_record(property, document_id, account)?;
+
+        let now = time();
+        let token = Self::to_hex(&Sha256::digest(&seed));
+        LINKS.with(|links| {
+            let mut links = links.borrow_mut();
+            let expired: Vec<LinkKey> = links.iter()
+                .filter(|(_, (_, expires_at))| *expires_at <= now)
+                .map(|(key, _)| key)
+                .collect();
+            for key in expired {
+                links.remove(&key);
+            }
+            links.insert(Self::link_key(&token).expect("hex digests fit the link key"), ((property.id, document_id), now + LINK_TTL));
+        });
+
+        Ok(token)
+    }
+
+    /// Checks an anonymous HTTP read: public files, or any file with a live link token.
+    pub fn http_readable(property_id: u64, document_id: u64, token: Option<&str>) -> Option<StoredDocument> {
+        let record = Self::get_document(property_id, document_id)?;
+        if record.access == DocumentAccess::Public {
+            return Some(record);
+        }
+
+        let now = time();
+        let linked = token.and_then(Self::link_key).is_some_and(|key| {
+            LINKS.with(|links| {
+                links.borrow().get(&key).is_some_and(|(linked, expires_at)| {
+                    linked == (property_id, document_id) && expires_at > now
+                })
+            })
+        });
+        linked.then_some(record)
+    }
+
+    pub(crate) fn chunk(property_id: u64, document_id: u64, index: u64) -> Result<Vec<u8>, PropertyError> {
+        CHUNK_POSITIONS.with(|chunks| chunks.borrow().get(&((property_id, document_id), index)))
+            .and_then(Self::read_chunk)
+            .ok_or(PropertyError::ChunkNotFound { document_id, index })
+    }
+
+    fn read_chunk(position: u64) -> Option<Vec<u8>> {
+        CHUNKS.with(|chunks| chunks.borrow().get(position))
+    }
+
+    // The stored file, if `account` may read it
+    fn readable_record(property: &Property, document_id: u64, account: Principal) -> Result<StoredDocument, PropertyError> {
+        let record = Self::get_document(property.id, document_id)
+            .ok_or(PropertyError::DocumentNotFound { property_id: property.id, document_id })?;
+        if !Self::can_read(property, record.access, account) {
+            return Err(PropertyError::Unauthorized);
+        }
+        Ok(record)
+    }
+
+    fn own_upload(upload_id: u64) -> Result<DocumentUpload, PropertyError> {
+        let upload = Self::get_upload(upload_id).ok_or(PropertyError::UploadNotFound { upload_id })?;
+        if upload.uploader != ic_caller() {
+            return Err(PropertyError::Unauthorized);
+        }
+        Ok(upload)
+    }
+
+    // Declared hashes must be hex SHA-256 digests for the upload to be checked against them
+    fn parse_hash(hash: &str) -> Result<String, String> {
+        let hash = hash.trim().to_lowercase();
+        if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err("Declared document hash is not a hex SHA-256 digest".to_string());
+        }
+        Ok(hash)
+    }
+
+    fn link_key(token: &str) -> Option<LinkKey> {
+        LinkKey::try_from(token.as_bytes()).ok()
+    }
+
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
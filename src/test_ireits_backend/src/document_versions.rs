@@ -0,0 +1,155 @@
+use candid::{CandidType, Deserialize, Principal};
+use ic_cdk::api::time;
+
+use crate::access_control::{AccessControl, Role};
+use crate::{Document, DocumentType, Property, PropertyError};
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct DocumentRevocation {
+    pub reason: String,
+    pub revoked_by: Principal,
+    pub revoked_at: u64,
+}
+
+/// Version chains, revocation and expiry for property documents. Documents are never
+/// removed, so a document id is always its 1-based position in `Property::documents`.
+pub struct DocumentVersions;
+
+impl DocumentVersions {
+    /// Current means the latest version, not revoked and not expired.
+    pub fn is_current(document: &Document, now: u64) -> bool {
+        document.superseded_by.is_none()
+            && document.revocation.is_none()
+            && document.expires_at.is_none_or(|expires_at| expires_at > now)
+    }
+
+    /// Says why a document is not current.
+    pub(crate) fn ensure_current(document: &Document, now: u64) -> Result<(), PropertyError> {
+        let document_id = document.id;
+        if let Some(superseded_by) = document.superseded_by {
+            return Err(PropertyError::DocumentSuperseded { document_id, superseded_by });
+        }
+        if document.revocation.is_some() {
+            return Err(PropertyError::DocumentRevoked { document_id });
+        }
+        match document.expires_at {
+            Some(expired_at) if expired_at <= now => Err(PropertyError::DocumentExpired { document_id, expired_at }),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn current(property: &Property, doc_type: Option<DocumentType>) -> Vec<Document> {
+        let now = time();
+        property.documents.iter()
+            .filter(|document| doc_type.is_none_or(|doc_type| document.doc_type == doc_type))
+            .filter(|document| Self::is_current(document, now))
+            .cloned()
+            .collect()
+    }
+
+    pub(crate) fn add(
+        property: &mut Property,
+        doc_type: DocumentType,
+        hash: String,
+        expires_at: Option<u64>,
+    ) -> Result<u64, PropertyError> {
+        let now = time();
+        if hash.trim().is_empty() {
+            return Err(PropertyError::InvalidInput { reason: "Document hash cannot be empty".to_string() });
+        }
+        if expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Err(PropertyError::InvalidInput { reason: "Expiry must be in the future".to_string() });
+        }
+
+        let id = property.documents.len() as u64 + 1;
+        property.documents.push(Document {
+            id,
+            doc_type,
+            hash,
+            timestamp: now,
+            version: 1,
+            supersedes: None,
+            superseded_by: None,
+            expires_at,
+            revocation: None,
+        });
+        Ok(id)
+    }
+
+    /// Adds a new version of a document and links the two. Expired documents can be
+    /// superseded; revoked ones cannot.
+    pub(crate) fn supersede(
+        property: &mut Property,
+        document_id: u64,
+        hash: String,
+        expires_at: Option<u64>,
+    ) -> Result<u64, PropertyError> {
+        let previous = Self::find(property, document_id)?.clone();
+        if let Some(superseded_by) = previous.superseded_by {
+            return Err(PropertyError::DocumentSuperseded { document_id, superseded_by });
+        }
+        if previous.revocation.is_some() {
+            return Err(PropertyError::DocumentRevoked { document_id });
+        }
+
+        let id = Self::add(property, previous.doc_type, hash, expires_at)?;
+        if let Some(document) = property.documents.iter_mut().find(|document| document.id == id) {
+            document.version = previous.version + 1;
+            document.supersedes = Some(document_id);
+        }
+        if let Some(document) = property.documents.iter_mut().find(|document| document.id == document_id) {
+            document.superseded_by = Some(id);
+        }
+        Ok(id)
+    }
+
+    /// The owner or a verifier can revoke a document, with a reason.
+    pub(crate) fn revoke(
+        property: &mut Property,
+        document_id: u64,
+        reason: String,
+        caller: Principal,
+    ) -> Result<(), PropertyError> {
+        if property.owner != caller && !AccessControl::has_role(caller, Role::PropertyVerifier) {
+            return Err(PropertyError::Unauthorized);
+        }
+        if reason.trim().is_empty() {
+            return Err(PropertyError::ReasonRequired);
+        }
+
+        let property_id = property.id;
+        let document = property.documents.iter_mut()
+            .find(|document| document.id == document_id)
+            .ok_or(PropertyError::DocumentNotFound { property_id, document_id })?;
+        if document.revocation.is_some() {
+            return Err(PropertyError::DocumentRevoked { document_id });
+        }
+
+        document.revocation = Some(DocumentRevocation {
+            reason,
+            revoked_by: caller,
+            revoked_at: time(),
+        });
+        Ok(())
+    }
+
+    /// Every version of a document, oldest first.
+    pub fn chain(property: &Property, document_id: u64) -> Result<Vec<Document>, PropertyError> {
+        let mut first = Self::find(property, document_id)?;
+        while let Some(previous) = first.supersedes {
+            first = Self::find(property, previous)?;
+        }
+
+        let mut chain = vec![first.clone()];
+        while let Some(next) = chain.last().and_then(|document| document.superseded_by) {
+            chain.push(Self::find(property, next)?.clone());
+        }
+        Ok(chain)
+    }
+
+    fn find(property: &Property, document_id: u64) -> Result<&Document, PropertyError> {
+        property.documents.iter()
+            .find(|document| document.id == document_id)
+            .ok_or(PropertyError::DocumentNotFound { property_id: property.id, document_id })
+    }
+}
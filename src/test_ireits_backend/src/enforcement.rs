@@ -0,0 +1,175 @@
+use candid::{CandidType, Deserialize, Principal};
+use ic_cdk::api::time;
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::cap_table::ShareMovementKind;
+use crate::icrc7_token::ICRC7Token;
+use crate::marketplace::Marketplace;
+use crate::ret_token::RETToken;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AssetClass {
+    Ret,
+    Icrc7,
+    PropertyShares,
+}
+
+// Returned when a frozen account tries to move a frozen asset class
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Frozen {
+    pub account: Principal,
+    pub asset: AssetClass,
+}
+
+impl std::fmt::Display for Frozen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?} holdings of {} are frozen", self.asset, self.account)
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum ForcedAsset {
+    // Liquid RET only; staked RET stays with the holder
+    Ret { amount: u64 },
+    Icrc7 { token_id: u64 },
+    PropertyShares { property_id: u64, amount: u16 },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum EnforcementKind {
+    Freeze { assets: Vec<AssetClass> },
+    Unfreeze { assets: Vec<AssetClass> },
+    ForcedTransfer { to: Principal, asset: ForcedAsset, tx_id: Option<u64> },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct EnforcementRecord {
+    pub id: u64,
+    // Admin council action that authorized this
+    pub action_id: u64,
+    pub account: Principal,
+    pub kind: EnforcementKind,
+    pub reason: String,
+    pub timestamp: u64,
+}
+
+thread_local! {
+    static FROZEN: RefCell<HashMap<Principal, BTreeSet<AssetClass>>> = RefCell::new(HashMap::new());
+    static ENFORCEMENT_LOG: RefCell<Vec<EnforcementRecord>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Freezes and forced transfers for regulated assets. Everything here runs only as
+/// an approved admin council action, and every change is logged with its reason.
+pub struct Enforcement;
+
+impl Enforcement {
+    pub fn is_frozen(account: Principal, asset: AssetClass) -> bool {
+        FROZEN.with(|frozen| {
+            frozen.borrow()
+                .get(&account)
+                .is_some_and(|assets| assets.contains(&asset))
+        })
+    }
+
+    pub fn ensure_not_frozen(account: Principal, asset: AssetClass) -> Result<(), Frozen> {
+        if Self::is_frozen(account, asset) {
+            Err(Frozen { account, asset })
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn get_frozen_assets(account: Principal) -> Vec<AssetClass> {
+        FROZEN.with(|frozen| {
+            frozen.borrow()
+                .get(&account)
+                .map(|assets| assets.iter().copied().collect())
+                .unwrap_or_default()
+        })
+    }
+
+    pub fn get_log(start: u64, length: u64) -> Vec<EnforcementRecord> {
+        ENFORCEMENT_LOG.with(|log| {
+            log.borrow()
+                .iter()
+                .skip(start as usize)
+                .take(length as usize)
+                .cloned()
+                .collect()
+        })
+    }
+
+    pub(crate) fn validate_reason(reason: &str) -> Result<(), String> {
+        if reason.trim().is_empty() {
+            return Err("A documented reason is required".to_string());
+        }
+        Ok(())
+    }
+
+    pub(crate) fn freeze(action_id: u64, account: Principal, assets: Vec<AssetClass>, reason: String) {
+        FROZEN.with(|frozen| {
+            frozen.borrow_mut().entry(account).or_default().extend(assets.iter().copied());
+        });
+
+        Self::record(action_id, account, EnforcementKind::Freeze { assets }, reason);
+    }
+
+    pub(crate) fn unfreeze(action_id: u64, account: Principal, assets: Vec<AssetClass>, reason: String) {
+        FROZEN.with(|frozen| {
+            let mut frozen = frozen.borrow_mut();
+            if let Some(held) = frozen.get_mut(&account) {
+                for asset in &assets {
+                    held.remove(asset);
+                }
+                if held.is_empty() {
+                    frozen.remove(&account);
+                }
+            }
+        });
+
+        Self::record(action_id, account, EnforcementKind::Unfreeze { assets }, reason);
+    }
+
+    /// Moves assets regardless of freezes, pauses and transfer policies.
+    pub(crate) fn forced_transfer(
+        action_id: u64,
+        from: Principal,
+        to: Principal,
+        asset: ForcedAsset,
+        reason: String,
+    ) -> Result<(), String> {
+        let tx_id = match &asset {
+            ForcedAsset::Ret { amount } => {
+                Some(RETToken::force_transfer(from, to, *amount, Some(format!("forced:{}", action_id).into_bytes()))?)
+            }
+            ForcedAsset::Icrc7 { token_id } => {
+                ICRC7Token::force_transfer(*token_id, from, to)?;
+                None
+            }
+            ForcedAsset::PropertyShares { property_id, amount } => {
+                Marketplace::reassign_shares(*property_id, from, to, *amount, ShareMovementKind::Forced)
+                    .map_err(|error| error.to_string())?;
+                None
+            }
+        };
+
+        Self::record(action_id, from, EnforcementKind::ForcedTransfer { to, asset, tx_id }, reason);
+        Ok(())
+    }
+
+    fn record(action_id: u64, account: Principal, kind: EnforcementKind, reason: String) {
+        ENFORCEMENT_LOG.with(|log| {
+            let mut log = log.borrow_mut();
+            let id = log.len() as u64 + 1;
+            log.push(EnforcementRecord {
+                id,
+                action_id,
+                account,
+                kind,
+                reason,
+                timestamp: time(),
+            });
+        });
+    }
+}
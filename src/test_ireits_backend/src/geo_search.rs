@@ -0,0 +1,418 @@
+use candid::{CandidType, Deserialize, Principal};
+use ic_stable_structures::StableBTreeMap;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+
+use crate::amount::Amount;
+use crate::memory::{self, Memory, PROPERTY_GEO_CODES, PROPERTY_GEO_INDEX};
+use crate::property_details::{GeoPoint, PropertyData, PropertyType};
+use crate::property_search::{PropertyFilter, PropertySearch, MAX_PAGE_SIZE};
+use crate::{Property, PropertyMap, PropertyStatus};
+
+const EARTH_RADIUS_KM: f64 = 6371.0;
+const MAX_RADIUS_KM: f64 = 20_000.0;
+// Upper bound on index ranges scanned per box; the grid coarsens until the box fits
+const MAX_CELLS: u64 = 32;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug)]
+pub struct BoundingBox {
+    pub min_latitude: f64,
+    pub min_longitude: f64,
+    pub max_latitude: f64,
+    // Below `min_longitude` when the box crosses the antimeridian
+    pub max_longitude: f64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct PropertySummary {
+    pub id: u64,
+    pub owner: Principal,
+    pub status: PropertyStatus,
+    pub price: Amount,
+    pub location: String,
+    pub property_type: Option<PropertyType>,
+    pub coordinates: GeoPoint,
+    pub token_id: Option<u64>,
+    // Set for radius queries only
+    pub distance_km: Option<f64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct GeoPage {
+    pub items: Vec<PropertySummary>,
+    pub total: u64,
+}
+
+thread_local! {
+    // (geohash, property id); the geohash is the full 64-bit interleaving of longitude and latitude
+    static GEO_INDEX: RefCell<StableBTreeMap<(u64, u64), (), Memory>> = RefCell::new(
+        StableBTreeMap::init(memory::get(PROPERTY_GEO_INDEX))
+    );
+    static GEO_CODES: RefCell<StableBTreeMap<u64, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(memory::get(PROPERTY_GEO_CODES))
+    );
+}
+
+/// Radius and bounding-box queries over property coordinates. Properties are indexed by
+/// geohash in stable memory; a query scans the few geohash prefixes covering its box and
+/// then checks each candidate exactly.
+pub struct GeoSearch;
+
+impl GeoSearch {
+    pub(crate) fn refresh(property: &Property) {
+        let code = property.details.as_ref()
+            .and_then(|details| details.coordinates)
+            .map(Self::geohash);
+        let previous = GEO_CODES.with(|codes| codes.borrow().get(&property.id));
+        if previous == code {
+            return;
+        }
+
+        if let Some(previous) = previous {
+            GEO_INDEX.with(|index| index.borrow_mut().remove(&(previous, property.id)));
+            GEO_CODES.with(|codes| codes.borrow_mut().remove(&property.id));
+        }
+        if let Some(code) = code {
+            GEO_INDEX.with(|index| index.borrow_mut().insert((code, property.id), ()));
+            GEO_CODES.with(|codes| codes.borrow_mut().insert(property.id, code));
+        }
+    }
+
+    /// Nearest first.
+    pub fn within_radius(
+        properties: &PropertyMap,
+        center: GeoPoint,
+        radius_km: f64,
+        filter: &PropertyFilter,
+        start: u64,
+        length: u64,
+    ) -> Result<GeoPage, String> {
+        Self::validate_point(center)?;
+        if !(radius_km > 0.0 && radius_km <= MAX_RADIUS_KM) {
+            return Err(format!("Radius must be between 0 and {} km", MAX_RADIUS_KM));
+        }
+
+        let mut matches: Vec<(f64, Property, GeoPoint)> = Self::radius_boxes(center, radius_km)
+            .into_iter()
+            .flat_map(Self::candidates)
+            .collect::<BTreeSet<u64>>()
+            .into_iter()
+            .filter_map(|id| Self::located(properties, id, filter))
+            .map(|(property, point)| (Self::distance_km(center, point), property, point))
+            .filter(|(distance, _, _)| *distance <= radius_km)
+            .collect();
+        matches.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.id.cmp(&b.1.id)));
+
+        Self::page(matches.into_iter().map(|(distance, property, point)| (Some(distance), property, point)), start, length)
+    }
+
+    /// Ordered by property id.
+    pub fn in_bounding_box(
+        properties: &PropertyMap,
+        bbox: BoundingBox,
+        filter: &PropertyFilter,
+        start: u64,
+        length: u64,
+    ) -> Result<GeoPage, String> {
+        Self::validate_point(GeoPoint { latitude: bbox.min_latitude, longitude: bbox.min_longitude })?;
+        Self::validate_point(GeoPoint { latitude: bbox.max_latitude, longitude: bbox.max_longitude })?;
+        if bbox.min_latitude > bbox.max_latitude {
+            return Err("Minimum latitude exceeds maximum latitude".to_string());
+        }
+
+        let matches = Self::split_antimeridian(bbox)
+            .into_iter()
+            .flat_map(Self::candidates)
+            .collect::<BTreeSet<u64>>()
+            .into_iter()
+            .filter_map(|id| Self::located(properties, id, filter))
+            .filter(|(_, point)| Self::contains(bbox, *point))
+            .map(|(property, point)| (None, property, point));
+
+        Self::page(matches, start, length)
+    }
+
+    fn page(
+        matches: impl Iterator<Item = (Option<f64>, Property, GeoPoint)>,
+        start: u64,
+        length: u64,
+    ) -> Result<GeoPage, String> {
+        if length == 0 || length > MAX_PAGE_SIZE {
+            return Err(format!("Length must be between 1 and {}", MAX_PAGE_SIZE));
+        }
+
+        let matches: Vec<_> = matches.collect();
+        Ok(GeoPage {
+            total: matches.len() as u64,
+            items: matches.into_iter()
+                .skip(start as usize)
+                .take(length as usize)
+                .map(|(distance_km, property, coordinates)| Self::summary(&property, coordinates, distance_km))
+                .collect(),
+        })
+    }
+
+    fn summary(property: &Property, coordinates: GeoPoint, distance_km: Option<f64>) -> PropertySummary {
+        let details = property.details.as_ref();
+        PropertySummary {
+            id: property.id,
+            owner: property.owner,
+            status: property.status,
+            price: property.price,
+            location: details.map_or_else(|| property.location.clone(), PropertyData::location_line),
+            property_type: details.and_then(|details| details.property_type),
+            coordinates,
+            token_id: property.token_id,
+            distance_km,
+        }
+    }
+
+    fn located(properties: &PropertyMap, id: u64, filter: &PropertyFilter) -> Option<(Property, GeoPoint)> {
+        let property = properties.get(&id)?;
+        let point = property.details.as_ref()?.coordinates?;
+        PropertySearch::matches(&property, filter).then_some((property, point))
+    }
+
+    // Ids in every geohash cell overlapping the box, at the finest level that needs at most MAX_CELLS cells
+    fn candidates(bbox: BoundingBox) -> Vec<u64> {
+        let (lon_min, lat_min) = Self::quantize(GeoPoint { latitude: bbox.min_latitude, longitude: bbox.min_longitude });
+        let (lon_max, lat_max) = Self::quantize(GeoPoint { latitude: bbox.max_latitude, longitude: bbox.max_longitude });
+
+        let level = Self::level((lon_min, lat_min), (lon_max, lat_max));
+        let shift = 32 - level;
+        let span = u64::MAX.checked_shr(2 * level).unwrap_or(0);
+
+        GEO_INDEX.with(|index| {
+            let index = index.borrow();
+            let mut ids = Vec::new();
+            for lon_cell in (lon_min >> shift)..=(lon_max >> shift) {
+                for lat_cell in (lat_min >> shift)..=(lat_max >> shift) {
+                    let low = Self::interleave(lon_cell << shift, lat_cell << shift);
+                    ids.extend(index.range((low, 0)..=(low | span, u64::MAX)).map(|((_, id), _)| id));
+                }
+            }
+            ids
+        })
+    }
+
+    // Bits per axis of the finest grid covering the quantized corners with at most MAX_CELLS cells
+    fn level((lon_min, lat_min): (u64, u64), (lon_max, lat_max): (u64, u64)) -> u32 {
+        (0..=32u32).rev()
+            .find(|level| {
+                let shift = 32 - level;
+                ((lon_max >> shift) - (lon_min >> shift) + 1).saturating_mul((lat_max >> shift) - (lat_min >> shift) + 1) <= MAX_CELLS
+            })
+            .unwrap_or(0)
+    }
+
+    fn radius_boxes(center: GeoPoint, radius_km: f64) -> Vec<BoundingBox> {
+        let delta_lat = (radius_km / EARTH_RADIUS_KM).to_degrees();
+        let min_latitude = (center.latitude - delta_lat).max(-90.0);
+        let max_latitude = (center.latitude + delta_lat).min(90.0);
+
+        // Circles reaching a pole cover every longitude
+        let cos_lat = center.latitude.to_radians().cos();
+        if min_latitude <= -90.0 || max_latitude >= 90.0 || cos_lat < 1e-9 {
+            return vec![BoundingBox { min_latitude, min_longitude: -180.0, max_latitude, max_longitude: 180.0 }];
+        }
+        let delta_lon = (delta_lat / cos_lat).min(180.0);
+        if delta_lon >= 180.0 {
+            return vec![BoundingBox { min_latitude, min_longitude: -180.0, max_latitude, max_longitude: 180.0 }];
+        }
+
+        let wrap = |longitude: f64| {
+            if longitude < -180.0 {
+                longitude + 360.0
+            } else if longitude > 180.0 {
+                longitude - 360.0
+            } else {
+                longitude
+            }
+        };
+        Self::split_antimeridian(BoundingBox {
+            min_latitude,
+            min_longitude: wrap(center.longitude - delta_lon),
+            max_latitude,
+            max_longitude: wrap(center.longitude + delta_lon),
+        })
+    }
+
+    fn split_antimeridian(bbox: BoundingBox) -> Vec<BoundingBox> {
+        if bbox.min_longitude <= bbox.max_longitude {
+            return vec![bbox];
+        }
+        vec![
+            BoundingBox { max_longitude: 180.0, ..bbox },
+            BoundingBox { min_longitude: -180.0, ..bbox },
+        ]
+    }
+
+    fn contains(bbox: BoundingBox, point: GeoPoint) -> bool {
+        let in_longitude = if bbox.min_longitude <= bbox.max_longitude {
+            (bbox.min_longitude..=bbox.max_longitude).contains(&point.longitude)
+        } else {
+            point.longitude >= bbox.min_longitude || point.longitude <= bbox.max_longitude
+        };
+        in_longitude && (bbox.min_latitude..=bbox.max_latitude).contains(&point.latitude)
+    }
+
+    // Haversine great-circle distance
+    fn distance_km(a: GeoPoint, b: GeoPoint) -> f64 {
+        let d_lat = (b.latitude - a.latitude).to_radians();
+        let d_lon = (b.longitude - a.longitude).to_radians();
+        let h = (d_lat / 2.0).sin().powi(2)
+            + a.latitude.to_radians().cos() * b.latitude.to_radians().cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * h.sqrt().min(1.0).asin()
+    }
+
+    fn validate_point(point: GeoPoint) -> Result<(), String> {
+        if !(-90.0..=90.0).contains(&point.latitude) || !(-180.0..=180.0).contains(&point.longitude) {
+            return Err("Coordinates are out of range".to_string());
+        }
+        Ok(())
+    }
+
+    fn geohash(point: GeoPoint) -> u64 {
+        let (lon, lat) = Self::quantize(point);
+        Self::interleave(lon, lat)
+    }
+
+    // 32-bit grid positions for longitude and latitude
+    fn quantize(point: GeoPoint) -> (u64, u64) {
+        let scale = |value: f64, min: f64, range: f64| {
+            (((value - min) / range) * 4_294_967_296.0).clamp(0.0, 4_294_967_295.0) as u64
+        };
+        (scale(point.longitude, -180.0, 360.0), scale(point.latitude, -90.0, 180.0))
+    }
+
+    // Longitude bits in the odd positions and latitude in the even ones, most significant first
+    fn interleave(lon: u64, lat: u64) -> u64 {
+        let spread = |mut value: u64| {
+            value &= 0xFFFF_FFFF;
+            value = (value | (value << 16)) & 0x0000_FFFF_0000_FFFF;
+            value = (value | (value << 8)) & 0x00FF_00FF_00FF_00FF;
+            value = (value | (value << 4)) & 0x0F0F_0F0F_0F0F_0F0F;
+            value = (value | (value << 2)) & 0x3333_3333_3333_3333;
+            (value | (value << 1)) & 0x5555_5555_5555_5555
+        };
+        (spread(lon) << 1) | spread(lat)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const U32_MAX: u64 = 0xFFFF_FFFF;
+
+    fn point(latitude: f64, longitude: f64) -> GeoPoint {
+        GeoPoint { latitude, longitude }
+    }
+
+    fn bbox(min_latitude: f64, min_longitude: f64, max_latitude: f64, max_longitude: f64) -> BoundingBox {
+        BoundingBox { min_latitude, min_longitude, max_latitude, max_longitude }
+    }
+
+    fn index(id: u64, at: GeoPoint) {
+        GEO_INDEX.with(|index| index.borrow_mut().insert((GeoSearch::geohash(at), id), ()));
+    }
+
+    fn found(bbox: BoundingBox) -> BTreeSet<u64> {
+        GeoSearch::split_antimeridian(bbox).into_iter().flat_map(GeoSearch::candidates).collect()
+    }
+
+    #[test]
+    fn test_interleave_places_longitude_in_the_odd_bits() {
+        assert_eq!(GeoSearch::interleave(0, 0), 0);
+        assert_eq!(GeoSearch::interleave(1, 0), 0b10);
+        assert_eq!(GeoSearch::interleave(0, 1), 0b01);
+        assert_eq!(GeoSearch::interleave(1 << 31, 0), 1 << 63);
+        assert_eq!(GeoSearch::interleave(U32_MAX, 0), 0xAAAA_AAAA_AAAA_AAAA);
+        assert_eq!(GeoSearch::interleave(U32_MAX, U32_MAX), u64::MAX);
+        // Only the low 32 bits of each axis are used
+        assert_eq!(GeoSearch::interleave(1 << 32, 1 << 32), 0);
+    }
+
+    #[test]
+    fn test_quantize_maps_the_globe_onto_the_grid() {
+        assert_eq!(GeoSearch::quantize(point(-90.0, -180.0)), (0, 0));
+        assert_eq!(GeoSearch::quantize(point(0.0, 0.0)), (1 << 31, 1 << 31));
+        // The far edges clamp into the last cell instead of wrapping
+        assert_eq!(GeoSearch::quantize(point(90.0, 180.0)), (U32_MAX, U32_MAX));
+    }
+
+    #[test]
+    fn test_level_coarsens_until_the_box_fits() {
+        let origin = GeoSearch::quantize(point(0.0, 0.0));
+        assert_eq!(GeoSearch::level(origin, origin), 32);
+        // The whole globe fits in 4 x 4 cells but not 8 x 8
+        assert_eq!(GeoSearch::level((0, 0), (U32_MAX, U32_MAX)), 2);
+
+        let min = GeoSearch::quantize(point(40.0, -75.0));
+        let max = GeoSearch::quantize(point(41.0, -73.0));
+        let level = GeoSearch::level(min, max);
+        let shift = 32 - level;
+        let cells = ((max.0 >> shift) - (min.0 >> shift) + 1) * ((max.1 >> shift) - (min.1 >> shift) + 1);
+        assert!(cells <= MAX_CELLS);
+        // One level finer would need too many cells
+        let finer = shift - 1;
+        assert!(((max.0 >> finer) - (min.0 >> finer) + 1) * ((max.1 >> finer) - (min.1 >> finer) + 1) > MAX_CELLS);
+    }
+
+    #[test]
+    fn test_candidates_cover_the_box() {
+        index(1, point(40.7, -74.0));
+        index(2, point(40.8, -73.9));
+        index(3, point(51.5, -0.1));
+
+        assert_eq!(found(bbox(40.5, -74.5, 41.0, -73.5)), BTreeSet::from([1, 2]));
+        assert_eq!(found(bbox(51.0, -1.0, 52.0, 1.0)), BTreeSet::from([3]));
+        assert_eq!(found(bbox(-90.0, -180.0, 90.0, 180.0)), BTreeSet::from([1, 2, 3]));
+    }
+
+    #[test]
+    fn test_split_antimeridian() {
+        let plain = bbox(-10.0, 10.0, 10.0, 20.0);
+        assert_eq!(GeoSearch::split_antimeridian(plain).len(), 1);
+
+        let crossing = GeoSearch::split_antimeridian(bbox(-10.0, 170.0, 10.0, -170.0));
+        assert_eq!(crossing.len(), 2);
+        assert_eq!((crossing[0].min_longitude, crossing[0].max_longitude), (170.0, 180.0));
+        assert_eq!((crossing[1].min_longitude, crossing[1].max_longitude), (-180.0, -170.0));
+    }
+
+    #[test]
+    fn test_candidates_across_the_antimeridian() {
+        index(1, point(-17.7, 178.0));
+        index(2, point(-14.3, -170.7));
+        index(3, point(0.0, 0.0));
+
+        assert_eq!(found(bbox(-20.0, 175.0, -10.0, -165.0)), BTreeSet::from([1, 2]));
+        assert!(GeoSearch::contains(bbox(-20.0, 175.0, -10.0, -165.0), point(-14.3, -170.7)));
+        assert!(!GeoSearch::contains(bbox(-20.0, 175.0, -10.0, -165.0), point(-14.3, 0.0)));
+    }
+
+    #[test]
+    fn test_radius_boxes_wrap_the_antimeridian() {
+        let boxes = GeoSearch::radius_boxes(point(0.0, 179.5), 200.0);
+        assert_eq!(boxes.len(), 2);
+        assert_eq!(boxes[0].max_longitude, 180.0);
+        assert_eq!(boxes[1].min_longitude, -180.0);
+        assert!(boxes[1].max_longitude > -179.0 && boxes[1].max_longitude < -178.0);
+    }
+
+    #[test]
+    fn test_radius_boxes_near_the_poles_cover_every_longitude() {
+        for center in [point(89.9, 12.0), point(-89.5, -100.0), point(90.0, 0.0)] {
+            let boxes = GeoSearch::radius_boxes(center, 100.0);
+            assert_eq!(boxes.len(), 1);
+            assert_eq!((boxes[0].min_longitude, boxes[0].max_longitude), (-180.0, 180.0));
+        }
+
+        // Far from the poles the box stays narrow and symmetric
+        let boxes = GeoSearch::radius_boxes(point(0.0, 0.0), 111.0);
+        assert_eq!(boxes.len(), 1);
+        assert!((boxes[0].max_latitude - 1.0).abs() < 0.01);
+        assert!((boxes[0].max_longitude + boxes[0].min_longitude).abs() < 1e-9);
+    }
+}
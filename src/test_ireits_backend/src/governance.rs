@@ -0,0 +1,470 @@
+use candid::{CandidType, Deserialize, Principal};
+use ic_cdk::api::caller as ic_caller;
+use ic_cdk::api::time;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use crate::amount::Amount;
+use crate::checkpoints::Checkpoints;
+use crate::delegation::Delegation;
+use crate::marketplace::{Marketplace, MAX_LISTING_FEE_PERCENTAGE};
+use crate::ret_token::RETToken;
+use crate::PropertyStatus;
+
+const DAY: u64 = 24 * 60 * 60 * 1_000_000_000; // 1 day in nanoseconds
+const MAX_STAKE_APR: u64 = 50;
+const MIN_VOTING_PERIOD: u64 = DAY;
+const MAX_VOTING_PERIOD: u64 = 30 * DAY;
+// A passed proposal always leaves time to react before it runs
+const MIN_TIMELOCK: u64 = DAY;
+const MAX_TIMELOCK: u64 = 30 * DAY;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct GovernanceConfig {
+    pub voting_period: u64,
+    pub timelock: u64,
+    // Share of staked supply that must vote, in basis points
+    pub quorum_percentage: u64,
+    // Share of for + against votes that must be in favour, in basis points
+    pub approval_threshold: u64,
+    // Minimum staked RET needed to submit a proposal
+    pub proposal_threshold: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum PropertyAction {
+    // Limited to edges reserved for the system, such as UnderContract back to Tokenized
+    SetStatus { status: PropertyStatus },
+    UpdatePrice { price: Amount },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum ProposalAction {
+    // Signalling only, nothing is executed
+    Motion,
+    SetListingFee { basis_points: u64 },
+    SetStakeApr { percentage: u64 },
+    // Paid from the canister's own RET account
+    TreasurySpend { recipient: Principal, amount: u64 },
+    Property { property_id: u64, action: PropertyAction },
+    UpdateConfig { config: GovernanceConfig },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum ProposalStatus {
+    Open,
+    Rejected,
+    Queued,
+    Executed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum VoteChoice {
+    For,
+    Against,
+    Abstain,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Proposal {
+    pub id: u64,
+    pub proposer: Principal,
+    pub title: String,
+    pub description: String,
+    pub action: ProposalAction,
+    pub status: ProposalStatus,
+    pub snapshot_id: u64,
+    pub created_at: u64,
+    pub voting_ends_at: u64,
+    pub executable_at: Option<u64>,
+    pub votes_for: u64,
+    pub votes_against: u64,
+    pub votes_abstain: u64,
+    pub quorum: u64,
+    pub executed_at: Option<u64>,
+    pub failure_reason: Option<String>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct VoteRecord {
+    pub choice: VoteChoice,
+    pub weight: u64,
+    pub timestamp: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum GovernanceError {
+    InsufficientStake { required: u64, staked: u64 },
+    ProposalNotFound { proposal_id: u64 },
+    NoShareholders { property_id: u64 },
+    NotShareholder,
+    NotProposer,
+    NotOpen,
+    NotQueued,
+    VotingClosed,
+    VotingNotEnded { ends_at: u64 },
+    TimelockNotElapsed { executable_at: u64 },
+    AlreadyVoted,
+    NoVotingPower,
+    ListingFeeTooHigh { max: u64 },
+    StakeAprTooHigh { max: u64 },
+    ZeroTreasurySpend,
+    // Quorum and approval threshold are basis points
+    InvalidBasisPoints,
+    VotingPeriodOutOfRange { min: u64, max: u64 },
+    TimelockOutOfRange { min: u64, max: u64 },
+    InvalidAmount { reason: String },
+    Overflow,
+    SelfDelegation,
+    AnonymousDelegate,
+    AlreadyDelegated { delegate: Principal },
+    NoDelegation,
+}
+
+impl std::fmt::Display for GovernanceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GovernanceError::InsufficientStake { required, staked } => {
+                write!(f, "Submitting a proposal needs {} staked RET, found {}", required, staked)
+            }
+            GovernanceError::ProposalNotFound { proposal_id } => write!(f, "Proposal {} not found", proposal_id),
+            GovernanceError::NoShareholders { property_id } => write!(f, "Property {} has no shareholders", property_id),
+            GovernanceError::NotShareholder => write!(f, "Caller is not a shareholder of the property"),
+            GovernanceError::NotProposer => write!(f, "Only the proposer can cancel"),
+            GovernanceError::NotOpen => write!(f, "Proposal is not open"),
+            GovernanceError::NotQueued => write!(f, "Proposal is not queued"),
+            GovernanceError::VotingClosed => write!(f, "Voting is closed"),
+            GovernanceError::VotingNotEnded { ends_at } => write!(f, "Voting runs until {}", ends_at),
+            GovernanceError::TimelockNotElapsed { executable_at } => write!(f, "Timelock runs until {}", executable_at),
+            GovernanceError::AlreadyVoted => write!(f, "Already voted"),
+            GovernanceError::NoVotingPower => write!(f, "No voting power at proposal snapshot"),
+            GovernanceError::ListingFeeTooHigh { max } => write!(f, "Listing fee exceeds the maximum of {} basis points", max),
+            GovernanceError::StakeAprTooHigh { max } => write!(f, "Stake APR exceeds the maximum of {}%", max),
+            GovernanceError::ZeroTreasurySpend => write!(f, "Treasury spend must be greater than zero"),
+            GovernanceError::InvalidBasisPoints => write!(f, "Percentages are in basis points and cannot exceed 10000"),
+            GovernanceError::VotingPeriodOutOfRange { min, max } => {
+                write!(f, "Voting period must be between {} and {} ns", min, max)
+            }
+            GovernanceError::TimelockOutOfRange { min, max } => write!(f, "Timelock must be between {} and {} ns", min, max),
+            GovernanceError::InvalidAmount { reason } => write!(f, "{}", reason),
+            GovernanceError::Overflow => write!(f, "Arithmetic overflow"),
+            GovernanceError::SelfDelegation => write!(f, "Cannot delegate to yourself"),
+            GovernanceError::AnonymousDelegate => write!(f, "Cannot delegate to the anonymous principal"),
+            GovernanceError::AlreadyDelegated { delegate } => write!(f, "Already delegated to {}", delegate),
+            GovernanceError::NoDelegation => write!(f, "No active delegation"),
+        }
+    }
+}
+
+thread_local! {
+    static CONFIG: RefCell<GovernanceConfig> = const { RefCell::new(GovernanceConfig {
+        voting_period: 3 * DAY,
+        timelock: 2 * DAY,
+        quorum_percentage: 1_000,
+        approval_threshold: 5_000,
+        proposal_threshold: 1_000,
+    }) };
+    static PROPOSALS: RefCell<BTreeMap<u64, Proposal>> = const { RefCell::new(BTreeMap::new()) };
+    static VOTES: RefCell<HashMap<u64, HashMap<Principal, VoteRecord>>> = RefCell::new(HashMap::new());
+    static PROPOSAL_COUNTER: RefCell<u64> = const { RefCell::new(0) };
+}
+
+pub struct Governance;
+
+impl Governance {
+    pub fn submit_proposal(title: String, description: String, action: ProposalAction) -> Result<u64, GovernanceError> {
+        let caller = ic_caller();
+        let config = Self::get_config();
+
+        let staked = RETToken::staked_balance_of(caller);
+        if staked < config.proposal_threshold {
+            return Err(GovernanceError::InsufficientStake { required: config.proposal_threshold, staked });
+        }
+        Self::validate_action(&action)?;
+
+        let id = PROPOSAL_COUNTER.with(|counter| {
+            let mut counter = counter.borrow_mut();
+            *counter += 1;
+            *counter
+        });
+
+        let snapshot_id = Checkpoints::create_snapshot(format!("proposal:{}", id), caller);
+        let snapshot_time = Self::snapshot_time(snapshot_id);
+        let quorum = (Checkpoints::total_staked_at(snapshot_time) as u128
+            * config.quorum_percentage as u128
+            / 10_000) as u64;
+
+        let now = time();
+        let voting_ends_at = now.checked_add(config.voting_period).ok_or(GovernanceError::Overflow)?;
+        PROPOSALS.with(|proposals| {
+            proposals.borrow_mut().insert(id, Proposal {
+                id,
+                proposer: caller,
+                title,
+                description,
+                action,
+                status: ProposalStatus::Open,
+                snapshot_id,
+                created_at: now,
+                voting_ends_at,
+                executable_at: None,
+                votes_for: 0,
+                votes_against: 0,
+                votes_abstain: 0,
+                quorum,
+                executed_at: None,
+                failure_reason: None,
+            });
+        });
+
+        ic_cdk_timers::set_timer(Duration::from_nanos(config.voting_period), move || {
+            let _ = Self::finalize(id);
+        });
+
+        Ok(id)
+    }
+
+    pub fn vote(proposal_id: u64, choice: VoteChoice) -> Result<u64, GovernanceError> {
+        let caller = ic_caller();
+        let proposal = Self::get_proposal(proposal_id).ok_or(GovernanceError::ProposalNotFound { proposal_id })?;
+
+        if proposal.status != ProposalStatus::Open || time() >= proposal.voting_ends_at {
+            return Err(GovernanceError::VotingClosed);
+        }
+
+        let already_voted = VOTES.with(|votes| {
+            votes.borrow()
+                .get(&proposal_id)
+                .map(|votes| votes.contains_key(&caller))
+                .unwrap_or(false)
+        });
+        if already_voted {
+            return Err(GovernanceError::AlreadyVoted);
+        }
+
+        let weight = Self::voting_power(caller, Self::snapshot_time(proposal.snapshot_id));
+        if weight == 0 {
+            return Err(GovernanceError::NoVotingPower);
+        }
+
+        VOTES.with(|votes| {
+            votes.borrow_mut()
+                .entry(proposal_id)
+                .or_default()
+                .insert(caller, VoteRecord {
+                    choice,
+                    weight,
+                    timestamp: time(),
+                });
+        });
+
+        PROPOSALS.with(|proposals| {
+            if let Some(proposal) = proposals.borrow_mut().get_mut(&proposal_id) {
+                match choice {
+                    VoteChoice::For => proposal.votes_for += weight,
+                    VoteChoice::Against => proposal.votes_against += weight,
+                    VoteChoice::Abstain => proposal.votes_abstain += weight,
+                }
+            }
+        });
+
+        Ok(weight)
+    }
+
+    /// Staked RET held by or delegated to `voter` at `timestamp`.
+    pub fn voting_power(voter: Principal, timestamp: u64) -> u64 {
+        Delegation::ret_voting_power(voter, timestamp)
+    }
+
+    /// Tallies a proposal whose voting period has ended. Normally driven by a timer;
+    /// callable by anyone in case the timer was lost, e.g. across an upgrade.
+    pub fn finalize(proposal_id: u64) -> Result<ProposalStatus, GovernanceError> {
+        let config = Self::get_config();
+
+        let status = PROPOSALS.with(|proposals| {
+            let mut proposals = proposals.borrow_mut();
+            let proposal = proposals.get_mut(&proposal_id)
+                .ok_or(GovernanceError::ProposalNotFound { proposal_id })?;
+
+            if proposal.status != ProposalStatus::Open {
+                return Err(GovernanceError::NotOpen);
+            }
+            if time() < proposal.voting_ends_at {
+                return Err(GovernanceError::VotingNotEnded { ends_at: proposal.voting_ends_at });
+            }
+
+            let turnout = proposal.votes_for + proposal.votes_against + proposal.votes_abstain;
+            let decisive = proposal.votes_for + proposal.votes_against;
+            let approved = proposal.votes_for as u128 * 10_000
+                > decisive as u128 * config.approval_threshold as u128;
+
+            if turnout >= proposal.quorum && approved {
+                proposal.status = ProposalStatus::Queued;
+                proposal.executable_at = Some(time().checked_add(config.timelock).ok_or(GovernanceError::Overflow)?);
+            } else {
+                proposal.status = ProposalStatus::Rejected;
+            }
+
+            Ok(proposal.status.clone())
+        })?;
+
+        if status == ProposalStatus::Queued {
+            ic_cdk_timers::set_timer(Duration::from_nanos(config.timelock), move || {
+                let _ = Self::execute(proposal_id);
+            });
+        }
+
+        Ok(status)
+    }
+
+    /// Runs a queued proposal once its timelock has elapsed.
+    pub fn execute(proposal_id: u64) -> Result<ProposalStatus, GovernanceError> {
+        let proposal = Self::get_proposal(proposal_id).ok_or(GovernanceError::ProposalNotFound { proposal_id })?;
+
+        if proposal.status != ProposalStatus::Queued {
+            return Err(GovernanceError::NotQueued);
+        }
+        if let Some(executable_at) = proposal.executable_at.filter(|executable_at| time() < *executable_at) {
+            return Err(GovernanceError::TimelockNotElapsed { executable_at });
+        }
+
+        let result = Self::apply_action(&proposal.action);
+
+        PROPOSALS.with(|proposals| {
+            let mut proposals = proposals.borrow_mut();
+            let proposal = proposals.get_mut(&proposal_id)
+                .ok_or(GovernanceError::ProposalNotFound { proposal_id })?;
+
+            proposal.executed_at = Some(time());
+            match result {
+                Ok(()) => proposal.status = ProposalStatus::Executed,
+                Err(reason) => {
+                    proposal.status = ProposalStatus::Failed;
+                    proposal.failure_reason = Some(reason);
+                }
+            }
+
+            Ok(proposal.status.clone())
+        })
+    }
+
+    pub fn cancel(proposal_id: u64) -> Result<bool, GovernanceError> {
+        let caller = ic_caller();
+
+        PROPOSALS.with(|proposals| {
+            let mut proposals = proposals.borrow_mut();
+            let proposal = proposals.get_mut(&proposal_id)
+                .ok_or(GovernanceError::ProposalNotFound { proposal_id })?;
+
+            if proposal.proposer != caller {
+                return Err(GovernanceError::NotProposer);
+            }
+            if proposal.status != ProposalStatus::Open {
+                return Err(GovernanceError::NotOpen);
+            }
+
+            proposal.status = ProposalStatus::Cancelled;
+            Ok(true)
+        })
+    }
+
+    pub fn get_config() -> GovernanceConfig {
+        CONFIG.with(|config| config.borrow().clone())
+    }
+
+    pub fn get_proposal(proposal_id: u64) -> Option<Proposal> {
+        PROPOSALS.with(|proposals| proposals.borrow().get(&proposal_id).cloned())
+    }
+
+    pub fn get_proposals(status: Option<ProposalStatus>, start: u64, length: u64) -> Vec<Proposal> {
+        PROPOSALS.with(|proposals| {
+            proposals.borrow()
+                .values()
+                .rev()
+                .filter(|proposal| status.as_ref().is_none_or(|status| proposal.status == *status))
+                .skip(start as usize)
+                .take(length as usize)
+                .cloned()
+                .collect()
+        })
+    }
+
+    pub fn get_vote(proposal_id: u64, voter: Principal) -> Option<VoteRecord> {
+        VOTES.with(|votes| {
+            votes.borrow()
+                .get(&proposal_id)
+                .and_then(|votes| votes.get(&voter).cloned())
+        })
+    }
+
+    // Power is measured just before the snapshot so stake added in the same round doesn't count
+    fn snapshot_time(snapshot_id: u64) -> u64 {
+        Checkpoints::get_snapshot(snapshot_id)
+            .map(|snapshot| snapshot.timestamp.saturating_sub(1))
+            .unwrap_or(0)
+    }
+
+    fn validate_action(action: &ProposalAction) -> Result<(), GovernanceError> {
+        match action {
+            ProposalAction::SetListingFee { basis_points } if *basis_points > MAX_LISTING_FEE_PERCENTAGE => {
+                Err(GovernanceError::ListingFeeTooHigh { max: MAX_LISTING_FEE_PERCENTAGE })
+            }
+            ProposalAction::SetStakeApr { percentage } if *percentage > MAX_STAKE_APR => {
+                Err(GovernanceError::StakeAprTooHigh { max: MAX_STAKE_APR })
+            }
+            ProposalAction::TreasurySpend { amount, .. } if *amount == 0 => {
+                Err(GovernanceError::ZeroTreasurySpend)
+            }
+            ProposalAction::UpdateConfig { config } => {
+                if config.quorum_percentage > 10_000 || config.approval_threshold > 10_000 {
+                    return Err(GovernanceError::InvalidBasisPoints);
+                }
+                if !(MIN_VOTING_PERIOD..=MAX_VOTING_PERIOD).contains(&config.voting_period) {
+                    return Err(GovernanceError::VotingPeriodOutOfRange { min: MIN_VOTING_PERIOD, max: MAX_VOTING_PERIOD });
+                }
+                if !(MIN_TIMELOCK..=MAX_TIMELOCK).contains(&config.timelock) {
+                    return Err(GovernanceError::TimelockOutOfRange { min: MIN_TIMELOCK, max: MAX_TIMELOCK });
+                }
+                Ok(())
+            }
+            ProposalAction::Property { action: PropertyAction::UpdatePrice { price }, .. } => {
+                price.validate_positive()
+                    .map(|_| ())
+                    .map_err(|reason| GovernanceError::InvalidAmount { reason })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn apply_action(action: &ProposalAction) -> Result<(), String> {
+        match action {
+            ProposalAction::Motion => Ok(()),
+            ProposalAction::SetListingFee { basis_points } => {
+                Marketplace::set_listing_fee_percentage(*basis_points);
+                Ok(())
+            }
+            ProposalAction::SetStakeApr { percentage } => {
+                RETToken::set_stake_apr(*percentage);
+                Ok(())
+            }
+            ProposalAction::TreasurySpend { recipient, amount } => {
+                RETToken::transfer_internal(ic_cdk::api::id(), *recipient, *amount, Some(b"treasury".to_vec()))
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            }
+            ProposalAction::Property { property_id, action } => match action {
+                PropertyAction::SetStatus { status } => crate::set_property_status(*property_id, *status)
+                    .map_err(|error| error.to_string()),
+                PropertyAction::UpdatePrice { price } => crate::set_property_price(*property_id, *price)
+                    .map_err(|error| error.to_string()),
+            },
+            ProposalAction::UpdateConfig { config } => {
+                CONFIG.with(|current| *current.borrow_mut() = config.clone());
+                Ok(())
+            }
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use candid::{define_function, CandidType, Deserialize};
+
+use crate::cap_table::{CapTables, ExportFormat};
+use crate::document_storage::{DocumentAccess, DocumentStore};
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct HttpRequest {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct HttpResponse {
+    pub status_code: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub streaming_strategy: Option<StreamingStrategy>,
+}
+
+// Identifies the next chunk of a document being streamed
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct StreamingToken {
+    pub property_id: u64,
+    pub document_id: u64,
+    pub chunk_index: u64,
+    pub link: Option<String>,
+}
+
+define_function!(pub StreamingCallback : (StreamingToken) -> (StreamingCallbackHttpResponse) query);
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum StreamingStrategy {
+    Callback {
+        callback: StreamingCallback,
+        token: StreamingToken,
+    },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct StreamingCallbackHttpResponse {
+    pub body: Vec<u8>,
+    pub token: Option<StreamingToken>,
+}
+
+/// Read-only HTTP gateway for exports and stored documents. Routes:
+/// - `GET /cap-table/{property_id}.csv?link={token}[&as_of={nanos}]`
+/// - `GET /cap-table/{property_id}.json?link={token}[&as_of={nanos}]`
+/// - `GET /documents/{property_id}/{document_id}[?link={token}]`
+pub struct HttpGateway;
+
+impl HttpGateway {
+    pub fn handle(request: HttpRequest) -> HttpResponse {
+        if request.method != "GET" {
+            return Self::error(405, "Method not allowed");
+        }
+
+        let (path, query) = match request.url.split_once('?') {
+            Some((path, query)) => (path, query),
+            None => (request.url.as_str(), ""),
+        };
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+        match segments.as_slice() {
+            ["cap-table", file] => Self::cap_table(file, query),
+            ["documents", property_id, document_id] => Self::document(property_id, document_id, query),
+            _ => Self::error(404, "Not found"),
+        }
+    }
+
+    /// Serves the chunk after `token`, re-checking access on every call.
+    pub fn stream_document(token: StreamingToken) -> StreamingCallbackHttpResponse {
+        let readable = DocumentStore::http_readable(token.property_id, token.document_id, token.link.as_deref());
+        let record = match readable {
+            Some(record) => record,
+            None => return StreamingCallbackHttpResponse { body: Vec::new(), token: None },
+        };
+
+        let body = DocumentStore::chunk(token.property_id, token.document_id, token.chunk_index).unwrap_or_default();
+        StreamingCallbackHttpResponse {
+            body,
+            token: Self::next_token(&token, record.chunk_count),
+        }
+    }
+
+    fn document(property_id: &str, document_id: &str, query: &str) -> HttpResponse {
+        let (property_id, document_id) = match (property_id.parse::<u64>(), document_id.parse::<u64>()) {
+            (Ok(property_id), Ok(document_id)) => (property_id, document_id),
+            _ => return Self::error(400, "Invalid document path"),
+        };
+
+        let link = Self::query_param(query, "link");
+        // Restricted and missing files look the same to anonymous callers
+        let record = match DocumentStore::http_readable(property_id, document_id, link) {
+            Some(record) => record,
+            None => return Self::error(404, "Not found"),
+        };
+        let body = match DocumentStore::chunk(property_id, document_id, 0) {
+            Ok(body) => body,
+            Err(error) => return Self::error(500, &error.to_string()),
+        };
+
+        let cache_control = if record.access == DocumentAccess::Public { "public, max-age=3600" } else { "private, no-store" };
+        let first = StreamingToken {
+            property_id,
+            document_id,
+            chunk_index: 0,
+            link: link.map(str::to_string),
+        };
+        let streaming_strategy = Self::next_token(&first, record.chunk_count).map(|token| StreamingStrategy::Callback {
+            callback: StreamingCallback::new(ic_cdk::api::id(), "http_request_streaming_callback".to_string()),
+            token,
+        });
+
+        HttpResponse {
+            status_code: 200,
+            headers: vec![
+                ("Content-Type".to_string(), record.content_type),
+                ("Content-Length".to_string(), record.size.to_string()),
+                ("Cache-Control".to_string(), cache_control.to_string()),
+                ("X-Content-Type-Options".to_string(), "nosniff".to_string()),
+            ],
+            body,
+            streaming_strategy,
+        }
+    }
+
+    fn next_token(token: &StreamingToken, chunk_count: u64) -> Option<StreamingToken> {
+        let chunk_index = token.chunk_index + 1;
+        (chunk_index < chunk_count).then(|| StreamingToken { chunk_index, ..token.clone() })
+    }
+
+    fn cap_table(file: &str, query: &str) -> HttpResponse {
+        let (id, format, content_type) = if let Some(id) = file.strip_suffix(".csv") {
+            (id, ExportFormat::Csv, "text/csv")
+        } else if let Some(id) = file.strip_suffix(".json") {
+            (id, ExportFormat::Json, "application/json")
+        } else {
+            return Self::error(404, "Not found");
+        };
+
+        let property_id = match id.parse::<u64>() {
+            Ok(property_id) => property_id,
+            Err(_) => return Self::error(400, "Invalid property id"),
+        };
+        if !CapTables::http_readable(property_id, Self::query_param(query, "link")) {
+            return Self::error(404, "Not found");
+        }
+        let as_of = match Self::query_param(query, "as_of").map(str::parse::<u64>) {
+            Some(Ok(as_of)) => Some(as_of),
+            Some(Err(_)) => return Self::error(400, "Invalid as_of timestamp"),
+            None => None,
+        };
+
+        let body = CapTables::export(property_id, as_of, format);
+        HttpResponse {
+            status_code: 200,
+            headers: vec![
+                ("Content-Type".to_string(), content_type.to_string()),
+                ("Content-Length".to_string(), body.len().to_string()),
+                ("Cache-Control".to_string(), "private, no-store".to_string()),
+            ],
+            body: body.into_bytes(),
+            streaming_strategy: None,
+        }
+    }
+
+    fn query_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+        query.split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value)
+    }
+
+    fn error(status_code: u16, message: &str) -> HttpResponse {
+        HttpResponse {
+            status_code,
+            headers: vec![("Content-Type".to_string(), "text/plain".to_string())],
+            body: message.as_bytes().to_vec(),
+            streaming_strategy: None,
+        }
+    }
+}
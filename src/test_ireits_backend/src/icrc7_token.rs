@@ -4,6 +4,11 @@ use ic_cdk::api::time;
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 
+use crate::compliance::{Compliance, ComplianceAction, ComplianceRejection};
+use crate::enforcement::{AssetClass, Enforcement, Frozen};
+use crate::pause::{CircuitBreaker, Paused, Subsystem};
+use crate::transfer_policy::{PolicyViolation, TransferPolicies};
+
 // Core Token Types
 #[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct TokenMetadata {
@@ -20,10 +25,62 @@ pub struct TokenMetadata {
     pub royalties: Option<u16>,
     pub royalty_recipient: Option<Principal>,
     pub tags: Option<Vec<String>>,
+    // Key-value traits such as the structured property data
+    pub attributes: Option<Vec<(String, String)>>,
     pub created_at: u64,
     pub modified_at: u64,
 }
 
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum TransferError {
+    TokenNotFound { token_id: u64 },
+    NotOwner { token_id: u64 },
+    // Neither the owner nor an unexpired approved spender
+    Unauthorized,
+    ComplianceRejected { reason: ComplianceRejection },
+    PolicyViolation { violation: PolicyViolation },
+    Paused { subsystem: Subsystem },
+    Frozen { asset: AssetClass },
+}
+
+impl std::fmt::Display for TransferError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransferError::TokenNotFound { token_id } => write!(f, "Token {} not found", token_id),
+            TransferError::NotOwner { token_id } => write!(f, "Token {} not owned by sender", token_id),
+            TransferError::Unauthorized => write!(f, "Not authorized to transfer"),
+            TransferError::ComplianceRejected { reason } => write!(f, "{}", reason),
+            TransferError::PolicyViolation { violation } => write!(f, "{}", violation),
+            TransferError::Paused { subsystem } => write!(f, "{}", Paused { subsystem: *subsystem }),
+            TransferError::Frozen { asset } => write!(f, "{:?} holdings are frozen", asset),
+        }
+    }
+}
+
+impl From<ComplianceRejection> for TransferError {
+    fn from(reason: ComplianceRejection) -> Self {
+        TransferError::ComplianceRejected { reason }
+    }
+}
+
+impl From<PolicyViolation> for TransferError {
+    fn from(violation: PolicyViolation) -> Self {
+        TransferError::PolicyViolation { violation }
+    }
+}
+
+impl From<Paused> for TransferError {
+    fn from(paused: Paused) -> Self {
+        TransferError::Paused { subsystem: paused.subsystem }
+    }
+}
+
+impl From<Frozen> for TransferError {
+    fn from(frozen: Frozen) -> Self {
+        TransferError::Frozen { asset: frozen.asset }
+    }
+}
+
 #[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct Token {
     pub token_id: u64,
@@ -55,7 +112,7 @@ pub struct TokenStats {
     pub unique_holders: u64,
     pub market_cap: u64,
     pub volume_24h: u64,
-    pub price_change_24h: f64,
+    pub price_change_24h_bps: i64,
 }
 
 #[derive(CandidType, Deserialize, Clone, Debug)]
@@ -211,7 +268,7 @@ impl ICRC7Token {
                 unique_holders: 1,
                 market_cap: 0,
                 volume_24h: 0,
-                price_change_24h: 0.0,
+                price_change_24h_bps: 0,
             });
         });
 
@@ -239,7 +296,10 @@ impl ICRC7Token {
         Some(token_id)
     }
 
-    pub fn transfer(args: TransferArgs) -> Result<bool, String> {
+    pub fn transfer(args: TransferArgs) -> Result<bool, TransferError> {
+        CircuitBreaker::ensure_active(Subsystem::Icrc7Transfers)?;
+        Compliance::check(ComplianceAction::Icrc7Transfer, &[args.from, args.to])?;
+        Enforcement::ensure_not_frozen(args.from, AssetClass::Icrc7)?;
         let caller = ic_caller();
         
         // Verify ownership or approval
@@ -258,7 +318,7 @@ impl ICRC7Token {
             });
 
             if !is_approved {
-                return Err("Not authorized to transfer".to_string());
+                return Err(TransferError::Unauthorized);
             }
         }
 
@@ -267,10 +327,11 @@ impl ICRC7Token {
             let mut tokens = tokens.borrow_mut();
             if let Some(token) = tokens.get_mut(&args.token_id) {
                 if token.owner != args.from {
-                    return Err("Token not owned by sender".to_string());
+                    return Err(TransferError::NotOwner { token_id: args.token_id });
                 }
+                // Restricted tokens may only move as their property's transfer policy allows
                 if token.transfer_restricted {
-                    return Err("Token transfers are restricted".to_string());
+                    TransferPolicies::check_token_transfer(args.token_id, args.to)?;
                 }
                 token.owner = args.to;
                 token.last_transfer = Some(time());
@@ -284,7 +345,7 @@ impl ICRC7Token {
                 
                 Ok(())
             } else {
-                Err("Token not found".to_string())
+                Err(TransferError::TokenNotFound { token_id: args.token_id })
             }
         })?;
 
@@ -309,9 +370,44 @@ impl ICRC7Token {
             approvals.borrow_mut().remove(&(args.from, args.token_id));
         });
 
+        crate::sync_property_owner(args.token_id, args.to, caller);
+
         Ok(true)
     }
 
+    /// Reassigns a token without approval, freeze or policy checks. Only reachable
+    /// through an approved admin council action.
+    pub(crate) fn force_transfer(token_id: u64, from: Principal, to: Principal) -> Result<(), String> {
+        TOKENS.with(|tokens| {
+            let mut tokens = tokens.borrow_mut();
+            let token = tokens.get_mut(&token_id).ok_or("Token not found")?;
+            if token.owner != from {
+                return Err("Token not owned by sender".to_string());
+            }
+            token.owner = to;
+            token.last_transfer = Some(time());
+            Ok::<(), String>(())
+        })?;
+
+        TOKEN_OWNERS.with(|owners| {
+            let mut owners = owners.borrow_mut();
+            if let Some(owned_tokens) = owners.get_mut(&from) {
+                owned_tokens.remove(&token_id);
+            }
+            owners
+                .entry(to)
+                .or_insert_with(HashSet::new)
+                .insert(token_id);
+        });
+
+        APPROVALS.with(|approvals| {
+            approvals.borrow_mut().remove(&(from, token_id));
+        });
+
+        crate::sync_property_owner(token_id, to, ic_cdk::api::id());
+        Ok(())
+    }
+
     pub fn approve(args: ApprovalArgs) -> Result<bool, String> {
         let caller = ic_caller();
         
@@ -33,6 +33,7 @@ mod tests {
             royalties: Some(250),
             royalty_recipient: Some(Principal::anonymous()),
             tags: Some(vec!["test".to_string()]),
+            attributes: None,
             created_at: time(),
             modified_at: time(),
         }
@@ -1,207 +1,1117 @@
-use candid::{CandidType, Deserialize, Principal};
+use candid::{CandidType, Decode, Deserialize, Encode, Principal};
+use ic_stable_structures::{BoundedStorable, StableBTreeMap, StableCell, Storable};
+use std::borrow::Cow;
 use std::cell::RefCell;
-use std::collections::HashMap;
 
+mod access_control;
+mod airdrop;
+mod amount;
+mod attestations;
+mod cap_table;
+mod checkpoints;
+mod compliance;
+mod delegation;
+mod document_storage;
+mod document_versions;
+mod enforcement;
+mod geo_search;
+mod governance;
+mod http;
 mod icrc7_token;
+mod lifecycle;
+mod ret_stats;
 mod ret_token;
 mod marketplace;
+mod memory;
+mod multisig;
+mod pause;
 mod payments;
+mod property_details;
+mod property_governance;
+mod property_history;
+mod property_search;
+mod transfer_policy;
 mod types;
 
-use types::TokenType;
-use ret_token::{RETToken, TokenMetadata as RETTokenMetadata, TokenStats, TransferArgs};
-use icrc7_token::{ICRC7Token, TokenMetadata as ICRC7TokenMetadata};
+use amount::Amount;
+use ret_token::{FeeConfig, RETToken, TokenMetadata as RETTokenMetadata, StakingError, TokenError, TokenStats, Transaction, TransferArgs, TransferError};
+use icrc7_token::{ICRC7Token, TokenMetadata as ICRC7TokenMetadata, TransferArgs as ICRC7TransferArgs, TransferError as ICRC7TransferError};
+use ret_stats::{PricePoint, PriceSource, RETStats, SnapshotGranularity, StatsSnapshot};
+use attestations::{Attestation, AttestationVerdict, DocumentAttestations, VerificationStatus, TOKENIZATION_DOCUMENTS};
+use checkpoints::{Checkpoint, Checkpoints, Snapshot, SnapshotError};
+use cap_table::{CapTable, CapTables, ExportFormat, ShareMovement};
+use compliance::{AccreditationLevel, Compliance, ComplianceAction, ComplianceError, ComplianceRejection, ComplianceRule, InvestorRecord, KycStatus, RuleConfig};
+use delegation::{DelegateSummary, Delegation, DelegationChange, DelegationScope};
+use enforcement::{AssetClass, Enforcement, EnforcementRecord};
+use document_storage::{DocumentAccess, DocumentStore, DocumentUpload, StoredDocument};
+use document_versions::{DocumentRevocation, DocumentVersions};
+use http::{HttpGateway, HttpRequest, HttpResponse, StreamingCallbackHttpResponse, StreamingToken};
+use lifecycle::{Actor, PropertyLifecycle, StatusChange};
+use geo_search::{BoundingBox, GeoPage, GeoSearch};
+use governance::{Governance, GovernanceConfig, GovernanceError, Proposal, ProposalAction, ProposalStatus, VoteChoice, VoteRecord};
+use property_details::{GeoPoint, MigrationReport, PropertyData, PropertyDetails};
+use property_governance::{PropertyGovernance, PropertyProposal, PropertyProposalKind};
+use property_history::{PropertyChangeKind, PropertyHistory, PropertyRevision, PropertyUpdate};
+use property_search::{PropertyFilter, PropertyPage, PropertySearch, PropertySort};
+use access_control::{AccessControl, AccessControlError, Role, RoleChange};
+use marketplace::{MarketplaceError, PropertyShare, ShareOffer};
+use multisig::{ActionStatus, AdminAction, AdminCouncil, CouncilConfig, CouncilError, PendingAction};
+use pause::{CircuitBreaker, PauseError, PauseEvent, Subsystem};
+use transfer_policy::{PropertyPolicy, TransferPolicies, TransferPolicy};
+use airdrop::{AirdropCampaign, AirdropError, AirdropManager, AirdropMode, BatchResult, CampaignReport};
+use memory::{Memory, PROPERTY_ID_COUNTER, PROPERTY_RECORDS};
+
+// Encoded bound for a property record, including its status history and document list
+const MAX_PROPERTY_SIZE: u32 = 32 * 1024;
+
+pub(crate) type PropertyMap = StableBTreeMap<u64, Property, Memory>;
 
 #[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct Property {
     pub id: u64,
+    // Bumped on every edit; prior versions are kept by `PropertyHistory`
+    pub version: u64,
     pub owner: Principal,
-    pub price: f64,
+    pub price: Amount,
     pub location: String,
     pub description: String,
+    // None until set by the owner or backfilled from `location` by the migration
+    pub details: Option<PropertyDetails>,
     pub status: PropertyStatus,
+    pub status_history: Vec<StatusChange>,
     pub documents: Vec<Document>,
     pub rental_income: Option<RentalIncome>,
     pub token_id: Option<u64>,
+    pub manager: Option<Principal>,
 }
 
-#[derive(CandidType, Deserialize, Clone, Debug)]
+// Changed only through `PropertyLifecycle::transition`
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
 pub enum PropertyStatus {
+    Draft,
+    PendingVerification,
+    Verified,
     Listed,
     UnderContract,
     Sold,
     Tokenized,
+    Delisted,
+    Archived,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Document {
+    pub id: u64,
+    pub doc_type: DocumentType,
+    pub hash: String,
+    pub timestamp: u64,
+    pub version: u32,
+    pub supersedes: Option<u64>,
+    pub superseded_by: Option<u64>,
+    // For time-limited documents such as inspections and insurance
+    pub expires_at: Option<u64>,
+    pub revocation: Option<DocumentRevocation>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DocumentType {
+    Deed,
+    Title,
+    Contract,
+    Inspection,
+    Other,
+}
+
+impl Storable for Property {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).expect("failed to encode property"))
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).expect("failed to decode property")
+    }
+}
+
+impl BoundedStorable for Property {
+    const MAX_SIZE: u32 = MAX_PROPERTY_SIZE;
+    const IS_FIXED_SIZE: bool = false;
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct RentalIncome {
+    pub monthly_amount: Amount,
+    pub last_distribution: u64,
+    pub distribution_frequency: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum PropertyError {
+    NotFound { property_id: u64 },
+    NotOwner { property_id: u64, owner: Principal },
+    Unauthorized,
+    InvalidTransition { from: PropertyStatus, to: PropertyStatus },
+    // The edge exists but the caller may not take it
+    TransitionNotAllowed { from: PropertyStatus, to: PropertyStatus },
+    StatusLocked { status: PropertyStatus },
+    ReasonRequired,
+    MissingDocument { doc_type: DocumentType },
+    UnverifiedDocuments { doc_types: Vec<DocumentType> },
+    DocumentNotFound { property_id: u64, document_id: u64 },
+    DocumentSuperseded { document_id: u64, superseded_by: u64 },
+    DocumentRevoked { document_id: u64 },
+    DocumentExpired { document_id: u64, expired_at: u64 },
+    AlreadyAttested { document_id: u64 },
+    UploadNotFound { upload_id: u64 },
+    AlreadyTokenized { token_id: u64 },
+    NotTokenized { property_id: u64 },
+    InvalidInput { reason: String },
+    // The ICRC-7 collection is missing or at its maximum supply
+    MintFailed,
+    TokenTransferFailed { error: ICRC7TransferError },
+    ChunkNotFound { document_id: u64, index: u64 },
+    StorageFull,
+    RandomnessUnavailable { reason: String },
+    RecordTooLarge { size: u64, max: u64 },
+}
+
+impl std::fmt::Display for PropertyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PropertyError::NotFound { property_id } => write!(f, "Property {} not found", property_id),
+            PropertyError::NotOwner { .. } => write!(f, "Not the property owner"),
+            PropertyError::Unauthorized => write!(f, "Not authorized"),
+            PropertyError::InvalidTransition { from, to } => write!(f, "Cannot move property from {:?} to {:?}", from, to),
+            PropertyError::TransitionNotAllowed { to, .. } => write!(f, "Not authorized to move property to {:?}", to),
+            PropertyError::StatusLocked { status } => write!(f, "Property is {:?}", status),
+            PropertyError::ReasonRequired => write!(f, "A reason is required"),
+            PropertyError::MissingDocument { doc_type } => write!(f, "A {:?} document is required", doc_type),
+            PropertyError::UnverifiedDocuments { doc_types } => write!(f, "Verified documents required: {:?}", doc_types),
+            PropertyError::DocumentNotFound { document_id, .. } => write!(f, "Document {} not found", document_id),
+            PropertyError::DocumentSuperseded { superseded_by, .. } => write!(f, "Document superseded by {}", superseded_by),
+            PropertyError::DocumentRevoked { document_id } => write!(f, "Document {} has been revoked", document_id),
+            PropertyError::DocumentExpired { document_id, .. } => write!(f, "Document {} has expired", document_id),
+            PropertyError::AlreadyAttested { document_id } => write!(f, "Document {} already attested by this verifier", document_id),
+            PropertyError::UploadNotFound { upload_id } => write!(f, "Upload {} not found", upload_id),
+            PropertyError::AlreadyTokenized { token_id } => write!(f, "Property already tokenized as {}", token_id),
+            PropertyError::NotTokenized { property_id } => write!(f, "Property {} not tokenized", property_id),
+            PropertyError::InvalidInput { reason } => write!(f, "{}", reason),
+            PropertyError::MintFailed => write!(f, "Failed to mint token"),
+            PropertyError::TokenTransferFailed { error } => write!(f, "Token transfer failed: {}", error),
+            PropertyError::ChunkNotFound { document_id, index } => {
+                write!(f, "Chunk {} of document {} not found", index, document_id)
+            }
+            PropertyError::StorageFull => write!(f, "Stable memory is full"),
+            PropertyError::RandomnessUnavailable { reason } => write!(f, "Failed to get randomness: {}", reason),
+            PropertyError::RecordTooLarge { size, max } => {
+                write!(f, "Property record of {} bytes exceeds the {} byte limit", size, max)
+            }
+        }
+    }
+}
+
+// Stable, like the document, cap-table and search indexes keyed by property id, so ids are never reused after an upgrade
+thread_local! {
+    static PROPERTY_COUNTER: RefCell<StableCell<u64, Memory>> = RefCell::new(
+        StableCell::init(memory::get(PROPERTY_ID_COUNTER), 0).expect("failed to initialize property counter")
+    );
+    static PROPERTIES: RefCell<PropertyMap> = RefCell::new(
+        StableBTreeMap::init(memory::get(PROPERTY_RECORDS))
+    );
+}
+
+// Applies `change` to a copy of the property and writes it back only if it succeeds
+fn update_property_record<T>(
+    property_id: u64,
+    change: impl FnOnce(&mut Property) -> Result<T, PropertyError>,
+) -> Result<T, PropertyError> {
+    let mut property = get_property(property_id).ok_or(PropertyError::NotFound { property_id })?;
+    let result = change(&mut property)?;
+    store_property(property)?;
+    Ok(result)
+}
+
+// Writes the record and refreshes its search indexes
+fn store_property(property: Property) -> Result<(), PropertyError> {
+    let size = property.to_bytes().len() as u64;
+    if size > MAX_PROPERTY_SIZE as u64 {
+        return Err(PropertyError::RecordTooLarge { size, max: MAX_PROPERTY_SIZE as u64 });
+    }
+    PropertySearch::refresh(&property);
+    PROPERTIES.with(|properties| properties.borrow_mut().insert(property.id, property));
+    Ok(())
+}
+
+#[ic_cdk_macros::init]
+fn init() {
+    let caller = ic_cdk::api::caller();
+    AccessControl::bootstrap(caller);
+    AdminCouncil::bootstrap(caller);
+}
+
+// Access Control
+#[ic_cdk_macros::update]
+fn grant_role(account: Principal, role: Role) -> Result<bool, AccessControlError> {
+    AccessControl::grant_role(account, role)
+}
+
+#[ic_cdk_macros::update]
+fn revoke_role(account: Principal, role: Role) -> Result<bool, AccessControlError> {
+    AccessControl::revoke_role(account, role)
+}
+
+#[ic_cdk_macros::query]
+fn get_roles(account: Principal) -> Vec<Role> {
+    AccessControl::get_roles(account)
+}
+
+#[ic_cdk_macros::query]
+fn get_role_members(role: Role) -> Vec<Principal> {
+    AccessControl::get_role_members(role)
+}
+
+#[ic_cdk_macros::query]
+fn has_role(account: Principal, role: Role) -> bool {
+    AccessControl::has_role(account, role)
+}
+
+#[ic_cdk_macros::query]
+fn get_role_audit_log(start: u64, length: u64) -> Vec<RoleChange> {
+    AccessControl::get_audit_log(start, length)
+}
+
+// RET Token Management
+#[ic_cdk_macros::update]
+fn initialize_ret(owner: Principal, website: Option<String>, social_links: Option<Vec<String>>) -> Result<bool, TokenError> {
+    AccessControl::require(Role::Admin).map_err(|_| TokenError::Unauthorized)?;
+    Ok(RETToken::initialize(owner, website, social_links))
+}
+
+#[ic_cdk_macros::query]
+fn get_ret_metadata() -> Option<RETTokenMetadata> {
+    RETToken::get_metadata()
+}
+
+#[ic_cdk_macros::query]
+fn balance_of(owner: Principal) -> u64 {
+    RETToken::balance_of(owner)
+}
+
+#[ic_cdk_macros::query]
+fn staked_balance_of(owner: Principal) -> u64 {
+    RETToken::staked_balance_of(owner)
+}
+
+#[ic_cdk_macros::update]
+fn stake(amount: u64, duration: u64) -> Result<bool, StakingError> {
+    RETToken::stake(amount, duration)
+}
+
+#[ic_cdk_macros::update]
+fn unstake() -> Result<u64, StakingError> {
+    RETToken::unstake()
+}
+
+#[ic_cdk_macros::update]
+fn transfer(args: TransferArgs) -> Result<u64, TransferError> {
+    RETToken::transfer(args)
+}
+
+#[ic_cdk_macros::update]
+fn airdrop_ret(recipients: Vec<(Principal, u64)>) -> Result<bool, TokenError> {
+    AirdropManager::airdrop(recipients)
+}
+
+#[ic_cdk_macros::query]
+fn get_ret_stats() -> TokenStats {
+    RETToken::get_stats()
+}
+
+#[ic_cdk_macros::query]
+fn get_ret_stats_history(granularity: SnapshotGranularity, from: u64, to: u64) -> Vec<StatsSnapshot> {
+    RETStats::get_history(granularity, from, to)
+}
+
+#[ic_cdk_macros::query]
+fn get_ret_price() -> Option<PricePoint> {
+    RETStats::current_price()
+}
+
+#[ic_cdk_macros::query]
+fn get_ret_price_history(from: u64, to: u64) -> Vec<PricePoint> {
+    RETStats::get_price_history(from, to)
+}
+
+#[ic_cdk_macros::update]
+fn set_ret_price_feed(feed: Principal, source: Option<PriceSource>) -> Result<bool, TokenError> {
+    RETStats::set_price_feed(feed, source)
+}
+
+#[ic_cdk_macros::update]
+fn report_ret_price(price: u64) -> Result<bool, TokenError> {
+    RETStats::report_price(price)
+}
+
+#[ic_cdk_macros::update]
+fn burn_ret(amount: u64, memo: Option<Vec<u8>>) -> Result<u64, TokenError> {
+    RETToken::burn(amount, memo)
+}
+
+#[ic_cdk_macros::query]
+fn get_ret_fee_config() -> FeeConfig {
+    RETToken::get_fee_config()
+}
+
+#[ic_cdk_macros::query]
+fn get_ret_transactions(start: u64, length: u64) -> Vec<Transaction> {
+    RETToken::get_transactions(start, length)
+}
+
+// Balance Checkpoints & Snapshots
+#[ic_cdk_macros::query]
+fn balance_at(account: Principal, timestamp: u64) -> u64 {
+    Checkpoints::balance_at(account, timestamp)
+}
+
+#[ic_cdk_macros::query]
+fn staked_balance_at(account: Principal, timestamp: u64) -> u64 {
+    Checkpoints::staked_at(account, timestamp)
+}
+
+#[ic_cdk_macros::query]
+fn total_supply_at(timestamp: u64) -> u64 {
+    Checkpoints::total_supply_at(timestamp)
+}
+
+#[ic_cdk_macros::query]
+fn total_staked_at(timestamp: u64) -> u64 {
+    Checkpoints::total_staked_at(timestamp)
+}
+
+#[ic_cdk_macros::query]
+fn get_balance_history(account: Principal) -> Vec<Checkpoint> {
+    Checkpoints::get_account_history(account)
+}
+
+#[ic_cdk_macros::update]
+fn take_snapshot(name: String) -> Result<u64, SnapshotError> {
+    Checkpoints::take_snapshot(name)
+}
+
+#[ic_cdk_macros::query]
+fn get_snapshot(snapshot_id: u64) -> Option<Snapshot> {
+    Checkpoints::get_snapshot(snapshot_id)
+}
+
+#[ic_cdk_macros::query]
+fn get_snapshots() -> Vec<Snapshot> {
+    Checkpoints::get_snapshots()
+}
+
+#[ic_cdk_macros::query]
+fn balance_at_snapshot(snapshot_id: u64, account: Principal) -> Result<u64, SnapshotError> {
+    Checkpoints::balance_at_snapshot(snapshot_id, account)
+}
+
+#[ic_cdk_macros::query]
+fn total_supply_at_snapshot(snapshot_id: u64) -> Result<u64, SnapshotError> {
+    Checkpoints::total_supply_at_snapshot(snapshot_id)
+}
+
+// Airdrop Campaigns
+#[ic_cdk_macros::update]
+fn create_airdrop_campaign(
+    name: String,
+    mode: AirdropMode,
+    snapshot_id: Option<u64>,
+    budget: u64,
+    expires_at: u64,
+) -> Result<u64, AirdropError> {
+    AirdropManager::create_campaign(name, mode, snapshot_id, budget, expires_at)
+}
+
+#[ic_cdk_macros::update]
+fn distribute_airdrop_batch(campaign_id: u64, recipients: Vec<(Principal, u64)>) -> Result<BatchResult, AirdropError> {
+    AirdropManager::distribute_batch(campaign_id, recipients)
+}
+
+#[ic_cdk_macros::update]
+fn claim_airdrop(campaign_id: u64, amount: u64, proof: Vec<Vec<u8>>) -> Result<u64, AirdropError> {
+    AirdropManager::claim(campaign_id, amount, proof)
+}
+
+#[ic_cdk_macros::update]
+fn reclaim_airdrop(campaign_id: u64) -> Result<u64, AirdropError> {
+    AirdropManager::reclaim(campaign_id)
+}
+
+#[ic_cdk_macros::query]
+fn get_airdrop_campaign(campaign_id: u64) -> Option<AirdropCampaign> {
+    AirdropManager::get_campaign(campaign_id)
+}
+
+#[ic_cdk_macros::query]
+fn get_airdrop_campaigns() -> Vec<AirdropCampaign> {
+    AirdropManager::get_campaigns()
+}
+
+#[ic_cdk_macros::query]
+fn get_airdrop_report(campaign_id: u64) -> Option<CampaignReport> {
+    AirdropManager::get_report(campaign_id)
+}
+
+#[ic_cdk_macros::query]
+fn get_airdrop_claims(campaign_id: u64, start: u64, length: u64) -> Vec<(Principal, u64)> {
+    AirdropManager::get_claims(campaign_id, start, length)
+}
+
+#[ic_cdk_macros::query]
+fn has_claimed_airdrop(campaign_id: u64, principal: Principal) -> bool {
+    AirdropManager::has_claimed(campaign_id, principal)
+}
+
+#[ic_cdk_macros::update]
+fn buyback_and_burn(amount: u64) -> Result<u64, MarketplaceError> {
+    marketplace::Marketplace::buyback_and_burn(amount)
+}
+
+// Governance
+#[ic_cdk_macros::update]
+fn submit_proposal(title: String, description: String, action: ProposalAction) -> Result<u64, GovernanceError> {
+    Governance::submit_proposal(title, description, action)
+}
+
+#[ic_cdk_macros::update]
+fn vote_on_proposal(proposal_id: u64, choice: VoteChoice) -> Result<u64, GovernanceError> {
+    Governance::vote(proposal_id, choice)
+}
+
+#[ic_cdk_macros::update]
+fn finalize_proposal(proposal_id: u64) -> Result<ProposalStatus, GovernanceError> {
+    Governance::finalize(proposal_id)
+}
+
+#[ic_cdk_macros::update]
+fn execute_proposal(proposal_id: u64) -> Result<ProposalStatus, GovernanceError> {
+    Governance::execute(proposal_id)
+}
+
+#[ic_cdk_macros::update]
+fn cancel_proposal(proposal_id: u64) -> Result<bool, GovernanceError> {
+    Governance::cancel(proposal_id)
+}
+
+#[ic_cdk_macros::query]
+fn get_proposal(proposal_id: u64) -> Option<Proposal> {
+    Governance::get_proposal(proposal_id)
+}
+
+#[ic_cdk_macros::query]
+fn get_proposals(status: Option<ProposalStatus>, start: u64, length: u64) -> Vec<Proposal> {
+    Governance::get_proposals(status, start, length)
+}
+
+#[ic_cdk_macros::query]
+fn get_proposal_vote(proposal_id: u64, voter: Principal) -> Option<VoteRecord> {
+    Governance::get_vote(proposal_id, voter)
+}
+
+#[ic_cdk_macros::query]
+fn get_voting_power(voter: Principal) -> u64 {
+    Governance::voting_power(voter, ic_cdk::api::time())
+}
+
+#[ic_cdk_macros::query]
+fn get_governance_config() -> GovernanceConfig {
+    Governance::get_config()
+}
+
+// Property Shareholder Governance
+#[ic_cdk_macros::update]
+fn submit_property_proposal(property_id: u64, kind: PropertyProposalKind, description: String) -> Result<u64, GovernanceError> {
+    PropertyGovernance::submit_proposal(property_id, kind, description)
+}
+
+#[ic_cdk_macros::update]
+fn vote_on_property_proposal(proposal_id: u64, choice: VoteChoice) -> Result<u64, GovernanceError> {
+    PropertyGovernance::vote(proposal_id, choice)
+}
+
+#[ic_cdk_macros::update]
+fn finalize_property_proposal(proposal_id: u64) -> Result<ProposalStatus, GovernanceError> {
+    PropertyGovernance::finalize(proposal_id)
+}
+
+#[ic_cdk_macros::update]
+fn cancel_property_proposal(proposal_id: u64) -> Result<bool, GovernanceError> {
+    PropertyGovernance::cancel(proposal_id)
+}
+
+#[ic_cdk_macros::query]
+fn get_property_proposal(proposal_id: u64) -> Option<PropertyProposal> {
+    PropertyGovernance::get_proposal(proposal_id)
+}
+
+#[ic_cdk_macros::query]
+fn get_property_proposals(property_id: u64) -> Vec<PropertyProposal> {
+    PropertyGovernance::get_property_proposals(property_id)
+}
+
+#[ic_cdk_macros::query]
+fn get_property_proposal_vote(proposal_id: u64, voter: Principal) -> Option<VoteRecord> {
+    PropertyGovernance::get_vote(proposal_id, voter)
+}
+
+// Vote Delegation
+#[ic_cdk_macros::update]
+fn delegate_votes(scope: DelegationScope, delegate: Principal) -> Result<bool, GovernanceError> {
+    Delegation::delegate(scope, delegate)
+}
+
+#[ic_cdk_macros::update]
+fn revoke_delegation(scope: DelegationScope) -> Result<bool, GovernanceError> {
+    Delegation::revoke(scope)
+}
+
+#[ic_cdk_macros::query]
+fn get_delegate(scope: DelegationScope, delegator: Principal) -> Option<Principal> {
+    Delegation::current_delegate(scope, delegator)
+}
+
+#[ic_cdk_macros::query]
+fn get_delegate_at(scope: DelegationScope, delegator: Principal, timestamp: u64) -> Option<Principal> {
+    Delegation::delegate_at(scope, delegator, timestamp)
+}
+
+#[ic_cdk_macros::query]
+fn get_delegators(scope: DelegationScope, delegate: Principal) -> Vec<Principal> {
+    Delegation::delegators_at(scope, delegate, ic_cdk::api::time())
+}
+
+#[ic_cdk_macros::query]
+fn get_delegation_history(scope: DelegationScope, delegator: Principal) -> Vec<DelegationChange> {
+    Delegation::get_history(scope, delegator)
+}
+
+#[ic_cdk_macros::query]
+fn get_delegate_leaderboard(scope: DelegationScope, limit: u64) -> Vec<DelegateSummary> {
+    Delegation::get_leaderboard(scope, limit)
+}
+
+// Compliance
+#[ic_cdk_macros::update]
+fn set_investor_record(
+    principal: Principal,
+    kyc_status: KycStatus,
+    jurisdiction: String,
+    accreditation: AccreditationLevel,
+    expires_at: u64,
+) -> Result<bool, ComplianceError> {
+    Compliance::set_investor(principal, kyc_status, jurisdiction, accreditation, expires_at)
+}
+
+#[ic_cdk_macros::query]
+fn get_investor_record(principal: Principal) -> Option<InvestorRecord> {
+    Compliance::get_investor(principal)
+}
+
+#[ic_cdk_macros::update]
+fn add_compliance_rule(rule: ComplianceRule, actions: Vec<ComplianceAction>) -> Result<u64, ComplianceError> {
+    Compliance::add_rule(rule, actions)
+}
+
+#[ic_cdk_macros::update]
+fn set_compliance_rule_enabled(rule_id: u64, enabled: bool) -> Result<bool, ComplianceError> {
+    Compliance::set_rule_enabled(rule_id, enabled)
+}
+
+#[ic_cdk_macros::update]
+fn remove_compliance_rule(rule_id: u64) -> Result<bool, ComplianceError> {
+    Compliance::remove_rule(rule_id)
+}
+
+#[ic_cdk_macros::query]
+fn get_compliance_rules() -> Vec<RuleConfig> {
+    Compliance::get_rules()
+}
+
+#[ic_cdk_macros::query]
+fn check_compliance(action: ComplianceAction, parties: Vec<Principal>) -> Result<(), ComplianceRejection> {
+    Compliance::check(action, &parties)
+}
+
+// Freezes & Forced Transfers (applied through the admin council)
+#[ic_cdk_macros::query]
+fn get_frozen_assets(account: Principal) -> Vec<AssetClass> {
+    Enforcement::get_frozen_assets(account)
+}
+
+#[ic_cdk_macros::query]
+fn get_enforcement_log(start: u64, length: u64) -> Vec<EnforcementRecord> {
+    Enforcement::get_log(start, length)
+}
+
+// Emergency Pause
+#[ic_cdk_macros::update]
+fn pause_subsystem(subsystem: Subsystem, reason: String) -> Result<bool, PauseError> {
+    CircuitBreaker::pause(subsystem, reason)
+}
+
+#[ic_cdk_macros::update]
+fn unpause_subsystem(subsystem: Subsystem, reason: String) -> Result<bool, PauseError> {
+    CircuitBreaker::unpause(subsystem, reason)
+}
+
+#[ic_cdk_macros::query]
+fn is_paused(subsystem: Subsystem) -> bool {
+    CircuitBreaker::is_paused(subsystem)
+}
+
+#[ic_cdk_macros::query]
+fn get_paused_subsystems() -> Vec<Subsystem> {
+    CircuitBreaker::get_paused()
+}
+
+#[ic_cdk_macros::query]
+fn get_pause_log(start: u64, length: u64) -> Vec<PauseEvent> {
+    CircuitBreaker::get_log(start, length)
+}
+
+// Admin Council
+#[ic_cdk_macros::update]
+fn propose_admin_action(action: AdminAction) -> Result<u64, CouncilError> {
+    AdminCouncil::propose(action)
+}
+
+#[ic_cdk_macros::update]
+fn approve_admin_action(action_id: u64) -> Result<ActionStatus, CouncilError> {
+    AdminCouncil::approve(action_id)
+}
+
+#[ic_cdk_macros::update]
+fn cancel_admin_action(action_id: u64) -> Result<bool, CouncilError> {
+    AdminCouncil::cancel(action_id)
+}
+
+#[ic_cdk_macros::query]
+fn get_admin_action(action_id: u64) -> Option<PendingAction> {
+    AdminCouncil::get_action(action_id)
+}
+
+#[ic_cdk_macros::query]
+fn get_admin_actions(status: Option<ActionStatus>, start: u64, length: u64) -> Vec<PendingAction> {
+    AdminCouncil::get_actions(status, start, length)
+}
+
+#[ic_cdk_macros::query]
+fn get_admin_council() -> CouncilConfig {
+    AdminCouncil::get_council()
+}
+
+// Property Management
+#[ic_cdk_macros::update]
+fn list_property(
+    price: Amount,
+    location: String,
+    description: String,
+    rental_income: Option<RentalIncome>,
+) -> Result<Property, PropertyError> {
+    let caller = ic_cdk::api::caller();
+    let price = price.validate_positive().map_err(|reason| PropertyError::InvalidInput { reason })?;
+    if let Some(income) = &rental_income {
+        income.monthly_amount.validate().map_err(|reason| PropertyError::InvalidInput { reason })?;
+    }
+    let id = PROPERTY_COUNTER.with(|counter| {
+        let mut counter = counter.borrow_mut();
+        let id = *counter.get() + 1;
+        counter.set(id).expect("failed to write property counter");
+        id
+    });
+    
+    let mut property = Property {
+        id,
+        version: 1,
+        owner: caller,
+        price,
+        location,
+        description,
+        details: None,
+        status: PropertyStatus::Draft,
+        status_history: Vec::new(),
+        documents: Vec::new(),
+        rental_income,
+        token_id: None,
+        manager: None,
+    };
+    PropertyLifecycle::record_creation(&mut property, caller);
+    store_property(property.clone())?;
+    
+    Ok(property)
+}
+
+// Used by governance to apply passed property proposals
+pub(crate) fn set_property_status(property_id: u64, status: PropertyStatus) -> Result<(), PropertyError> {
+    update_property_record(property_id, |property| {
+        PropertyLifecycle::transition(property, status, Actor::System, None)?;
+        Ok(())
+    })
+}
+
+// Governance changes are recorded as made by the canister itself
+pub(crate) fn set_property_manager(property_id: u64, manager: Principal) -> Result<(), PropertyError> {
+    update_property_record(property_id, |property| {
+        PropertyHistory::record(property, PropertyChangeKind::ManagerChange, ic_cdk::api::id());
+        property.manager = Some(manager);
+        Ok(())
+    })
+}
+
+pub(crate) fn set_property_price(property_id: u64, price: Amount) -> Result<(), PropertyError> {
+    let price = price.validate_positive().map_err(|reason| PropertyError::InvalidInput { reason })?;
+
+    update_property_record(property_id, |property| {
+        PropertyHistory::record(property, PropertyChangeKind::PriceChange, ic_cdk::api::id());
+        property.price = price;
+        Ok(())
+    })
+}
+
+#[ic_cdk_macros::query]
+fn get_property(property_id: u64) -> Option<Property> {
+    PROPERTIES.with(|properties| properties.borrow().get(&property_id))
+}
+
+/// Every property in listing order, a page at a time.
+#[ic_cdk_macros::query]
+fn get_all_properties(cursor: Option<String>, limit: u64) -> Result<PropertyPage, PropertyError> {
+    search_properties(PropertyFilter::default(), PropertySort::Oldest, cursor, limit)
+}
+
+#[ic_cdk_macros::query]
+fn get_user_properties(user: Principal) -> Vec<Property> {
+    PROPERTIES.with(|properties| {
+        let properties = properties.borrow();
+        PropertySearch::owned_by(user)
+            .iter()
+            .filter_map(|id| properties.get(id))
+            .collect()
+    })
 }
 
-#[derive(CandidType, Deserialize, Clone, Debug)]
-pub struct Document {
-    pub id: u64,
-    pub doc_type: DocumentType,
-    pub hash: String,
-    pub timestamp: u64,
+#[ic_cdk_macros::query]
+fn search_properties(
+    filter: PropertyFilter,
+    sort: PropertySort,
+    cursor: Option<String>,
+    limit: u64,
+) -> Result<PropertyPage, PropertyError> {
+    PROPERTIES.with(|properties| PropertySearch::search(&properties.borrow(), filter, sort, cursor, limit))
+        .map_err(|reason| PropertyError::InvalidInput { reason })
 }
 
-#[derive(CandidType, Deserialize, Clone, Debug)]
-pub enum DocumentType {
-    Deed,
-    Title,
-    Contract,
-    Inspection,
-    Other,
+#[ic_cdk_macros::query]
+fn search_properties_near(
+    center: GeoPoint,
+    radius_km: f64,
+    filter: PropertyFilter,
+    start: u64,
+    length: u64,
+) -> Result<GeoPage, PropertyError> {
+    PROPERTIES.with(|properties| {
+        GeoSearch::within_radius(&properties.borrow(), center, radius_km, &filter, start, length)
+    })
+    .map_err(|reason| PropertyError::InvalidInput { reason })
 }
 
-#[derive(CandidType, Deserialize, Clone, Debug)]
-pub struct RentalIncome {
-    pub monthly_amount: u64,
-    pub last_distribution: u64,
-    pub distribution_frequency: u64,
+#[ic_cdk_macros::query]
+fn search_properties_in_box(
+    bbox: BoundingBox,
+    filter: PropertyFilter,
+    start: u64,
+    length: u64,
+) -> Result<GeoPage, PropertyError> {
+    PROPERTIES.with(|properties| GeoSearch::in_bounding_box(&properties.borrow(), bbox, &filter, start, length))
+        .map_err(|reason| PropertyError::InvalidInput { reason })
 }
 
-thread_local! {
-    static OWNER: RefCell<Principal> = RefCell::new(Principal::anonymous());
-    static PROPERTY_COUNTER: RefCell<u64> = RefCell::new(0);
-    static PROPERTIES: RefCell<HashMap<u64, Property>> = RefCell::new(HashMap::new());
+/// Returns the new document's id.
+#[ic_cdk_macros::update]
+fn add_document(
+    property_id: u64,
+    doc_type: DocumentType,
+    hash: String,
+    expires_at: Option<u64>,
+) -> Result<u64, PropertyError> {
+    let caller = ic_cdk::api::caller();
+    
+    update_property_record(property_id, |property| {
+
+        if property.owner != caller {
+            return Err(PropertyError::NotOwner { property_id, owner: property.owner });
+        }
+        DocumentVersions::add(property, doc_type, hash, expires_at)
+    })
 }
 
-#[ic_cdk_macros::init]
-fn init() {
+#[ic_cdk_macros::update]
+fn supersede_document(
+    property_id: u64,
+    document_id: u64,
+    hash: String,
+    expires_at: Option<u64>,
+) -> Result<u64, PropertyError> {
     let caller = ic_cdk::api::caller();
-    OWNER.with(|owner| {
-        *owner.borrow_mut() = caller;
-    });
+
+    update_property_record(property_id, |property| {
+
+        if property.owner != caller {
+            return Err(PropertyError::NotOwner { property_id, owner: property.owner });
+        }
+        DocumentVersions::supersede(property, document_id, hash, expires_at)
+    })
 }
 
-// RET Token Management
 #[ic_cdk_macros::update]
-fn initialize_ret(owner: Principal, website: Option<String>, social_links: Option<Vec<String>>) -> bool {
-    RETToken::initialize(owner, website, social_links)
-}
+fn revoke_document(property_id: u64, document_id: u64, reason: String) -> Result<bool, PropertyError> {
+    let caller = ic_cdk::api::caller();
 
-#[ic_cdk_macros::query]
-fn get_ret_metadata() -> Option<RETTokenMetadata> {
-    RETToken::get_metadata()
+    update_property_record(property_id, |property| {
+        DocumentVersions::revoke(property, document_id, reason, caller)?;
+        Ok(true)
+    })
 }
 
 #[ic_cdk_macros::query]
-fn balance_of(owner: Principal) -> u64 {
-    RETToken::balance_of(owner)
+fn get_current_documents(property_id: u64, doc_type: Option<DocumentType>) -> Vec<Document> {
+    get_property(property_id)
+        .map(|property| DocumentVersions::current(&property, doc_type))
+        .unwrap_or_default()
 }
 
 #[ic_cdk_macros::query]
-fn staked_balance_of(owner: Principal) -> u64 {
-    RETToken::staked_balance_of(owner)
+fn get_document_versions(property_id: u64, document_id: u64) -> Result<Vec<Document>, PropertyError> {
+    let property = get_property(property_id).ok_or(PropertyError::NotFound { property_id })?;
+    DocumentVersions::chain(&property, document_id)
 }
 
 #[ic_cdk_macros::update]
-fn stake(amount: u64, duration: u64) -> Result<bool, String> {
-    RETToken::stake(amount, duration)
+fn attest_document(
+    property_id: u64,
+    document_id: u64,
+    verdict: AttestationVerdict,
+    notes: Option<String>,
+) -> Result<u64, PropertyError> {
+    let property = get_property(property_id).ok_or(PropertyError::NotFound { property_id })?;
+    DocumentAttestations::attest(&property, document_id, verdict, notes)
 }
 
-#[ic_cdk_macros::update]
-fn unstake() -> Result<u64, String> {
-    RETToken::unstake()
+#[ic_cdk_macros::query]
+fn get_document_attestations(property_id: u64, document_id: u64) -> Vec<Attestation> {
+    DocumentAttestations::get_attestations(property_id, document_id)
 }
 
-#[ic_cdk_macros::update]
-fn transfer(args: TransferArgs) -> Result<bool, String> {
-    RETToken::transfer(args)
+#[ic_cdk_macros::query]
+fn get_document_status(property_id: u64, document_id: u64) -> Option<VerificationStatus> {
+    let property = get_property(property_id)?;
+    property.documents.iter()
+        .find(|document| document.id == document_id)
+        .map(|document| DocumentAttestations::status(property_id, document))
 }
 
 #[ic_cdk_macros::update]
-fn airdrop_ret(recipients: Vec<(Principal, u64)>) -> Result<bool, String> {
-    RETToken::airdrop(recipients)
+fn set_required_attestations(doc_type: DocumentType, count: u8) -> Result<bool, PropertyError> {
+    DocumentAttestations::set_required_attestations(doc_type, count)
 }
 
 #[ic_cdk_macros::query]
-fn get_ret_stats() -> TokenStats {
-    RETToken::get_stats()
+fn get_required_attestations(doc_type: DocumentType) -> u8 {
+    DocumentAttestations::required_attestations(doc_type)
 }
 
-// Payment Management
 #[ic_cdk_macros::update]
-fn initialize_payment_manager(ret_ledger: Principal) {
+fn transition_property(property_id: u64, status: PropertyStatus, note: Option<String>) -> Result<bool, PropertyError> {
     let caller = ic_cdk::api::caller();
-    OWNER.with(|owner| {
-        assert_eq!(caller, *owner.borrow(), "Only owner can initialize payment manager");
-    });
-    payments::initialize_payment_manager(ret_ledger);
+
+    update_property_record(property_id, |property| {
+        PropertyLifecycle::transition(property, status, Actor::Caller(caller), note)?;
+        Ok(true)
+    })
+}
+
+#[ic_cdk_macros::query]
+fn get_property_transitions(property_id: u64) -> Vec<PropertyStatus> {
+    get_property(property_id)
+        .map(|property| PropertyLifecycle::next_statuses(property.status))
+        .unwrap_or_default()
 }
 
-// Property Management
 #[ic_cdk_macros::update]
-fn list_property(price: f64, location: String, description: String, rental_income: Option<RentalIncome>) -> Property {
+fn update_property(property_id: u64, update: PropertyUpdate) -> Result<bool, PropertyError> {
     let caller = ic_cdk::api::caller();
-    let id = PROPERTY_COUNTER.with(|counter| {
-        let mut counter = counter.borrow_mut();
-        *counter += 1;
-        *counter
-    });
-    
-    let property = Property {
-        id,
-        owner: caller,
-        price,
-        location,
-        description,
-        status: PropertyStatus::Listed,
-        documents: Vec::new(),
-        rental_income,
-        token_id: None,
-    };
-    
-    PROPERTIES.with(|properties| {
-        properties.borrow_mut().insert(id, property.clone());
-    });
-    
-    property
+    let update = PropertyHistory::validate_update(update).map_err(|reason| PropertyError::InvalidInput { reason })?;
+    // Prices are valuations, so owners need an appraiser to change them
+    if update.price.is_some() && !AccessControl::has_role(caller, Role::Appraiser) {
+        return Err(PropertyError::Unauthorized);
+    }
+
+    update_property_record(property_id, |property| {
+
+        if property.owner != caller {
+            return Err(PropertyError::NotOwner { property_id, owner: property.owner });
+        }
+        if matches!(property.status, PropertyStatus::UnderContract | PropertyStatus::Sold | PropertyStatus::Archived) {
+            return Err(PropertyError::StatusLocked { status: property.status });
+        }
+
+        PropertyHistory::record(property, PropertyChangeKind::Update, caller);
+        if let Some(price) = update.price {
+            property.price = price;
+        }
+        if let Some(location) = update.location {
+            property.location = location;
+        }
+        if let Some(description) = update.description {
+            property.description = description;
+        }
+        if let Some(rental_income) = update.rental_income {
+            property.rental_income = Some(rental_income);
+        }
+        if let Some(details) = update.details {
+            property.location = PropertyData::location_line(&details);
+            property.details = Some(details);
+        }
+        Ok(true)
+    })
 }
 
-#[ic_cdk_macros::query]
-fn get_property(property_id: u64) -> Option<Property> {
-    PROPERTIES.with(|properties| properties.borrow().get(&property_id).cloned())
+/// Records an appraiser's valuation as the property's price.
+#[ic_cdk_macros::update]
+fn appraise_property(property_id: u64, price: Amount) -> Result<bool, PropertyError> {
+    let appraiser = AccessControl::require(Role::Appraiser).map_err(|_| PropertyError::Unauthorized)?;
+    let price = price.validate_positive().map_err(|reason| PropertyError::InvalidInput { reason })?;
+
+    update_property_record(property_id, |property| {
+        if matches!(property.status, PropertyStatus::UnderContract | PropertyStatus::Sold | PropertyStatus::Archived) {
+            return Err(PropertyError::StatusLocked { status: property.status });
+        }
+
+        PropertyHistory::record(property, PropertyChangeKind::PriceChange, appraiser);
+        property.price = price;
+        Ok(true)
+    })
 }
 
-#[ic_cdk_macros::query]
-fn get_all_properties() -> Vec<Property> {
-    PROPERTIES.with(|properties| properties.borrow().values().cloned().collect())
+/// Backfills structured details for records created before they existed, in id order.
+#[ic_cdk_macros::update]
+fn migrate_property_details(start: u64, length: u64) -> Result<MigrationReport, PropertyError> {
+    AccessControl::require(Role::Admin).map_err(|_| PropertyError::Unauthorized)?;
+
+    let (batch, total): (Vec<Property>, u64) = PROPERTIES.with(|properties| {
+        let properties = properties.borrow();
+        let batch = properties.iter()
+            .skip(start as usize)
+            .take(length as usize)
+            .map(|(_, property)| property)
+            .collect();
+        (batch, properties.len())
+    });
+
+    let mut report = MigrationReport { migrated: 0, already_structured: 0, next_start: None };
+    for mut property in batch {
+        if property.details.is_some() {
+            report.already_structured += 1;
+        } else {
+            property.details = Some(PropertyData::from_legacy(&property.location));
+            store_property(property)?;
+            report.migrated += 1;
+        }
+    }
+
+    let next = start.saturating_add(length);
+    if next < total {
+        report.next_start = Some(next);
+    }
+    Ok(report)
 }
 
-#[ic_cdk_macros::query]
-fn get_user_properties(user: Principal) -> Vec<Property> {
-    PROPERTIES.with(|properties| {
-        properties
-            .borrow()
-            .values()
-            .filter(|p| p.owner == user)
-            .cloned()
-            .collect()
+#[ic_cdk_macros::update]
+fn delist_property(property_id: u64, reason: Option<String>) -> Result<bool, PropertyError> {
+    let caller = ic_cdk::api::caller();
+
+    update_property_record(property_id, |property| {
+
+        PropertyLifecycle::ensure_transition(property, PropertyStatus::Delisted, Actor::Caller(caller), &reason)?;
+        PropertyHistory::record(property, PropertyChangeKind::Delist, caller);
+        PropertyLifecycle::transition(property, PropertyStatus::Delisted, Actor::Caller(caller), reason)?;
+        Ok(true)
     })
 }
 
+/// Tokenized properties move with their ICRC-7 token, so the token transfer's
+/// freeze, compliance and policy checks apply and both owners stay in step.
 #[ic_cdk_macros::update]
-fn add_document(property_id: u64, doc_type: DocumentType, hash: String) -> bool {
+fn transfer_property_ownership(property_id: u64, new_owner: Principal) -> Result<bool, PropertyError> {
     let caller = ic_cdk::api::caller();
-    let timestamp = ic_cdk::api::time();
-    
-    PROPERTIES.with(|properties| {
-        let mut properties = properties.borrow_mut();
-        if let Some(property) = properties.get_mut(&property_id) {
-            if property.owner != caller {
-                return false;
-            }
-            
-            let doc_id = property.documents.len() as u64 + 1;
-            property.documents.push(Document {
-                id: doc_id,
-                doc_type,
-                hash,
-                timestamp,
-            });
-            true
-        } else {
-            false
+    let property = get_property(property_id).ok_or(PropertyError::NotFound { property_id })?;
+
+    if property.owner != caller {
+        return Err(PropertyError::NotOwner { property_id, owner: property.owner });
+    }
+    if new_owner == caller {
+        return Err(PropertyError::InvalidInput { reason: "Property is already owned by this account".to_string() });
+    }
+    if property.status == PropertyStatus::Archived {
+        return Err(PropertyError::StatusLocked { status: property.status });
+    }
+
+    match property.token_id {
+        Some(token_id) => {
+            ICRC7Token::transfer(ICRC7TransferArgs {
+                spender_subaccount: None,
+                from: caller,
+                to: new_owner,
+                token_id,
+                memo: Some(format!("property:{}", property_id).into_bytes()),
+                created_at_time: None,
+            })
+            .map_err(|error| PropertyError::TokenTransferFailed { error })?;
         }
-    })
+        None => update_property_record(property_id, |property| {
+            PropertyHistory::record(property, PropertyChangeKind::OwnershipTransfer, caller);
+            property.owner = new_owner;
+            Ok(())
+        })?,
+    }
+
+    Ok(true)
+}
+
+// Called by the ICRC-7 ledger whenever a token changes hands
+pub(crate) fn sync_property_owner(token_id: u64, new_owner: Principal, changed_by: Principal) {
+    let property = PROPERTIES.with(|properties| {
+        properties.borrow()
+            .iter()
+            .map(|(_, property)| property)
+            .find(|property| property.token_id == Some(token_id))
+    });
+
+    if let Some(mut property) = property {
+        if property.owner != new_owner {
+            PropertyHistory::record(&mut property, PropertyChangeKind::OwnershipTransfer, changed_by);
+            property.owner = new_owner;
+            // Trapping rolls back the token transfer as well, so the two owners never diverge
+            store_property(property).expect("failed to store property owner");
+        }
+    }
+}
+
+#[ic_cdk_macros::query]
+fn get_property_revisions(property_id: u64) -> Vec<PropertyRevision> {
+    PropertyHistory::get_revisions(property_id)
+}
+
+#[ic_cdk_macros::query]
+fn get_property_revision(property_id: u64, version: u64) -> Option<PropertyRevision> {
+    PropertyHistory::get_revision(property_id, version)
 }
 
 #[ic_cdk_macros::update]
@@ -226,24 +1136,21 @@ fn tokenize_property(
     total_supply: u64,
     available_supply: u64,
     royalty_percentage: Option<u16>,
-) -> Result<bool, String> {
+) -> Result<bool, PropertyError> {
     let caller = ic_cdk::api::caller();
     
     // Verify property ownership
-    PROPERTIES.with(|properties| {
-        let mut properties = properties.borrow_mut();
-        let property = properties.get_mut(&property_id)
-            .ok_or("Property not found")?;
+    update_property_record(property_id, |property| {
         
         if property.owner != caller {
-            return Err("Not the property owner".to_string());
+            return Err(PropertyError::NotOwner { property_id, owner: property.owner });
         }
         
-        if property.token_id.is_some() {
-            return Err("Property already tokenized".to_string());
-        }
+        PropertyLifecycle::ensure_tokenizable(property)?;
+        DocumentAttestations::ensure_verified(property, &TOKENIZATION_DOCUMENTS)?;
         
         // Create ICRC7 token
+        let details = property.details.as_ref();
         let token_metadata = ICRC7TokenMetadata {
             name,
             symbol,
@@ -257,17 +1164,21 @@ fn tokenize_property(
             image: None,
             royalties: royalty_percentage,
             royalty_recipient: Some(caller),
-            tags: Some(vec!["real-estate".to_string()]),
+            tags: Some(details.map(PropertyData::token_tags).unwrap_or_else(|| vec!["real-estate".to_string()])),
+            attributes: details.map(PropertyData::token_attributes),
             created_at: ic_cdk::api::time(),
             modified_at: ic_cdk::api::time(),
         };
         
-        let token_id = ICRC7Token::mint(caller, token_metadata, false)
-            .ok_or("Failed to mint token")?;
+        // A policy set beforehand through `set_transfer_policy` restricts the token
+        let restricted = TransferPolicies::get_policy(property_id).is_some();
+        let token_id = ICRC7Token::mint(caller, token_metadata, restricted)
+            .ok_or(PropertyError::MintFailed)?;
+        TransferPolicies::register_issuance(property_id, token_id);
         
         // Update property status
-        property.status = PropertyStatus::Tokenized;
         property.token_id = Some(token_id);
+        PropertyLifecycle::transition(property, PropertyStatus::Tokenized, Actor::System, None)?;
         
         Ok(true)
     })
@@ -284,8 +1195,9 @@ fn initialize_collection(
     logo: Option<Vec<u8>>,
     website: Option<String>,
     social_links: Option<Vec<String>>,
-) -> bool {
-    ICRC7Token::initialize_collection(
+) -> Result<bool, TokenError> {
+    AccessControl::require(Role::Admin).map_err(|_| TokenError::Unauthorized)?;
+    Ok(ICRC7Token::initialize_collection(
         name,
         symbol,
         description,
@@ -295,41 +1207,204 @@ fn initialize_collection(
         logo,
         website,
         social_links,
-    )
+    ))
 }
 
 #[ic_cdk_macros::update]
 fn fractionalize_property(
     property_id: u64,
     shares: Vec<(Principal, u16)>,
-) -> Result<bool, String> {
+) -> Result<bool, MarketplaceError> {
     let caller = ic_cdk::api::caller();
     
     // Verify property ownership
-    PROPERTIES.with(|properties| {
-        let properties = properties.borrow();
-        let property = properties.get(&property_id)
-            .ok_or("Property not found")?;
-        
-        if property.owner != caller {
-            return Err("Not the property owner".to_string());
-        }
-        
-        if property.token_id.is_none() {
-            return Err("Property not tokenized".to_string());
-        }
-        
-        let token_id = property.token_id.unwrap();
-        
-        // Verify caller owns the token
-        if !ICRC7Token::owner_of(token_id)
-            .map(|owner| owner == caller)
-            .unwrap_or(false) {
-            return Err("Not the token owner".to_string());
-        }
-        
-        // Call marketplace to fractionalize
-        marketplace::Marketplace::fractionalize_property(property_id, shares)
-            .map(|_| true)
-    })
+    let property = get_property(property_id)
+        .ok_or(PropertyError::NotFound { property_id })?;
+    
+    if property.owner != caller {
+        return Err(PropertyError::NotOwner { property_id, owner: property.owner }.into());
+    }
+    
+    let token_id = property.token_id
+        .ok_or(PropertyError::NotTokenized { property_id })?;
+    
+    // Verify caller owns the token
+    if !ICRC7Token::owner_of(token_id)
+        .map(|owner| owner == caller)
+        .unwrap_or(false) {
+        return Err(MarketplaceError::NotTokenOwner { token_id });
+    }
+    
+    // Call marketplace to fractionalize
+    marketplace::Marketplace::fractionalize_property(property_id, token_id, shares)
+        .map(|_| true)
+}
+
+// Transfer Policies & Share Transfers
+#[ic_cdk_macros::update]
+fn set_transfer_policy(property_id: u64, policy: TransferPolicy) -> Result<bool, MarketplaceError> {
+    let caller = ic_cdk::api::caller();
+    let property = get_property(property_id).ok_or(PropertyError::NotFound { property_id })?;
+
+    // The property owner or a compliance officer; only the latter can loosen an issued policy
+    let is_officer = AccessControl::has_role(caller, Role::ComplianceOfficer);
+    if property.owner != caller && !is_officer {
+        return Err(PropertyError::Unauthorized.into());
+    }
+
+    TransferPolicies::set_policy(property_id, policy, is_officer)?;
+    Ok(true)
+}
+
+#[ic_cdk_macros::query]
+fn get_transfer_policy(property_id: u64) -> Option<PropertyPolicy> {
+    TransferPolicies::get_policy(property_id)
+}
+
+#[ic_cdk_macros::query]
+fn get_property_shares(property_id: u64) -> Option<Vec<PropertyShare>> {
+    marketplace::Marketplace::get_property_shares(property_id)
+}
+
+#[ic_cdk_macros::update]
+fn transfer_property_shares(property_id: u64, to: Principal, amount: u16) -> Result<bool, MarketplaceError> {
+    marketplace::Marketplace::transfer_shares(property_id, to, amount)
+}
+
+#[ic_cdk_macros::update]
+fn offer_property_shares(
+    property_id: u64,
+    buyer: Principal,
+    amount: u16,
+    price: Amount,
+) -> Result<u64, MarketplaceError> {
+    marketplace::Marketplace::offer_shares(property_id, buyer, amount, price)
+}
+
+#[ic_cdk_macros::update]
+fn exercise_share_offer(offer_id: u64) -> Result<bool, MarketplaceError> {
+    marketplace::Marketplace::exercise_rofr(offer_id)
+}
+
+#[ic_cdk_macros::update]
+fn complete_share_offer(offer_id: u64) -> Result<bool, MarketplaceError> {
+    marketplace::Marketplace::complete_share_offer(offer_id)
+}
+
+#[ic_cdk_macros::update]
+fn cancel_share_offer(offer_id: u64) -> Result<bool, MarketplaceError> {
+    marketplace::Marketplace::cancel_share_offer(offer_id)
+}
+
+#[ic_cdk_macros::query]
+fn get_share_offer(offer_id: u64) -> Option<ShareOffer> {
+    marketplace::Marketplace::get_share_offer(offer_id)
+}
+
+#[ic_cdk_macros::query]
+fn get_share_offers(property_id: u64) -> Vec<ShareOffer> {
+    marketplace::Marketplace::get_share_offers(property_id)
+}
+
+// Cap Table
+#[ic_cdk_macros::query]
+fn get_cap_table(property_id: u64, as_of: Option<u64>, start: u64, length: u64) -> Result<CapTable, PropertyError> {
+    let property = get_property(property_id).ok_or(PropertyError::NotFound { property_id })?;
+    CapTables::ensure_can_view(&property, ic_cdk::api::caller())?;
+    Ok(CapTables::get_cap_table(property_id, as_of, start, length))
+}
+
+#[ic_cdk_macros::query]
+fn get_share_movements(property_id: u64, start: u64, length: u64) -> Result<Vec<ShareMovement>, PropertyError> {
+    let property = get_property(property_id).ok_or(PropertyError::NotFound { property_id })?;
+    CapTables::ensure_can_view(&property, ic_cdk::api::caller())?;
+    Ok(CapTables::get_movements(property_id, start, length))
+}
+
+#[ic_cdk_macros::query]
+fn export_cap_table(property_id: u64, as_of: Option<u64>, format: ExportFormat) -> Result<String, PropertyError> {
+    let property = get_property(property_id).ok_or(PropertyError::NotFound { property_id })?;
+    CapTables::ensure_can_view(&property, ic_cdk::api::caller())?;
+    Ok(CapTables::export(property_id, as_of, format))
+}
+
+#[ic_cdk_macros::update]
+async fn create_cap_table_link(property_id: u64) -> Result<String, PropertyError> {
+    let caller = ic_cdk::api::caller();
+    let (seed,) = ic_cdk::api::management_canister::main::raw_rand()
+        .await
+        .map_err(|(_, reason)| PropertyError::RandomnessUnavailable { reason })?;
+
+    let property = get_property(property_id).ok_or(PropertyError::NotFound { property_id })?;
+    CapTables::create_link(&property, caller, seed)
+}
+
+// Document Storage
+#[ic_cdk_macros::update]
+fn begin_document_upload(
+    property_id: u64,
+    document_id: u64,
+    content_type: String,
+    total_size: u64,
+    access: DocumentAccess,
+) -> Result<u64, PropertyError> {
+    let property = get_property(property_id).ok_or(PropertyError::NotFound { property_id })?;
+    DocumentStore::begin_upload(&property, document_id, content_type, total_size, access)
+}
+
+#[ic_cdk_macros::update]
+fn upload_document_chunk(upload_id: u64, index: u64, bytes: Vec<u8>) -> Result<u64, PropertyError> {
+    DocumentStore::upload_chunk(upload_id, index, bytes)
+}
+
+#[ic_cdk_macros::update]
+fn finish_document_upload(upload_id: u64) -> Result<StoredDocument, PropertyError> {
+    let upload = DocumentStore::get_upload(upload_id).ok_or(PropertyError::UploadNotFound { upload_id })?;
+    let property = get_property(upload.property_id)
+        .ok_or(PropertyError::NotFound { property_id: upload.property_id })?;
+    DocumentStore::finish_upload(&property, upload_id)
+}
+
+#[ic_cdk_macros::query]
+fn get_document_upload(upload_id: u64) -> Option<DocumentUpload> {
+    DocumentStore::get_upload(upload_id)
+}
+
+#[ic_cdk_macros::query]
+fn get_stored_document(property_id: u64, document_id: u64) -> Option<StoredDocument> {
+    DocumentStore::get_document(property_id, document_id)
+}
+
+#[ic_cdk_macros::update]
+fn set_document_access(property_id: u64, document_id: u64, access: DocumentAccess) -> Result<bool, PropertyError> {
+    let property = get_property(property_id).ok_or(PropertyError::NotFound { property_id })?;
+    DocumentStore::set_access(&property, document_id, access)
+}
+
+#[ic_cdk_macros::query]
+fn get_document_chunk(property_id: u64, document_id: u64, index: u64) -> Result<Vec<u8>, PropertyError> {
+    let property = get_property(property_id).ok_or(PropertyError::NotFound { property_id })?;
+    DocumentStore::read_chunk_as(&property, document_id, index, ic_cdk::api::caller())
+}
+
+#[ic_cdk_macros::update]
+async fn create_document_link(property_id: u64, document_id: u64) -> Result<String, PropertyError> {
+    let caller = ic_cdk::api::caller();
+    let (seed,) = ic_cdk::api::management_canister::main::raw_rand()
+        .await
+        .map_err(|(_, reason)| PropertyError::RandomnessUnavailable { reason })?;
+
+    let property = get_property(property_id).ok_or(PropertyError::NotFound { property_id })?;
+    DocumentStore::create_link(&property, document_id, caller, seed)
+}
+
+// HTTP Gateway
+#[ic_cdk_macros::query]
+fn http_request(request: HttpRequest) -> HttpResponse {
+    HttpGateway::handle(request)
+}
+
+#[ic_cdk_macros::query]
+fn http_request_streaming_callback(token: StreamingToken) -> StreamingCallbackHttpResponse {
+    HttpGateway::stream_document(token)
 }
@@ -0,0 +1,318 @@
+use candid::{CandidType, Deserialize, Principal};
+use ic_cdk::api::time;
+
+use crate::access_control::{AccessControl, Role};
+use crate::document_versions::DocumentVersions;
+use crate::{DocumentType, Property, PropertyError, PropertyStatus};
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct StatusChange {
+    // None for the initial Draft entry
+    pub from: Option<PropertyStatus>,
+    pub to: PropertyStatus,
+    pub changed_by: Principal,
+    pub timestamp: u64,
+    pub note: Option<String>,
+}
+
+// Who started a transition
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Actor {
+    Caller(Principal),
+    // Tokenization and passed governance proposals
+    System,
+}
+
+// Who may take an edge; System takes SystemOnly edges and nothing else
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Authority {
+    Owner,
+    OwnerOrManager,
+    Verifier,
+    OwnerOrAdmin,
+    SystemOnly,
+}
+
+const TRANSITIONS: &[(PropertyStatus, PropertyStatus, Authority)] = &[
+    (PropertyStatus::Draft, PropertyStatus::PendingVerification, Authority::Owner),
+    (PropertyStatus::Draft, PropertyStatus::Archived, Authority::OwnerOrAdmin),
+    (PropertyStatus::PendingVerification, PropertyStatus::Verified, Authority::Verifier),
+    (PropertyStatus::PendingVerification, PropertyStatus::Draft, Authority::Verifier),
+    (PropertyStatus::Verified, PropertyStatus::Listed, Authority::Owner),
+    (PropertyStatus::Verified, PropertyStatus::Tokenized, Authority::SystemOnly),
+    (PropertyStatus::Listed, PropertyStatus::Tokenized, Authority::SystemOnly),
+    (PropertyStatus::Listed, PropertyStatus::UnderContract, Authority::OwnerOrManager),
+    (PropertyStatus::Listed, PropertyStatus::Delisted, Authority::Owner),
+    (PropertyStatus::Delisted, PropertyStatus::Listed, Authority::Owner),
+    (PropertyStatus::Delisted, PropertyStatus::Archived, Authority::OwnerOrAdmin),
+    (PropertyStatus::Tokenized, PropertyStatus::UnderContract, Authority::SystemOnly),
+    (PropertyStatus::UnderContract, PropertyStatus::Listed, Authority::Owner),
+    (PropertyStatus::UnderContract, PropertyStatus::Tokenized, Authority::SystemOnly),
+    (PropertyStatus::UnderContract, PropertyStatus::Sold, Authority::Owner),
+    (PropertyStatus::Sold, PropertyStatus::Archived, Authority::OwnerOrAdmin),
+];
+
+/// Property status state machine. Every status change goes through `transition`,
+/// which checks the edge exists, the actor may take it and its preconditions hold,
+/// then appends to the property's status history.
+pub struct PropertyLifecycle;
+
+impl PropertyLifecycle {
+    /// Statuses reachable from `from` by a caller, ignoring who that caller is.
+    pub fn next_statuses(from: PropertyStatus) -> Vec<PropertyStatus> {
+        TRANSITIONS.iter()
+            .filter(|(edge_from, _, authority)| *edge_from == from && *authority != Authority::SystemOnly)
+            .map(|(_, to, _)| *to)
+            .collect()
+    }
+
+    pub(crate) fn record_creation(property: &mut Property, owner: Principal) {
+        property.status = PropertyStatus::Draft;
+        property.status_history.push(StatusChange {
+            from: None,
+            to: PropertyStatus::Draft,
+            changed_by: owner,
+            timestamp: time(),
+            note: None,
+        });
+    }
+
+    /// Checks a transition without applying it.
+    pub(crate) fn ensure_transition(
+        property: &Property,
+        to: PropertyStatus,
+        actor: Actor,
+        note: &Option<String>,
+    ) -> Result<(), PropertyError> {
+        let from = property.status;
+        let authority = TRANSITIONS.iter()
+            .find(|(edge_from, edge_to, _)| *edge_from == from && *edge_to == to)
+            .map(|(_, _, authority)| *authority)
+            .ok_or(PropertyError::InvalidTransition { from, to })?;
+
+        match actor {
+            Actor::Caller(caller) => Self::authorize(property, to, authority, caller)?,
+            // Governance cannot stand in for the owner, manager or a verifier
+            Actor::System if authority != Authority::SystemOnly => {
+                return Err(PropertyError::TransitionNotAllowed { from, to });
+            }
+            Actor::System => {}
+        }
+        Self::check_preconditions(property, to, note)
+    }
+
+    pub(crate) fn transition(
+        property: &mut Property,
+        to: PropertyStatus,
+        actor: Actor,
+        note: Option<String>,
+    ) -> Result<(), PropertyError> {
+        Self::ensure_transition(property, to, actor, &note)?;
+
+        let changed_by = match actor {
+            Actor::Caller(caller) => caller,
+            Actor::System => ic_cdk::api::id(),
+        };
+        property.status_history.push(StatusChange {
+            from: Some(property.status),
+            to,
+            changed_by,
+            timestamp: time(),
+            note,
+        });
+        property.status = to;
+        Ok(())
+    }
+
+    /// Checks the property can be tokenized from its current status, before anything is minted.
+    pub(crate) fn ensure_tokenizable(property: &Property) -> Result<(), PropertyError> {
+        if let Some(token_id) = property.token_id {
+            return Err(PropertyError::AlreadyTokenized { token_id });
+        }
+        let allowed = TRANSITIONS.iter()
+            .any(|(from, to, _)| *from == property.status && *to == PropertyStatus::Tokenized);
+        if !allowed {
+            return Err(PropertyError::InvalidTransition { from: property.status, to: PropertyStatus::Tokenized });
+        }
+        Ok(())
+    }
+
+    fn authorize(property: &Property, to: PropertyStatus, authority: Authority, caller: Principal) -> Result<(), PropertyError> {
+        let allowed = match authority {
+            Authority::Owner => property.owner == caller,
+            Authority::OwnerOrManager => {
+                property.owner == caller
+                    || (property.manager == Some(caller) && AccessControl::has_role(caller, Role::PropertyManager))
+            }
+            Authority::Verifier => AccessControl::has_role(caller, Role::PropertyVerifier),
+            Authority::OwnerOrAdmin => property.owner == caller || AccessControl::has_role(caller, Role::Admin),
+            Authority::SystemOnly => false,
+        };
+
+        if !allowed {
+            return Err(PropertyError::TransitionNotAllowed { from: property.status, to });
+        }
+        Ok(())
+    }
+
+    fn check_preconditions(property: &Property, to: PropertyStatus, note: &Option<String>) -> Result<(), PropertyError> {
+        match (property.status, to) {
+            (_, PropertyStatus::PendingVerification) | (_, PropertyStatus::Verified) => {
+                Self::require_document(property, DocumentType::Deed)?;
+                Self::require_document(property, DocumentType::Title)
+            }
+            (PropertyStatus::PendingVerification, PropertyStatus::Draft) => {
+                let has_reason = note.as_deref().is_some_and(|note| !note.trim().is_empty());
+                if !has_reason {
+                    return Err(PropertyError::ReasonRequired);
+                }
+                Ok(())
+            }
+            (_, PropertyStatus::Listed) => {
+                if property.token_id.is_some() {
+                    return Err(PropertyError::InvalidTransition { from: property.status, to });
+                }
+                if property.price.is_zero() {
+                    return Err(PropertyError::InvalidInput { reason: "Price must be positive".to_string() });
+                }
+                Ok(())
+            }
+            (_, PropertyStatus::Tokenized) => {
+                if property.token_id.is_none() {
+                    return Err(PropertyError::NotTokenized { property_id: property.id });
+                }
+                Ok(())
+            }
+            (_, PropertyStatus::Sold) => Self::require_document(property, DocumentType::Contract),
+            _ => Ok(()),
+        }
+    }
+
+    fn require_document(property: &Property, doc_type: DocumentType) -> Result<(), PropertyError> {
+        let now = time();
+        let present = property.documents.iter()
+            .any(|document| document.doc_type == doc_type && DocumentVersions::is_current(document, now));
+        if !present {
+            return Err(PropertyError::MissingDocument { doc_type });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amount::{Amount, Currency};
+
+    const ALL: [PropertyStatus; 9] = [
+        PropertyStatus::Draft,
+        PropertyStatus::PendingVerification,
+        PropertyStatus::Verified,
+        PropertyStatus::Listed,
+        PropertyStatus::UnderContract,
+        PropertyStatus::Sold,
+        PropertyStatus::Tokenized,
+        PropertyStatus::Delisted,
+        PropertyStatus::Archived,
+    ];
+
+    fn account(id: u8) -> Principal {
+        Principal::from_slice(&[id; 29])
+    }
+
+    fn property(status: PropertyStatus) -> Property {
+        Property {
+            id: 1,
+            version: 1,
+            owner: account(1),
+            price: Amount::new(100_000, Currency::USD),
+            location: String::new(),
+            description: String::new(),
+            details: None,
+            status,
+            status_history: Vec::new(),
+            documents: Vec::new(),
+            rental_income: None,
+            token_id: None,
+            manager: Some(account(2)),
+        }
+    }
+
+    fn is_edge(from: PropertyStatus, to: PropertyStatus) -> bool {
+        TRANSITIONS.iter().any(|(edge_from, edge_to, _)| *edge_from == from && *edge_to == to)
+    }
+
+    #[test]
+    fn test_transitions_have_no_duplicates_or_self_loops() {
+        for (index, (from, to, _)) in TRANSITIONS.iter().enumerate() {
+            assert_ne!(from, to);
+            assert!(!TRANSITIONS[index + 1..].iter().any(|(other_from, other_to, _)| other_from == from && other_to == to));
+        }
+    }
+
+    #[test]
+    fn test_terminal_statuses() {
+        assert!(ALL.iter().all(|to| !is_edge(PropertyStatus::Archived, *to)));
+        assert_eq!(PropertyLifecycle::next_statuses(PropertyStatus::Sold), vec![PropertyStatus::Archived]);
+        // Nothing leads back to Draft except a verifier's rejection
+        let into_draft: Vec<_> = ALL.iter().filter(|from| is_edge(**from, PropertyStatus::Draft)).collect();
+        assert_eq!(into_draft, vec![&PropertyStatus::PendingVerification]);
+    }
+
+    #[test]
+    fn test_next_statuses_hide_system_edges() {
+        assert_eq!(
+            PropertyLifecycle::next_statuses(PropertyStatus::Listed),
+            vec![PropertyStatus::UnderContract, PropertyStatus::Delisted]
+        );
+        assert_eq!(PropertyLifecycle::next_statuses(PropertyStatus::Tokenized), vec![]);
+        assert!(!PropertyLifecycle::next_statuses(PropertyStatus::Verified).contains(&PropertyStatus::Tokenized));
+    }
+
+    #[test]
+    fn test_illegal_transitions_are_rejected() {
+        let owner = Actor::Caller(account(1));
+        for (from, to) in [
+            (PropertyStatus::Draft, PropertyStatus::Listed),
+            (PropertyStatus::Draft, PropertyStatus::Sold),
+            (PropertyStatus::Sold, PropertyStatus::Listed),
+            (PropertyStatus::Archived, PropertyStatus::Draft),
+            (PropertyStatus::Tokenized, PropertyStatus::Listed),
+            (PropertyStatus::Listed, PropertyStatus::Listed),
+        ] {
+            let result = PropertyLifecycle::ensure_transition(&property(from), to, owner, &None);
+            assert!(matches!(result, Err(PropertyError::InvalidTransition { .. })), "{:?} -> {:?}", from, to);
+        }
+    }
+
+    #[test]
+    fn test_authority_is_enforced() {
+        let delisted = property(PropertyStatus::Delisted);
+        assert!(PropertyLifecycle::ensure_transition(&delisted, PropertyStatus::Listed, Actor::Caller(account(1)), &None).is_ok());
+        assert!(matches!(
+            PropertyLifecycle::ensure_transition(&delisted, PropertyStatus::Listed, Actor::Caller(account(3)), &None),
+            Err(PropertyError::TransitionNotAllowed { .. })
+        ));
+
+        // The assigned manager still needs the PropertyManager role
+        let listed = property(PropertyStatus::Listed);
+        assert!(matches!(
+            PropertyLifecycle::ensure_transition(&listed, PropertyStatus::UnderContract, Actor::Caller(account(2)), &None),
+            Err(PropertyError::TransitionNotAllowed { .. })
+        ));
+    }
+
+    #[test]
+    fn test_system_takes_only_system_edges() {
+        let listed = property(PropertyStatus::Listed);
+        assert!(matches!(
+            PropertyLifecycle::ensure_transition(&listed, PropertyStatus::Delisted, Actor::System, &None),
+            Err(PropertyError::TransitionNotAllowed { .. })
+        ));
+        // Callers can never take a system edge, even the owner
+        assert!(matches!(
+            PropertyLifecycle::ensure_transition(&listed, PropertyStatus::Tokenized, Actor::Caller(account(1)), &None),
+            Err(PropertyError::TransitionNotAllowed { .. })
+        ));
+    }
+}
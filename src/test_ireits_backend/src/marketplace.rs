@@ -4,11 +4,20 @@ use ic_cdk::api::time;
 use std::cell::RefCell;
 use std::collections::HashMap;
 
+use crate::amount::{Amount, Currency, RoundingMode};
+use crate::cap_table::{CapTables, ShareMovementKind};
+use crate::compliance::{Compliance, ComplianceAction, ComplianceRejection};
+use crate::enforcement::{AssetClass, Enforcement, Frozen};
 use crate::icrc7_token::ICRC7Token;
-use crate::ret_token::{RETToken, TransferArgs};
+use crate::pause::{CircuitBreaker, Paused, Subsystem};
+use crate::payments::{PaymentError, PaymentManager};
+use crate::ret_token::{RETToken, TokenError};
+use crate::transfer_policy::{PolicyViolation, TransferPolicies};
 use crate::types::TokenType;
+use crate::PropertyError;
 
-const LISTING_FEE_PERCENTAGE: u64 = 100; // 1% = 100 basis points
+const DEFAULT_LISTING_FEE_PERCENTAGE: u64 = 100; // 1% = 100 basis points
+pub const MAX_LISTING_FEE_PERCENTAGE: u64 = 1_000; // 10%
 
 #[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct PropertyShare {
@@ -19,8 +28,8 @@ pub struct PropertyShare {
 
 #[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct ListingPrice {
-    pub amount: u64,
-    pub token_type: TokenType,
+    // Must be in a currency with a ledger token
+    pub amount: Amount,
 }
 
 #[derive(CandidType, Deserialize, Clone, Debug)]
@@ -33,7 +42,7 @@ pub struct Listing {
     pub status: ListingStatus,
     pub highest_bid: Option<Bid>,
     pub royalty_percentage: u16,
-    pub listing_fee: u64,
+    pub listing_fee: Amount,
 }
 
 #[derive(CandidType, Deserialize, Clone, Debug)]
@@ -46,11 +55,169 @@ pub enum ListingStatus {
 #[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct Bid {
     pub bidder: Principal,
-    pub amount: u64,
-    pub token_type: TokenType,
+    pub amount: Amount,
     pub timestamp: u64,
 }
 
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum ShareOfferStatus {
+    Open,
+    Exercised { by: Principal },
+    Completed,
+    Cancelled,
+}
+
+// A sale of shares to a new holder, open to existing shareholders for the ROFR window first
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct ShareOffer {
+    pub id: u64,
+    pub property_id: u64,
+    pub seller: Principal,
+    pub buyer: Principal,
+    pub amount: u16,
+    pub price: Amount, // In RET
+    // Marketplace fee paid by whoever takes the offer, on top of the price
+    pub fee: Amount,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub status: ShareOfferStatus,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum MarketplaceError {
+    ListingNotFound { listing_id: u64 },
+    NoBid { listing_id: u64 },
+    BidTooLow { highest_bid: Amount },
+    NotSeller { seller: Principal },
+    NotBuyer { buyer: Principal },
+    NotTokenOwner { token_id: u64 },
+    NotFractionalized { property_id: u64 },
+    AlreadyFractionalized { property_id: u64 },
+    // Shares are in basis points and must total 10000
+    InvalidShareTotal { total: u64 },
+    InsufficientShares { required: u64, available: u64 },
+    SelfTransfer,
+    // Sales to new holders go through a share offer while the property has a ROFR window
+    OfferRequired,
+    NoRofrWindow,
+    AlreadyShareholder { buyer: Principal },
+    NotShareholder,
+    OfferNotFound { offer_id: u64 },
+    OfferNotOpen { offer_id: u64, status: ShareOfferStatus },
+    RofrWindowOpen { closes_at: u64 },
+    RofrWindowClosed { closed_at: u64 },
+    UnsupportedCurrency { currency: Currency },
+    CurrencyMismatch { expected: Currency, actual: Currency },
+    InvalidAmount { reason: String },
+    ExceedsUnburnedFees { requested: u64, available: u64 },
+    NotFeeRecipient { recipient: Principal },
+    ComplianceRejected { reason: ComplianceRejection },
+    PolicyViolation { violation: PolicyViolation },
+    Paused { subsystem: Subsystem },
+    Frozen { asset: AssetClass },
+    Payment { error: PaymentError },
+    Token { error: TokenError },
+    Property { error: PropertyError },
+}
+
+impl std::fmt::Display for MarketplaceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MarketplaceError::ListingNotFound { listing_id } => write!(f, "Listing {} not found", listing_id),
+            MarketplaceError::NoBid { listing_id } => write!(f, "Listing {} has no active bid", listing_id),
+            MarketplaceError::BidTooLow { highest_bid } => write!(f, "Bid must exceed {}", highest_bid),
+            MarketplaceError::NotSeller { .. } => write!(f, "Not the seller"),
+            MarketplaceError::NotBuyer { .. } => write!(f, "Not the buyer"),
+            MarketplaceError::NotTokenOwner { token_id } => write!(f, "Not the owner of token {}", token_id),
+            MarketplaceError::NotFractionalized { property_id } => write!(f, "Property {} has no shares", property_id),
+            MarketplaceError::AlreadyFractionalized { property_id } => {
+                write!(f, "Property {} is already fractionalized", property_id)
+            }
+            MarketplaceError::InvalidShareTotal { total } => {
+                write!(f, "Total shares must equal 10000 basis points, got {}", total)
+            }
+            MarketplaceError::InsufficientShares { required, available } => {
+                write!(f, "Insufficient shares: {} required, {} available", required, available)
+            }
+            MarketplaceError::SelfTransfer => write!(f, "Cannot transfer shares to yourself"),
+            MarketplaceError::OfferRequired => {
+                write!(f, "Sales to new holders must first be offered to existing shareholders")
+            }
+            MarketplaceError::NoRofrWindow => write!(f, "Property has no right of first refusal; transfer shares directly"),
+            MarketplaceError::AlreadyShareholder { .. } => {
+                write!(f, "Buyer is already a shareholder; transfer shares directly")
+            }
+            MarketplaceError::NotShareholder => write!(f, "Only existing shareholders can exercise"),
+            MarketplaceError::OfferNotFound { offer_id } => write!(f, "Offer {} not found", offer_id),
+            MarketplaceError::OfferNotOpen { offer_id, .. } => write!(f, "Offer {} is not open", offer_id),
+            MarketplaceError::RofrWindowOpen { closes_at } => {
+                write!(f, "Right of first refusal window is open until {}", closes_at)
+            }
+            MarketplaceError::RofrWindowClosed { closed_at } => {
+                write!(f, "Right of first refusal window closed at {}", closed_at)
+            }
+            MarketplaceError::UnsupportedCurrency { currency } => write!(f, "{:?} has no payment token", currency),
+            MarketplaceError::CurrencyMismatch { expected, actual } => {
+                write!(f, "Expected an amount in {:?}, got {:?}", expected, actual)
+            }
+            MarketplaceError::InvalidAmount { reason } => write!(f, "{}", reason),
+            MarketplaceError::ExceedsUnburnedFees { requested, available } => {
+                write!(f, "Burn of {} exceeds {} unburned marketplace fees", requested, available)
+            }
+            MarketplaceError::NotFeeRecipient { .. } => write!(f, "Only the fee recipient can burn collected fees"),
+            MarketplaceError::ComplianceRejected { reason } => write!(f, "{}", reason),
+            MarketplaceError::PolicyViolation { violation } => write!(f, "{}", violation),
+            MarketplaceError::Paused { subsystem } => write!(f, "{}", Paused { subsystem: *subsystem }),
+            MarketplaceError::Frozen { asset } => write!(f, "{:?} holdings are frozen", asset),
+            MarketplaceError::Payment { error } => write!(f, "{}", error),
+            MarketplaceError::Token { error } => write!(f, "{}", error),
+            MarketplaceError::Property { error } => write!(f, "{}", error),
+        }
+    }
+}
+
+impl From<PaymentError> for MarketplaceError {
+    fn from(error: PaymentError) -> Self {
+        MarketplaceError::Payment { error }
+    }
+}
+
+impl From<TokenError> for MarketplaceError {
+    fn from(error: TokenError) -> Self {
+        MarketplaceError::Token { error }
+    }
+}
+
+impl From<PropertyError> for MarketplaceError {
+    fn from(error: PropertyError) -> Self {
+        MarketplaceError::Property { error }
+    }
+}
+
+impl From<ComplianceRejection> for MarketplaceError {
+    fn from(reason: ComplianceRejection) -> Self {
+        MarketplaceError::ComplianceRejected { reason }
+    }
+}
+
+impl From<PolicyViolation> for MarketplaceError {
+    fn from(violation: PolicyViolation) -> Self {
+        MarketplaceError::PolicyViolation { violation }
+    }
+}
+
+impl From<Paused> for MarketplaceError {
+    fn from(paused: Paused) -> Self {
+        MarketplaceError::Paused { subsystem: paused.subsystem }
+    }
+}
+
+impl From<Frozen> for MarketplaceError {
+    fn from(frozen: Frozen) -> Self {
+        MarketplaceError::Frozen { asset: frozen.asset }
+    }
+}
+
 #[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct MarketplaceStats {
     pub total_listings: u64,
@@ -59,12 +226,18 @@ pub struct MarketplaceStats {
     pub total_volume_ret: u64,
     pub total_volume_icp: u64,
     pub total_listing_fees: u64,
+    pub total_fees_burned: u64,
 }
 
 thread_local! {
     static LISTINGS: RefCell<HashMap<u64, Listing>> = RefCell::new(HashMap::new());
     static PROPERTY_SHARES: RefCell<HashMap<u64, Vec<PropertyShare>>> = RefCell::new(HashMap::new());
     static LISTING_COUNTER: RefCell<u64> = RefCell::new(0);
+    static SHARE_OFFERS: RefCell<HashMap<u64, ShareOffer>> = RefCell::new(HashMap::new());
+    static SHARE_OFFER_COUNTER: RefCell<u64> = const { RefCell::new(0) };
+    static LISTING_FEE_PERCENTAGE: RefCell<u64> = const { RefCell::new(DEFAULT_LISTING_FEE_PERCENTAGE) };
+    // RET listing and share offer fees paid to the fee recipient and not yet burned
+    static UNBURNED_RET_FEES: RefCell<u64> = const { RefCell::new(0) };
     static MARKETPLACE_STATS: RefCell<MarketplaceStats> = RefCell::new(MarketplaceStats {
         total_listings: 0,
         active_listings: 0,
@@ -72,6 +245,7 @@ thread_local! {
         total_volume_ret: 0,
         total_volume_icp: 0,
         total_listing_fees: 0,
+        total_fees_burned: 0,
     });
 }
 
@@ -82,15 +256,21 @@ impl Marketplace {
         property_token_id: u64,
         price: ListingPrice,
         royalty_percentage: u16,
-    ) -> Result<u64, String> {
+    ) -> Result<u64, MarketplaceError> {
+        CircuitBreaker::ensure_active(Subsystem::MarketplaceListings)?;
         let caller = ic_caller();
+        let amount = Self::positive(price.amount)?;
+        if amount.currency.token_type().is_none() {
+            return Err(MarketplaceError::UnsupportedCurrency { currency: amount.currency });
+        }
         
         // Calculate listing fee
-        let listing_fee = (price.amount * LISTING_FEE_PERCENTAGE) / 10000;
+        let listing_fee = amount.mul_div(Self::listing_fee_percentage(), 10_000, RoundingMode::HalfUp)
+            .map_err(|reason| MarketplaceError::InvalidAmount { reason })?;
         
         // Verify ownership
-        if !Self::verify_ownership(property_token_id, caller)? {
-            return Err("Not the property owner".to_string());
+        if !Self::verify_ownership(property_token_id, caller) {
+            return Err(MarketplaceError::NotTokenOwner { token_id: property_token_id });
         }
         
         // Create listing
@@ -122,47 +302,46 @@ impl Marketplace {
             let mut stats = stats.borrow_mut();
             stats.total_listings += 1;
             stats.active_listings += 1;
-            stats.total_listing_fees += listing_fee;
         });
         
         Ok(listing_id)
     }
 
-    pub fn place_bid(
-        listing_id: u64,
-        amount: u64,
-        token_type: TokenType,
-    ) -> Result<bool, String> {
+    pub fn place_bid(listing_id: u64, amount: Amount) -> Result<bool, MarketplaceError> {
+        CircuitBreaker::ensure_active(Subsystem::MarketplaceBids)?;
         let caller = ic_caller();
+        Compliance::check(ComplianceAction::Purchase, &[caller])?;
         
         LISTINGS.with(|listings| {
             let mut listings = listings.borrow_mut();
             let listing = listings.get_mut(&listing_id)
-                .ok_or("Listing not found")?;
+                .ok_or(MarketplaceError::ListingNotFound { listing_id })?;
             
-            if token_type != listing.price.token_type {
-                return Err("Invalid token type".to_string());
-            }
+            let amount = Self::in_currency(Self::positive(amount)?, listing.price.amount.currency)?;
             
-            if amount <= listing.price.amount {
+            let invalid = |reason| MarketplaceError::InvalidAmount { reason };
+            if amount.checked_cmp(listing.price.amount).map_err(invalid)?.is_le() {
                 if let Some(bid) = &listing.highest_bid {
-                    if amount <= bid.amount {
-                        return Err("Bid too low".to_string());
+                    if amount.checked_cmp(bid.amount).map_err(invalid)?.is_le() {
+                        return Err(MarketplaceError::BidTooLow { highest_bid: bid.amount });
                     }
                 }
             }
             
             // Verify bidder has enough balance
-            match token_type {
-                TokenType::RET => {
-                    let balance = RETToken::balance_of(caller);
-                    if balance < amount {
-                        return Err("Insufficient RET balance".to_string());
+            match amount.currency.token_type() {
+                Some(TokenType::RET) => {
+                    let available = RETToken::balance_of(caller);
+                    if available < amount.units {
+                        return Err(PaymentError::InsufficientBalance {
+                            required: amount,
+                            available: Amount { units: available, ..amount },
+                        }.into());
                     }
-                },
-                TokenType::ICP => {
-                    // Verify ICP balance (to be implemented)
-                    return Err("ICP payments not yet implemented".to_string());
+                }
+                // ICP payments are not implemented yet
+                Some(TokenType::ICP) | None => {
+                    return Err(MarketplaceError::UnsupportedCurrency { currency: amount.currency });
                 }
             }
             
@@ -170,7 +349,6 @@ impl Marketplace {
             listing.highest_bid = Some(Bid {
                 bidder: caller,
                 amount,
-                token_type,
                 timestamp: time(),
             });
             
@@ -178,44 +356,29 @@ impl Marketplace {
         })
     }
 
-    pub fn accept_bid(listing_id: u64) -> Result<bool, String> {
+    pub fn accept_bid(listing_id: u64) -> Result<bool, MarketplaceError> {
+        CircuitBreaker::ensure_active(Subsystem::MarketplaceBids)?;
         let caller = ic_caller();
         
         LISTINGS.with(|listings| {
             let mut listings = listings.borrow_mut();
             let listing = listings.get_mut(&listing_id)
-                .ok_or("Listing not found")?;
+                .ok_or(MarketplaceError::ListingNotFound { listing_id })?;
             
             if listing.seller != caller {
-                return Err("Not the seller".to_string());
+                return Err(MarketplaceError::NotSeller { seller: listing.seller });
             }
             
             let bid = listing.highest_bid.as_ref()
-                .ok_or("No active bid")?;
+                .ok_or(MarketplaceError::NoBid { listing_id })?;
             
             // Process payment
-            match bid.token_type {
-                TokenType::RET => {
-                    RETToken::transfer(TransferArgs {
-                        from: bid.bidder,
-                        to: listing.seller,
-                        amount: bid.amount,
-                        memo: None,
-                    })?;
-                    
-                    // Transfer listing fee
-                    if let Some(metadata) = RETToken::get_metadata() {
-                        RETToken::transfer(TransferArgs {
-                            from: bid.bidder,
-                            to: metadata.owner,
-                            amount: listing.listing_fee,
-                            memo: None,
-                        })?;
-                    }
-                },
-                TokenType::ICP => {
-                    return Err("ICP payments not yet implemented".to_string());
-                }
+            PaymentManager::pay(bid.bidder, listing.seller, bid.amount, None)?;
+            
+            // Transfer listing fee
+            if let Some(metadata) = RETToken::get_metadata() {
+                PaymentManager::pay(bid.bidder, metadata.owner, listing.listing_fee, None)?;
+                Self::record_fee(listing.listing_fee);
             }
             
             // Update listing status
@@ -226,9 +389,10 @@ impl Marketplace {
                 let mut stats = stats.borrow_mut();
                 stats.total_sales += 1;
                 stats.active_listings -= 1;
-                match bid.token_type {
-                    TokenType::RET => stats.total_volume_ret += bid.amount,
-                    TokenType::ICP => stats.total_volume_icp += bid.amount,
+                match bid.amount.currency {
+                    Currency::RET => stats.total_volume_ret += bid.amount.units,
+                    Currency::ICP => stats.total_volume_icp += bid.amount.units,
+                    Currency::USD => {}
                 }
             });
             
@@ -236,11 +400,11 @@ impl Marketplace {
         })
     }
 
-    fn verify_ownership(property_token_id: u64, caller: Principal) -> Result<bool, String> {
+    fn verify_ownership(property_token_id: u64, caller: Principal) -> bool {
         // Check direct ownership
         if let Some(owner) = ICRC7Token::owner_of(property_token_id) {
             if owner == caller {
-                return Ok(true);
+                return true;
             }
         }
         
@@ -248,41 +412,93 @@ impl Marketplace {
         PROPERTY_SHARES.with(|shares| {
             let shares = shares.borrow();
             if let Some(property_shares) = shares.get(&property_token_id) {
-                Ok(property_shares.iter().any(|share| share.owner == caller))
+                property_shares.iter().any(|share| share.owner == caller)
             } else {
-                Ok(false)
+                false
             }
         })
     }
 
+    pub fn listing_fee_percentage() -> u64 {
+        LISTING_FEE_PERCENTAGE.with(|fee| *fee.borrow())
+    }
+
+    // Changed through governance only
+    pub(crate) fn set_listing_fee_percentage(basis_points: u64) {
+        LISTING_FEE_PERCENTAGE.with(|fee| *fee.borrow_mut() = basis_points);
+    }
+
     pub fn get_stats() -> MarketplaceStats {
         MARKETPLACE_STATS.with(|stats| stats.borrow().clone())
     }
 
+    /// Burns RET collected as listing and share offer fees. Fees are paid to the RET owner account,
+    /// so only that account can burn them, and never more than has been collected.
+    pub fn buyback_and_burn(amount: u64) -> Result<u64, MarketplaceError> {
+        CircuitBreaker::ensure_active(Subsystem::MarketplaceListings)?;
+        let caller = ic_caller();
+        let metadata = RETToken::get_metadata().ok_or(TokenError::NotInitialized)?;
+
+        if metadata.owner != caller {
+            return Err(MarketplaceError::NotFeeRecipient { recipient: metadata.owner });
+        }
+
+        let unburned = UNBURNED_RET_FEES.with(|fees| *fees.borrow());
+        if amount > unburned {
+            return Err(MarketplaceError::ExceedsUnburnedFees { requested: amount, available: unburned });
+        }
+
+        let tx_id = RETToken::burn(amount, Some(b"buyback".to_vec()))?;
+
+        UNBURNED_RET_FEES.with(|fees| *fees.borrow_mut() -= amount);
+        MARKETPLACE_STATS.with(|stats| {
+            stats.borrow_mut().total_fees_burned += amount;
+        });
+
+        Ok(tx_id)
+    }
+
     pub fn get_property_shares(property_token_id: u64) -> Option<Vec<PropertyShare>> {
         PROPERTY_SHARES.with(|shares| {
             shares.borrow().get(&property_token_id).cloned()
         })
     }
 
+    /// Issues the initial shares of the property behind `token_id`, once.
     pub fn fractionalize_property(
-        property_token_id: u64,
+        property_id: u64,
+        token_id: u64,
         shares: Vec<(Principal, u16)>,
-    ) -> Result<(), String> {
+    ) -> Result<(), MarketplaceError> {
+        CircuitBreaker::ensure_active(Subsystem::MarketplaceListings)?;
         let caller = ic_cdk::api::caller();
         
         // Verify caller owns the property
-        if !ICRC7Token::owner_of(property_token_id)
+        if !ICRC7Token::owner_of(token_id)
             .map(|owner| owner == caller)
             .unwrap_or(false) {
-            return Err("Not the property owner".to_string());
+            return Err(MarketplaceError::NotTokenOwner { token_id });
+        }
+        // Reissuing would wipe existing holdings
+        if PROPERTY_SHARES.with(|shares| shares.borrow().contains_key(&property_id)) {
+            return Err(MarketplaceError::AlreadyFractionalized { property_id });
         }
         
         // Verify total shares add up to 100%
-        let total_shares: u16 = shares.iter().map(|(_, share)| share).sum();
+        let total_shares: u64 = shares.iter().map(|(_, share)| *share as u64).sum();
         if total_shares != 10000 {
-            return Err("Total shares must equal 100% (10000 basis points)".to_string());
+            return Err(MarketplaceError::InvalidShareTotal { total: total_shares });
         }
+
+        let recipients: Vec<Principal> = shares.iter().map(|(owner, _)| *owner).collect();
+        Compliance::check(ComplianceAction::ShareAllocation, &recipients)?;
+
+        let mut holdings: HashMap<Principal, u64> = HashMap::new();
+        for (owner, share) in &shares {
+            *holdings.entry(*owner).or_default() += *share as u64;
+        }
+        TransferPolicies::check_allocation(property_id, &holdings)?;
+        CapTables::record_issuance(property_id, &shares);
         
         // Create property shares
         let property_shares: Vec<PropertyShare> = shares
@@ -296,30 +512,304 @@ impl Marketplace {
         
         // Store shares
         PROPERTY_SHARES.with(|shares| {
-            shares.borrow_mut().insert(property_token_id, property_shares);
+            shares.borrow_mut().insert(property_id, property_shares);
         });
         
         Ok(())
     }
 
-    pub fn distribute_ret_rewards(property_token_id: u64, amount: u64) -> Result<(), String> {
+    /// Pays `amount` out to shareholders pro rata; the split always adds up to `amount`.
+    pub fn distribute_ret_rewards(property_token_id: u64, amount: Amount) -> Result<(), MarketplaceError> {
+        CircuitBreaker::ensure_active(Subsystem::Distributions)?;
+        let amount = Self::in_currency(Self::positive(amount)?, Currency::RET)?;
+
         PROPERTY_SHARES.with(|shares| {
             let shares = shares.borrow();
             let property_shares = shares.get(&property_token_id)
-                .ok_or("Property not found")?;
+                .ok_or(MarketplaceError::NotFractionalized { property_id: property_token_id })?;
             
-            for share in property_shares {
-                let reward = (amount * share.share_percentage as u64) / 10000;
+            let weights: Vec<u64> = property_shares.iter().map(|share| share.share_percentage as u64).collect();
+            let rewards = amount.split_pro_rata(&weights)
+                .map_err(|reason| MarketplaceError::InvalidAmount { reason })?;
+            for (share, reward) in property_shares.iter().zip(rewards) {
+                if reward.is_zero() {
+                    continue;
+                }
                 // Transfer RET tokens to share owner
-                RETToken::transfer(TransferArgs {
-                    from: ic_cdk::api::caller(),
-                    to: share.owner,
-                    amount: reward,
-                    memo: None,
-                })?;
+                PaymentManager::pay(ic_cdk::api::caller(), share.owner, reward, None)?;
             }
             
             Ok(())
         })
     }
-} 
\ No newline at end of file
+
+    /// Moves shares to `to` directly. Sales to new holders must go through `offer_shares`
+    /// when the property has a right-of-first-refusal window.
+    pub fn transfer_shares(property_id: u64, to: Principal, amount: u16) -> Result<bool, MarketplaceError> {
+        let caller = ic_caller();
+
+        if to == caller {
+            return Err(MarketplaceError::SelfTransfer);
+        }
+
+        let holdings = Self::share_holdings(property_id)?;
+        if !holdings.contains_key(&to) && TransferPolicies::rofr_window(property_id).is_some() {
+            return Err(MarketplaceError::OfferRequired);
+        }
+
+        Self::move_shares(property_id, caller, to, amount, ShareMovementKind::Transfer)?;
+        Ok(true)
+    }
+
+    pub fn offer_shares(property_id: u64, buyer: Principal, amount: u16, price: Amount) -> Result<u64, MarketplaceError> {
+        CircuitBreaker::ensure_active(Subsystem::MarketplaceListings)?;
+        let caller = ic_caller();
+        let price = price.validate().map_err(|reason| MarketplaceError::InvalidAmount { reason })?;
+        let price = Self::in_currency(price, Currency::RET)?;
+        let fee = price.mul_div(Self::listing_fee_percentage(), 10_000, RoundingMode::HalfUp)
+            .map_err(|reason| MarketplaceError::InvalidAmount { reason })?;
+        let window = TransferPolicies::rofr_window(property_id).ok_or(MarketplaceError::NoRofrWindow)?;
+
+        let holdings = Self::share_holdings(property_id)?;
+        if holdings.contains_key(&buyer) {
+            return Err(MarketplaceError::AlreadyShareholder { buyer });
+        }
+        Self::ensure_shares(&holdings, caller, amount)?;
+        Self::check_share_move(property_id, &holdings, caller, buyer, amount)?;
+
+        let id = SHARE_OFFER_COUNTER.with(|counter| {
+            let mut counter = counter.borrow_mut();
+            *counter += 1;
+            *counter
+        });
+
+        let now = time();
+        SHARE_OFFERS.with(|offers| {
+            offers.borrow_mut().insert(id, ShareOffer {
+                id,
+                property_id,
+                seller: caller,
+                buyer,
+                amount,
+                price,
+                fee,
+                created_at: now,
+                expires_at: now + window,
+                status: ShareOfferStatus::Open,
+            });
+        });
+
+        Ok(id)
+    }
+
+    /// An existing shareholder takes an open offer on the same terms during the window.
+    pub fn exercise_rofr(offer_id: u64) -> Result<bool, MarketplaceError> {
+        let caller = ic_caller();
+        let offer = Self::open_offer(offer_id)?;
+
+        if time() >= offer.expires_at {
+            return Err(MarketplaceError::RofrWindowClosed { closed_at: offer.expires_at });
+        }
+        if caller == offer.seller {
+            return Err(MarketplaceError::SelfTransfer);
+        }
+        if !Self::share_holdings(offer.property_id)?.contains_key(&caller) {
+            return Err(MarketplaceError::NotShareholder);
+        }
+
+        Self::settle_offer(&offer, caller)?;
+        Self::set_offer_status(offer_id, ShareOfferStatus::Exercised { by: caller });
+        Ok(true)
+    }
+
+    /// The buyer completes an offer nobody exercised once the window has closed.
+    pub fn complete_share_offer(offer_id: u64) -> Result<bool, MarketplaceError> {
+        let caller = ic_caller();
+        let offer = Self::open_offer(offer_id)?;
+
+        if caller != offer.buyer {
+            return Err(MarketplaceError::NotBuyer { buyer: offer.buyer });
+        }
+        if time() < offer.expires_at {
+            return Err(MarketplaceError::RofrWindowOpen { closes_at: offer.expires_at });
+        }
+
+        Self::settle_offer(&offer, caller)?;
+        Self::set_offer_status(offer_id, ShareOfferStatus::Completed);
+        Ok(true)
+    }
+
+    pub fn cancel_share_offer(offer_id: u64) -> Result<bool, MarketplaceError> {
+        let offer = Self::open_offer(offer_id)?;
+
+        if ic_caller() != offer.seller {
+            return Err(MarketplaceError::NotSeller { seller: offer.seller });
+        }
+
+        Self::set_offer_status(offer_id, ShareOfferStatus::Cancelled);
+        Ok(true)
+    }
+
+    pub fn get_share_offer(offer_id: u64) -> Option<ShareOffer> {
+        SHARE_OFFERS.with(|offers| offers.borrow().get(&offer_id).cloned())
+    }
+
+    pub fn get_share_offers(property_id: u64) -> Vec<ShareOffer> {
+        let mut offers: Vec<ShareOffer> = SHARE_OFFERS.with(|offers| {
+            offers.borrow()
+                .values()
+                .filter(|offer| offer.property_id == property_id)
+                .cloned()
+                .collect()
+        });
+        offers.sort_by_key(|offer| offer.id);
+        offers
+    }
+
+    fn open_offer(offer_id: u64) -> Result<ShareOffer, MarketplaceError> {
+        let offer = Self::get_share_offer(offer_id).ok_or(MarketplaceError::OfferNotFound { offer_id })?;
+        if offer.status != ShareOfferStatus::Open {
+            return Err(MarketplaceError::OfferNotOpen { offer_id, status: offer.status });
+        }
+        Ok(offer)
+    }
+
+    fn set_offer_status(offer_id: u64, status: ShareOfferStatus) {
+        SHARE_OFFERS.with(|offers| {
+            if let Some(offer) = offers.borrow_mut().get_mut(&offer_id) {
+                offer.status = status;
+            }
+        });
+    }
+
+    // Pays the seller and the fee from the caller and moves the offered shares to `recipient`
+    fn settle_offer(offer: &ShareOffer, recipient: Principal) -> Result<(), MarketplaceError> {
+        let holdings = Self::share_holdings(offer.property_id)?;
+        Self::ensure_shares(&holdings, offer.seller, offer.amount)?;
+        Self::check_share_move(offer.property_id, &holdings, offer.seller, recipient, offer.amount)?;
+
+        let memo = Some(format!("share-offer:{}", offer.id).into_bytes());
+        if !offer.price.is_zero() {
+            PaymentManager::pay(recipient, offer.seller, offer.price, memo.clone())?;
+        }
+        if !offer.fee.is_zero() {
+            let metadata = RETToken::get_metadata().ok_or(TokenError::NotInitialized)?;
+            PaymentManager::pay(recipient, metadata.owner, offer.fee, memo)?;
+            Self::record_fee(offer.fee);
+        }
+
+        Self::move_shares(
+            offer.property_id,
+            offer.seller,
+            recipient,
+            offer.amount,
+            ShareMovementKind::Sale { price: offer.price },
+        )
+    }
+
+    // Fees in RET fund `buyback_and_burn`
+    fn record_fee(fee: Amount) {
+        MARKETPLACE_STATS.with(|stats| stats.borrow_mut().total_listing_fees += fee.units);
+        if fee.currency == Currency::RET {
+            UNBURNED_RET_FEES.with(|fees| *fees.borrow_mut() += fee.units);
+        }
+    }
+
+    fn share_holdings(property_id: u64) -> Result<HashMap<Principal, u64>, MarketplaceError> {
+        let shares = Self::get_property_shares(property_id).ok_or(MarketplaceError::NotFractionalized { property_id })?;
+        let mut holdings: HashMap<Principal, u64> = HashMap::new();
+        for share in shares {
+            *holdings.entry(share.owner).or_default() += share.share_percentage as u64;
+        }
+        Ok(holdings)
+    }
+
+    fn check_share_move(
+        property_id: u64,
+        holdings: &HashMap<Principal, u64>,
+        from: Principal,
+        to: Principal,
+        amount: u16,
+    ) -> Result<(), MarketplaceError> {
+        CircuitBreaker::ensure_active(Subsystem::MarketplaceBids)?;
+        Enforcement::ensure_not_frozen(from, AssetClass::PropertyShares)?;
+        Compliance::check(ComplianceAction::ShareAllocation, &[to])?;
+        Ok(TransferPolicies::check_share_transfer(property_id, holdings, from, to, amount as u64)?)
+    }
+
+    fn ensure_shares(holdings: &HashMap<Principal, u64>, holder: Principal, amount: u16) -> Result<(), MarketplaceError> {
+        let available = holdings.get(&holder).copied().unwrap_or(0);
+        if available < amount as u64 {
+            return Err(MarketplaceError::InsufficientShares { required: amount as u64, available });
+        }
+        Ok(())
+    }
+
+    fn positive(amount: Amount) -> Result<Amount, MarketplaceError> {
+        amount.validate_positive().map_err(|reason| MarketplaceError::InvalidAmount { reason })
+    }
+
+    fn in_currency(amount: Amount, expected: Currency) -> Result<Amount, MarketplaceError> {
+        if amount.currency != expected {
+            return Err(MarketplaceError::CurrencyMismatch { expected, actual: amount.currency });
+        }
+        Ok(amount)
+    }
+
+    fn move_shares(
+        property_id: u64,
+        from: Principal,
+        to: Principal,
+        amount: u16,
+        kind: ShareMovementKind,
+    ) -> Result<(), MarketplaceError> {
+        let holdings = Self::share_holdings(property_id)?;
+        Self::check_share_move(property_id, &holdings, from, to, amount)?;
+        Self::reassign_shares(property_id, from, to, amount, kind)
+    }
+
+    /// Rewrites the share entries with no freeze, compliance or policy checks, and
+    /// records the movement in the cap table. Forced transfers call this directly.
+    pub(crate) fn reassign_shares(
+        property_id: u64,
+        from: Principal,
+        to: Principal,
+        amount: u16,
+        kind: ShareMovementKind,
+    ) -> Result<(), MarketplaceError> {
+        if amount == 0 {
+            return Err(MarketplaceError::InvalidAmount { reason: "Amount must be greater than zero".to_string() });
+        }
+
+        let holdings = Self::share_holdings(property_id)?;
+        Self::ensure_shares(&holdings, from, amount)?;
+
+        PROPERTY_SHARES.with(|shares| {
+            let mut shares = shares.borrow_mut();
+            let property_shares = shares.get_mut(&property_id)
+                .ok_or(MarketplaceError::NotFractionalized { property_id })?;
+
+            let mut remaining = amount;
+            for share in property_shares.iter_mut().filter(|share| share.owner == from) {
+                let taken = remaining.min(share.share_percentage);
+                share.share_percentage -= taken;
+                remaining -= taken;
+            }
+            property_shares.retain(|share| share.share_percentage > 0);
+
+            match property_shares.iter_mut().find(|share| share.owner == to) {
+                Some(share) => share.share_percentage += amount,
+                None => property_shares.push(PropertyShare {
+                    owner: to,
+                    share_percentage: amount,
+                    last_distribution: time(),
+                }),
+            }
+
+            Ok::<(), MarketplaceError>(())
+        })?;
+
+        CapTables::record_movement(property_id, kind, from, to, amount);
+        Ok(())
+    }
+}
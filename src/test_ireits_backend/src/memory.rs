@@ -0,0 +1,33 @@
+use ic_stable_structures::memory_manager::{MemoryId, MemoryManager, VirtualMemory};
+use ic_stable_structures::DefaultMemoryImpl;
+use std::cell::RefCell;
+
+pub type Memory = VirtualMemory<DefaultMemoryImpl>;
+
+// Every stable structure gets its own virtual memory; never reuse or renumber these
+pub const DOCUMENT_CHUNK_INDEX: MemoryId = MemoryId::new(0);
+pub const DOCUMENT_CHUNK_DATA: MemoryId = MemoryId::new(1);
+pub const PROPERTY_OWNER_INDEX: MemoryId = MemoryId::new(2);
+pub const PROPERTY_STATUS_INDEX: MemoryId = MemoryId::new(3);
+pub const PROPERTY_CITY_INDEX: MemoryId = MemoryId::new(4);
+pub const PROPERTY_INDEX_KEYS: MemoryId = MemoryId::new(5);
+pub const PROPERTY_GEO_INDEX: MemoryId = MemoryId::new(6);
+pub const PROPERTY_GEO_CODES: MemoryId = MemoryId::new(7);
+pub const CAP_TABLE_LINKS: MemoryId = MemoryId::new(8);
+pub const DOCUMENT_UPLOADS: MemoryId = MemoryId::new(9);
+pub const DOCUMENT_UPLOAD_CHUNKS: MemoryId = MemoryId::new(10);
+pub const DOCUMENT_UPLOAD_COUNTER: MemoryId = MemoryId::new(11);
+pub const DOCUMENT_RECORDS: MemoryId = MemoryId::new(12);
+pub const DOCUMENT_CHUNK_POSITIONS: MemoryId = MemoryId::new(13);
+pub const DOCUMENT_LINKS: MemoryId = MemoryId::new(14);
+pub const PROPERTY_RECORDS: MemoryId = MemoryId::new(15);
+pub const PROPERTY_ID_COUNTER: MemoryId = MemoryId::new(16);
+
+thread_local! {
+    static MEMORY_MANAGER: RefCell<MemoryManager<DefaultMemoryImpl>> =
+        RefCell::new(MemoryManager::init(DefaultMemoryImpl::default()));
+}
+
+pub fn get(id: MemoryId) -> Memory {
+    MEMORY_MANAGER.with(|manager| manager.borrow().get(id))
+}
@@ -0,0 +1,382 @@
+use candid::{CandidType, Deserialize, Principal};
+use ic_cdk::api::caller as ic_caller;
+use ic_cdk::api::time;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use crate::enforcement::{AssetClass, Enforcement, ForcedAsset};
+use crate::marketplace::{Marketplace, MAX_LISTING_FEE_PERCENTAGE};
+use crate::payments;
+use crate::ret_token::RETToken;
+
+const HOUR: u64 = 60 * 60 * 1_000_000_000; // 1 hour in nanoseconds
+const DEFAULT_ACTION_TTL: u64 = 7 * 24 * HOUR;
+// Short enough that stale approvals can't linger, long enough to gather signers
+const MIN_ACTION_TTL: u64 = HOUR;
+const MAX_ACTION_TTL: u64 = 30 * 24 * HOUR;
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum AdminAction {
+    SetPaymentLedger { ret_ledger: Principal },
+    MintRet { to: Principal, amount: u64, memo: Option<Vec<u8>> },
+    SetRetFeeConfig { transfer_fee: u64, fee_collector: Option<Principal> },
+    SetListingFee { basis_points: u64 },
+    AddSigner { signer: Principal },
+    RemoveSigner { signer: Principal },
+    SetThreshold { threshold: u64 },
+    SetActionTtl { ttl: u64 },
+    FreezeAccount { account: Principal, assets: Vec<AssetClass>, reason: String },
+    UnfreezeAccount { account: Principal, assets: Vec<AssetClass>, reason: String },
+    ForcedTransfer { from: Principal, to: Principal, asset: ForcedAsset, reason: String },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum ActionStatus {
+    Pending,
+    Executed,
+    Failed,
+    Cancelled,
+    Expired,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct PendingAction {
+    pub id: u64,
+    pub action: AdminAction,
+    pub proposer: Principal,
+    pub approvals: Vec<Principal>,
+    pub status: ActionStatus,
+    pub created_at: u64,
+    pub expires_at: u64,
+    pub executed_at: Option<u64>,
+    pub failure_reason: Option<String>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct CouncilConfig {
+    pub signers: Vec<Principal>,
+    pub threshold: u64,
+    // How long an action stays open for approvals, in nanoseconds
+    pub action_ttl: u64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum CouncilError {
+    NotSigner,
+    ActionNotFound { action_id: u64 },
+    NotPending,
+    Expired { expired_at: u64 },
+    AlreadyApproved,
+    NotProposer,
+    ZeroMint,
+    ListingFeeTooHigh { max: u64 },
+    AlreadySigner { signer: Principal },
+    UnknownSigner { signer: Principal },
+    ZeroThreshold,
+    TtlOutOfRange { min: u64, max: u64 },
+    Overflow,
+    NoAssetClasses,
+    ReasonRequired,
+    SameAccount,
+}
+
+impl std::fmt::Display for CouncilError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CouncilError::NotSigner => write!(f, "Caller is not a council signer"),
+            CouncilError::ActionNotFound { action_id } => write!(f, "Action {} not found", action_id),
+            CouncilError::NotPending => write!(f, "Action is not pending"),
+            CouncilError::Expired { expired_at } => write!(f, "Action expired at {}", expired_at),
+            CouncilError::AlreadyApproved => write!(f, "Already approved"),
+            CouncilError::NotProposer => write!(f, "Only the proposer can cancel"),
+            CouncilError::ZeroMint => write!(f, "Mint amount must be greater than zero"),
+            CouncilError::ListingFeeTooHigh { max } => write!(f, "Listing fee cannot exceed {} basis points", max),
+            CouncilError::AlreadySigner { signer } => write!(f, "{} is already a signer", signer),
+            CouncilError::UnknownSigner { signer } => write!(f, "{} is not a signer", signer),
+            CouncilError::ZeroThreshold => write!(f, "Threshold must be at least one"),
+            CouncilError::TtlOutOfRange { min, max } => write!(f, "Action TTL must be between {} and {} ns", min, max),
+            CouncilError::Overflow => write!(f, "Action expiry overflows"),
+            CouncilError::NoAssetClasses => write!(f, "At least one asset class is required"),
+            CouncilError::ReasonRequired => write!(f, "A documented reason is required"),
+            CouncilError::SameAccount => write!(f, "Source and destination must differ"),
+        }
+    }
+}
+
+thread_local! {
+    static COUNCIL: RefCell<CouncilConfig> = const { RefCell::new(CouncilConfig {
+        signers: Vec::new(),
+        threshold: 1,
+        action_ttl: DEFAULT_ACTION_TTL,
+    }) };
+    static ACTIONS: RefCell<BTreeMap<u64, PendingAction>> = const { RefCell::new(BTreeMap::new()) };
+    static ACTION_COUNTER: RefCell<u64> = const { RefCell::new(0) };
+}
+
+/// M-of-N admin council. Sensitive changes are proposed as pending actions and run
+/// as soon as enough signers have approved them.
+pub struct AdminCouncil;
+
+impl AdminCouncil {
+    /// Makes `signer` the only council member with a threshold of one. Called once from `init`.
+    pub(crate) fn bootstrap(signer: Principal) {
+        COUNCIL.with(|council| {
+            let mut council = council.borrow_mut();
+            council.signers = vec![signer];
+            council.threshold = 1;
+        });
+    }
+
+    pub fn is_signer(account: Principal) -> bool {
+        COUNCIL.with(|council| council.borrow().signers.contains(&account))
+    }
+
+    /// Queues `action` with the proposer's approval; executes immediately if that meets the threshold.
+    pub fn propose(action: AdminAction) -> Result<u64, CouncilError> {
+        let caller = ic_caller();
+
+        if !Self::is_signer(caller) {
+            return Err(CouncilError::NotSigner);
+        }
+        Self::validate_action(&action)?;
+
+        let now = time();
+        let ttl = COUNCIL.with(|council| council.borrow().action_ttl);
+        let expires_at = now.checked_add(ttl).ok_or(CouncilError::Overflow)?;
+
+        let id = ACTION_COUNTER.with(|counter| {
+            let mut counter = counter.borrow_mut();
+            *counter += 1;
+            *counter
+        });
+
+        ACTIONS.with(|actions| {
+            actions.borrow_mut().insert(id, PendingAction {
+                id,
+                action,
+                proposer: caller,
+                approvals: vec![caller],
+                status: ActionStatus::Pending,
+                created_at: now,
+                expires_at,
+                executed_at: None,
+                failure_reason: None,
+            });
+        });
+
+        Self::try_execute(id);
+        Ok(id)
+    }
+
+    pub fn approve(action_id: u64) -> Result<ActionStatus, CouncilError> {
+        let caller = ic_caller();
+
+        if !Self::is_signer(caller) {
+            return Err(CouncilError::NotSigner);
+        }
+
+        ACTIONS.with(|actions| {
+            let mut actions = actions.borrow_mut();
+            let pending = actions.get_mut(&action_id)
+                .ok_or(CouncilError::ActionNotFound { action_id })?;
+
+            if pending.status != ActionStatus::Pending {
+                return Err(CouncilError::NotPending);
+            }
+            if time() > pending.expires_at {
+                pending.status = ActionStatus::Expired;
+                return Err(CouncilError::Expired { expired_at: pending.expires_at });
+            }
+            if pending.approvals.contains(&caller) {
+                return Err(CouncilError::AlreadyApproved);
+            }
+
+            pending.approvals.push(caller);
+            Ok(())
+        })?;
+
+        Ok(Self::try_execute(action_id))
+    }
+
+    pub fn cancel(action_id: u64) -> Result<bool, CouncilError> {
+        let caller = ic_caller();
+
+        ACTIONS.with(|actions| {
+            let mut actions = actions.borrow_mut();
+            let pending = actions.get_mut(&action_id)
+                .ok_or(CouncilError::ActionNotFound { action_id })?;
+
+            if pending.proposer != caller {
+                return Err(CouncilError::NotProposer);
+            }
+            if pending.status != ActionStatus::Pending {
+                return Err(CouncilError::NotPending);
+            }
+
+            pending.status = ActionStatus::Cancelled;
+            Ok(true)
+        })
+    }
+
+    pub fn get_council() -> CouncilConfig {
+        COUNCIL.with(|council| council.borrow().clone())
+    }
+
+    pub fn get_action(action_id: u64) -> Option<PendingAction> {
+        ACTIONS.with(|actions| {
+            actions.borrow().get(&action_id).cloned().map(Self::with_expiry)
+        })
+    }
+
+    pub fn get_actions(status: Option<ActionStatus>, start: u64, length: u64) -> Vec<PendingAction> {
+        ACTIONS.with(|actions| {
+            actions.borrow()
+                .values()
+                .cloned()
+                .map(Self::with_expiry)
+                .filter(|pending| status.as_ref().is_none_or(|status| pending.status == *status))
+                .skip(start as usize)
+                .take(length as usize)
+                .collect()
+        })
+    }
+
+    // Queries can't write, so expiry is reported lazily
+    fn with_expiry(mut pending: PendingAction) -> PendingAction {
+        if pending.status == ActionStatus::Pending && time() > pending.expires_at {
+            pending.status = ActionStatus::Expired;
+        }
+        pending
+    }
+
+    // Runs the action if approvals from current signers meet the threshold.
+    fn try_execute(action_id: u64) -> ActionStatus {
+        let pending = match ACTIONS.with(|actions| actions.borrow().get(&action_id).cloned()) {
+            Some(pending) => pending,
+            None => return ActionStatus::Failed,
+        };
+
+        let (signers, threshold) = COUNCIL.with(|council| {
+            let council = council.borrow();
+            (council.signers.clone(), council.threshold)
+        });
+        let approvals = pending.approvals.iter()
+            .filter(|approver| signers.contains(approver))
+            .count() as u64;
+        if approvals < threshold {
+            return ActionStatus::Pending;
+        }
+
+        let (status, failure_reason) = match Self::apply_action(action_id, &pending.action) {
+            Ok(()) => (ActionStatus::Executed, None),
+            Err(reason) => (ActionStatus::Failed, Some(reason)),
+        };
+
+        ACTIONS.with(|actions| {
+            if let Some(pending) = actions.borrow_mut().get_mut(&action_id) {
+                pending.status = status.clone();
+                pending.executed_at = Some(time());
+                pending.failure_reason = failure_reason;
+            }
+        });
+
+        status
+    }
+
+    fn validate_action(action: &AdminAction) -> Result<(), CouncilError> {
+        match action {
+            AdminAction::MintRet { amount, .. } if *amount == 0 => {
+                Err(CouncilError::ZeroMint)
+            }
+            AdminAction::SetListingFee { basis_points } if *basis_points > MAX_LISTING_FEE_PERCENTAGE => {
+                Err(CouncilError::ListingFeeTooHigh { max: MAX_LISTING_FEE_PERCENTAGE })
+            }
+            AdminAction::AddSigner { signer } if Self::is_signer(*signer) => {
+                Err(CouncilError::AlreadySigner { signer: *signer })
+            }
+            AdminAction::RemoveSigner { signer } if !Self::is_signer(*signer) => {
+                Err(CouncilError::UnknownSigner { signer: *signer })
+            }
+            AdminAction::SetThreshold { threshold } if *threshold == 0 => {
+                Err(CouncilError::ZeroThreshold)
+            }
+            AdminAction::SetActionTtl { ttl } if !(MIN_ACTION_TTL..=MAX_ACTION_TTL).contains(ttl) => {
+                Err(CouncilError::TtlOutOfRange { min: MIN_ACTION_TTL, max: MAX_ACTION_TTL })
+            }
+            AdminAction::FreezeAccount { assets, reason, .. }
+            | AdminAction::UnfreezeAccount { assets, reason, .. } => {
+                if assets.is_empty() {
+                    return Err(CouncilError::NoAssetClasses);
+                }
+                Enforcement::validate_reason(reason).map_err(|_| CouncilError::ReasonRequired)
+            }
+            AdminAction::ForcedTransfer { from, to, reason, .. } => {
+                if from == to {
+                    return Err(CouncilError::SameAccount);
+                }
+                Enforcement::validate_reason(reason).map_err(|_| CouncilError::ReasonRequired)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn apply_action(action_id: u64, action: &AdminAction) -> Result<(), String> {
+        match action {
+            AdminAction::SetPaymentLedger { ret_ledger } => {
+                payments::initialize_payment_manager(*ret_ledger);
+                Ok(())
+            }
+            AdminAction::MintRet { to, amount, memo } => {
+                RETToken::mint_internal(*to, *amount, memo.clone())
+                    .map(|_| ())
+                    .map_err(|error| error.to_string())
+            }
+            AdminAction::SetRetFeeConfig { transfer_fee, fee_collector } => {
+                RETToken::set_fee_config(*transfer_fee, *fee_collector)
+            }
+            AdminAction::SetListingFee { basis_points } => {
+                Marketplace::set_listing_fee_percentage(*basis_points);
+                Ok(())
+            }
+            AdminAction::AddSigner { signer } => COUNCIL.with(|council| {
+                let mut council = council.borrow_mut();
+                if council.signers.contains(signer) {
+                    return Err("Principal is already a signer".to_string());
+                }
+                council.signers.push(*signer);
+                Ok(())
+            }),
+            AdminAction::RemoveSigner { signer } => COUNCIL.with(|council| {
+                let mut council = council.borrow_mut();
+                if council.signers.len() as u64 <= council.threshold {
+                    return Err("Removing this signer would leave fewer signers than the threshold".to_string());
+                }
+                council.signers.retain(|existing| existing != signer);
+                Ok(())
+            }),
+            AdminAction::SetThreshold { threshold } => COUNCIL.with(|council| {
+                let mut council = council.borrow_mut();
+                if *threshold > council.signers.len() as u64 {
+                    return Err("Threshold cannot exceed the number of signers".to_string());
+                }
+                council.threshold = *threshold;
+                Ok(())
+            }),
+            AdminAction::SetActionTtl { ttl } => {
+                COUNCIL.with(|council| council.borrow_mut().action_ttl = *ttl);
+                Ok(())
+            }
+            AdminAction::FreezeAccount { account, assets, reason } => {
+                Enforcement::freeze(action_id, *account, assets.clone(), reason.clone());
+                Ok(())
+            }
+            AdminAction::UnfreezeAccount { account, assets, reason } => {
+                Enforcement::unfreeze(action_id, *account, assets.clone(), reason.clone());
+                Ok(())
+            }
+            AdminAction::ForcedTransfer { from, to, asset, reason } => {
+                Enforcement::forced_transfer(action_id, *from, *to, asset.clone(), reason.clone())
+            }
+        }
+    }
+}
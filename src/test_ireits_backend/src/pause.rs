@@ -0,0 +1,142 @@
+use candid::{CandidType, Deserialize, Principal};
+use ic_cdk::api::time;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+
+use crate::access_control::{AccessControl, Role};
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Subsystem {
+    RetTransfers,
+    Staking,
+    Icrc7Transfers,
+    // Also fractionalization, new share offers and fee buybacks
+    MarketplaceListings,
+    // Also share transfers and share offer settlement
+    MarketplaceBids,
+    Distributions,
+}
+
+// Returned by guarded entry points while their subsystem is paused
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Paused {
+    pub subsystem: Subsystem,
+}
+
+impl std::fmt::Display for Paused {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?} is paused", self.subsystem)
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum PauseError {
+    Unauthorized,
+    ReasonRequired,
+    AlreadyPaused { subsystem: Subsystem },
+    NotPaused { subsystem: Subsystem },
+}
+
+impl std::fmt::Display for PauseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PauseError::Unauthorized => write!(f, "Not authorized"),
+            PauseError::ReasonRequired => write!(f, "A reason is required"),
+            PauseError::AlreadyPaused { subsystem } => write!(f, "{:?} is already paused", subsystem),
+            PauseError::NotPaused { subsystem } => write!(f, "{:?} is already active", subsystem),
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct PauseEvent {
+    pub id: u64,
+    pub subsystem: Subsystem,
+    pub paused: bool,
+    pub reason: String,
+    pub changed_by: Principal,
+    pub timestamp: u64,
+}
+
+thread_local! {
+    static PAUSED: RefCell<BTreeSet<Subsystem>> = const { RefCell::new(BTreeSet::new()) };
+    static PAUSE_LOG: RefCell<Vec<PauseEvent>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Per-subsystem circuit breaker. Each guarded entry point calls `ensure_active`
+/// before touching state.
+pub struct CircuitBreaker;
+
+impl CircuitBreaker {
+    pub fn pause(subsystem: Subsystem, reason: String) -> Result<bool, PauseError> {
+        Self::set_paused(subsystem, true, reason)
+    }
+
+    pub fn unpause(subsystem: Subsystem, reason: String) -> Result<bool, PauseError> {
+        Self::set_paused(subsystem, false, reason)
+    }
+
+    pub fn is_paused(subsystem: Subsystem) -> bool {
+        PAUSED.with(|paused| paused.borrow().contains(&subsystem))
+    }
+
+    pub fn ensure_active(subsystem: Subsystem) -> Result<(), Paused> {
+        if Self::is_paused(subsystem) {
+            Err(Paused { subsystem })
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn get_paused() -> Vec<Subsystem> {
+        PAUSED.with(|paused| paused.borrow().iter().copied().collect())
+    }
+
+    pub fn get_log(start: u64, length: u64) -> Vec<PauseEvent> {
+        PAUSE_LOG.with(|log| {
+            log.borrow()
+                .iter()
+                .skip(start as usize)
+                .take(length as usize)
+                .cloned()
+                .collect()
+        })
+    }
+
+    fn set_paused(subsystem: Subsystem, paused: bool, reason: String) -> Result<bool, PauseError> {
+        let caller = AccessControl::require(Role::Admin).map_err(|_| PauseError::Unauthorized)?;
+
+        if reason.trim().is_empty() {
+            return Err(PauseError::ReasonRequired);
+        }
+        match (Self::is_paused(subsystem), paused) {
+            (true, true) => return Err(PauseError::AlreadyPaused { subsystem }),
+            (false, false) => return Err(PauseError::NotPaused { subsystem }),
+            _ => {}
+        }
+
+        PAUSED.with(|set| {
+            let mut set = set.borrow_mut();
+            if paused {
+                set.insert(subsystem);
+            } else {
+                set.remove(&subsystem);
+            }
+        });
+
+        PAUSE_LOG.with(|log| {
+            let mut log = log.borrow_mut();
+            let id = log.len() as u64 + 1;
+            log.push(PauseEvent {
+                id,
+                subsystem,
+                paused,
+                reason,
+                changed_by: caller,
+                timestamp: time(),
+            });
+        });
+
+        Ok(true)
+    }
+}
@@ -1,13 +1,31 @@
 use candid::{CandidType, Deserialize, Principal};
 use std::cell::RefCell;
 
+use crate::amount::{Amount, Currency};
+use crate::ret_token::{RETToken, TransferArgs, TransferError};
 use crate::types::TokenType;
 
 #[derive(CandidType, Deserialize, Clone, Debug)]
 pub enum PaymentError {
-    InsufficientBalance,
-    TransferFailed,
-    InvalidToken,
+    InsufficientBalance { required: Amount, available: Amount },
+    // The ledger rejected the transfer for another reason
+    TransferFailed { error: TransferError },
+    // No ledger token, or one that cannot be paid with yet
+    InvalidToken { currency: Currency },
+    InvalidAmount { reason: String },
+}
+
+impl std::fmt::Display for PaymentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PaymentError::InsufficientBalance { required, available } => {
+                write!(f, "Insufficient balance: {} required, {} available", required, available)
+            }
+            PaymentError::TransferFailed { error } => write!(f, "Transfer failed: {}", error),
+            PaymentError::InvalidToken { currency } => write!(f, "{:?} cannot be used for payment", currency),
+            PaymentError::InvalidAmount { reason } => write!(f, "{}", reason),
+        }
+    }
 }
 
 pub struct PaymentManager {
@@ -25,16 +43,26 @@ impl PaymentManager {
         }
     }
 
-    pub fn verify_payment(&self, _from: Principal, _amount: u64, token_type: TokenType) -> Result<bool, PaymentError> {
-        match token_type {
-            TokenType::RET => {
-                // Verify RET balance and payment
-                Ok(true)
-            },
-            TokenType::ICP => {
-                // Verify ICP balance and payment
-                Ok(true)
-            }
+    /// Moves `amount` from `from` to `to` on its currency's ledger and returns the transaction id.
+    pub fn pay(from: Principal, to: Principal, amount: Amount, memo: Option<Vec<u8>>) -> Result<u64, PaymentError> {
+        let amount = amount.validate().map_err(|reason| PaymentError::InvalidAmount { reason })?;
+        match amount.currency.token_type() {
+            Some(TokenType::RET) => RETToken::transfer(TransferArgs {
+                from,
+                to,
+                amount: amount.units,
+                fee: None,
+                memo,
+                created_at_time: None,
+            })
+            .map_err(|error| match error {
+                TransferError::InsufficientFunds { balance } => PaymentError::InsufficientBalance {
+                    required: amount,
+                    available: Amount { units: balance, ..amount },
+                },
+                error => PaymentError::TransferFailed { error },
+            }),
+            Some(TokenType::ICP) | None => Err(PaymentError::InvalidToken { currency: amount.currency }),
         }
     }
 }
@@ -43,4 +71,4 @@ pub fn initialize_payment_manager(ret_ledger: Principal) {
     PAYMENT_MANAGER.with(|manager| {
         *manager.borrow_mut() = Some(PaymentManager::new(ret_ledger));
     });
-} 
\ No newline at end of file
+}
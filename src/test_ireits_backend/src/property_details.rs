@@ -0,0 +1,298 @@
+use candid::{CandidType, Deserialize};
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PropertyType {
+    Residential,
+    Commercial,
+    Industrial,
+    Land,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct Address {
+    pub street: String,
+    pub unit: Option<String>,
+    pub city: String,
+    pub region: String,
+    pub postal_code: String,
+    // ISO 3166-1 alpha-2
+    pub country: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct GeoPoint {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct PropertyDetails {
+    pub address: Address,
+    pub coordinates: Option<GeoPoint>,
+    // None only on records migrated from free-text locations
+    pub property_type: Option<PropertyType>,
+    pub square_footage: Option<u64>,
+    pub year_built: Option<u16>,
+    pub units: Option<u32>,
+    pub occupancy_rate: Option<u16>, // Basis points
+    pub amenities: Vec<String>,
+    // URLs or content ids of listing photos
+    pub photos: Vec<String>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct MigrationReport {
+    pub migrated: u64,
+    pub already_structured: u64,
+    // Next start for the following batch; None when done
+    pub next_start: Option<u64>,
+}
+
+const MAX_AMENITIES: usize = 50;
+const MAX_PHOTOS: usize = 50;
+
+/// Structured property data: validation, migration from the legacy free-text
+/// `location`, and the ICRC-7 metadata derived from it.
+pub struct PropertyData;
+
+impl PropertyData {
+    /// Normalizes codes and checks ranges. Listings must state a property type.
+    pub fn validate(mut details: PropertyDetails) -> Result<PropertyDetails, String> {
+        let address = &mut details.address;
+        address.street = address.street.trim().to_string();
+        address.city = address.city.trim().to_string();
+        address.region = address.region.trim().to_string();
+        address.postal_code = address.postal_code.trim().to_string();
+        address.country = address.country.trim().to_uppercase();
+
+        if address.street.is_empty() || address.city.is_empty() {
+            return Err("Street and city are required".to_string());
+        }
+        if address.country.len() != 2 || !address.country.chars().all(|c| c.is_ascii_uppercase()) {
+            return Err("Country must be a two-letter ISO code".to_string());
+        }
+        if details.property_type.is_none() {
+            return Err("Property type is required".to_string());
+        }
+        if let Some(point) = details.coordinates {
+            if !(-90.0..=90.0).contains(&point.latitude) || !(-180.0..=180.0).contains(&point.longitude) {
+                return Err("Coordinates are out of range".to_string());
+            }
+        }
+        if details.occupancy_rate.is_some_and(|rate| rate > 10_000) {
+            return Err("Occupancy rate cannot exceed 10000 basis points".to_string());
+        }
+        if details.units == Some(0) {
+            return Err("Units must be at least one".to_string());
+        }
+        if details.amenities.len() > MAX_AMENITIES || details.photos.len() > MAX_PHOTOS {
+            return Err(format!("At most {} amenities and {} photos", MAX_AMENITIES, MAX_PHOTOS));
+        }
+
+        details.amenities = details.amenities.iter()
+            .map(|amenity| amenity.trim().to_lowercase())
+            .filter(|amenity| !amenity.is_empty())
+            .collect();
+        details.amenities.sort();
+        details.amenities.dedup();
+        Ok(details)
+    }
+
+    /// Best-effort split of a legacy "street, city, region postal, country" location.
+    /// Anything that can't be placed is left empty.
+    pub fn from_legacy(location: &str) -> PropertyDetails {
+        let parts: Vec<&str> = location.split(',').map(str::trim).filter(|part| !part.is_empty()).collect();
+        let mut address = Address {
+            street: parts.first().map(|part| part.to_string()).unwrap_or_default(),
+            city: parts.get(1).map(|part| part.to_string()).unwrap_or_default(),
+            ..Default::default()
+        };
+
+        if let Some(region) = parts.get(2) {
+            match region.rsplit_once(' ') {
+                Some((name, code)) if code.chars().any(|c| c.is_ascii_digit()) => {
+                    address.region = name.to_string();
+                    address.postal_code = code.to_string();
+                }
+                _ => address.region = region.to_string(),
+            }
+        }
+        if let Some(country) = parts.get(3) {
+            if country.len() == 2 {
+                address.country = country.to_uppercase();
+            }
+        }
+
+        PropertyDetails {
+            address,
+            coordinates: None,
+            property_type: None,
+            square_footage: None,
+            year_built: None,
+            units: None,
+            occupancy_rate: None,
+            amenities: Vec::new(),
+            photos: Vec::new(),
+        }
+    }
+
+    /// One-line location kept in `Property::location` for older clients.
+    pub fn location_line(details: &PropertyDetails) -> String {
+        let address = &details.address;
+        [address.street.as_str(), address.city.as_str(), address.region.as_str(), address.country.as_str()]
+            .iter()
+            .filter(|part| !part.is_empty())
+            .copied()
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn token_tags(details: &PropertyDetails) -> Vec<String> {
+        let mut tags = vec!["real-estate".to_string()];
+        if let Some(property_type) = details.property_type {
+            tags.push(format!("{:?}", property_type).to_lowercase());
+        }
+        if !details.address.city.is_empty() {
+            tags.push(details.address.city.to_lowercase());
+        }
+        tags
+    }
+
+    pub fn token_attributes(details: &PropertyDetails) -> Vec<(String, String)> {
+        let address = &details.address;
+        let mut attributes = vec![
+            ("street".to_string(), address.street.clone()),
+            ("city".to_string(), address.city.clone()),
+            ("region".to_string(), address.region.clone()),
+            ("postal_code".to_string(), address.postal_code.clone()),
+            ("country".to_string(), address.country.clone()),
+        ];
+        if let Some(property_type) = details.property_type {
+            attributes.push(("property_type".to_string(), format!("{:?}", property_type)));
+        }
+        if let Some(point) = details.coordinates {
+            attributes.push(("latitude".to_string(), point.latitude.to_string()));
+            attributes.push(("longitude".to_string(), point.longitude.to_string()));
+        }
+        if let Some(square_footage) = details.square_footage {
+            attributes.push(("square_footage".to_string(), square_footage.to_string()));
+        }
+        if let Some(year_built) = details.year_built {
+            attributes.push(("year_built".to_string(), year_built.to_string()));
+        }
+        if let Some(units) = details.units {
+            attributes.push(("units".to_string(), units.to_string()));
+        }
+        if let Some(occupancy_rate) = details.occupancy_rate {
+            attributes.push(("occupancy_rate_bps".to_string(), occupancy_rate.to_string()));
+        }
+        if !details.amenities.is_empty() {
+            attributes.push(("amenities".to_string(), details.amenities.join(",")));
+        }
+        if let Some(photo) = details.photos.first() {
+            attributes.push(("photo".to_string(), photo.clone()));
+        }
+        attributes.retain(|(_, value)| !value.is_empty());
+        attributes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn details() -> PropertyDetails {
+        PropertyDetails {
+            address: Address {
+                street: " 123 Main St ".to_string(),
+                unit: None,
+                city: "Springfield ".to_string(),
+                region: "IL".to_string(),
+                postal_code: " 62701".to_string(),
+                country: " us".to_string(),
+            },
+            coordinates: Some(GeoPoint { latitude: 39.78, longitude: -89.65 }),
+            property_type: Some(PropertyType::Residential),
+            square_footage: Some(1_800),
+            year_built: Some(1998),
+            units: Some(1),
+            occupancy_rate: Some(9_500),
+            amenities: vec![" Pool".to_string(), "garage".to_string(), "POOL".to_string(), " ".to_string()],
+            photos: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_from_legacy_full_address() {
+        let parsed = PropertyData::from_legacy("123 Main St, Springfield, IL 62701, us");
+        assert_eq!(parsed.address.street, "123 Main St");
+        assert_eq!(parsed.address.city, "Springfield");
+        assert_eq!(parsed.address.region, "IL");
+        assert_eq!(parsed.address.postal_code, "62701");
+        assert_eq!(parsed.address.country, "US");
+        assert!(parsed.property_type.is_none());
+    }
+
+    #[test]
+    fn test_from_legacy_partial_addresses() {
+        // A region without a numeric code keeps all of its words
+        let parsed = PropertyData::from_legacy("1 Rue de Rivoli, Paris, Ile de France, FR");
+        assert_eq!(parsed.address.region, "Ile de France");
+        assert!(parsed.address.postal_code.is_empty());
+
+        // Alphanumeric codes count as postal codes
+        let parsed = PropertyData::from_legacy("10 Downing St, London, Westminster SW1A, GB");
+        assert_eq!((parsed.address.region.as_str(), parsed.address.postal_code.as_str()), ("Westminster", "SW1A"));
+
+        // Country names that aren't ISO codes are dropped, empty segments skipped
+        let parsed = PropertyData::from_legacy("9 Elm St,, Austin, TX 78701, United States");
+        assert_eq!(parsed.address.city, "Austin");
+        assert!(parsed.address.country.is_empty());
+
+        let parsed = PropertyData::from_legacy("Townhouse");
+        assert_eq!(parsed.address.street, "Townhouse");
+        assert!(parsed.address.city.is_empty());
+        assert!(PropertyData::from_legacy("").address.street.is_empty());
+    }
+
+    #[test]
+    fn test_validate_normalizes() {
+        let valid = PropertyData::validate(details()).unwrap();
+        assert_eq!(valid.address.street, "123 Main St");
+        assert_eq!(valid.address.city, "Springfield");
+        assert_eq!(valid.address.postal_code, "62701");
+        assert_eq!(valid.address.country, "US");
+        assert_eq!(valid.amenities, vec!["garage".to_string(), "pool".to_string()]);
+        assert_eq!(PropertyData::location_line(&valid), "123 Main St, Springfield, IL, US");
+    }
+
+    #[test]
+    fn test_validate_rejects() {
+        let cases: Vec<fn(&mut PropertyDetails)> = vec![
+            |details| details.address.street = "  ".to_string(),
+            |details| details.address.city = String::new(),
+            |details| details.address.country = "USA".to_string(),
+            |details| details.address.country = "1A".to_string(),
+            |details| details.property_type = None,
+            |details| details.coordinates = Some(GeoPoint { latitude: 91.0, longitude: 0.0 }),
+            |details| details.coordinates = Some(GeoPoint { latitude: 0.0, longitude: -180.5 }),
+            |details| details.occupancy_rate = Some(10_001),
+            |details| details.units = Some(0),
+            |details| details.photos = vec![String::new(); MAX_PHOTOS + 1],
+        ];
+        for (index, change) in cases.into_iter().enumerate() {
+            let mut invalid = details();
+            change(&mut invalid);
+            assert!(PropertyData::validate(invalid).is_err(), "case {}", index);
+        }
+    }
+
+    #[test]
+    fn test_legacy_records_need_details_before_validating() {
+        // Migrated records have no property type, so they can't be listed as-is
+        let migrated = PropertyData::from_legacy("123 Main St, Springfield, IL 62701, US");
+        assert!(PropertyData::validate(migrated.clone()).is_err());
+        let completed = PropertyDetails { property_type: Some(PropertyType::Land), ..migrated };
+        assert!(PropertyData::validate(completed).is_ok());
+    }
+}
@@ -0,0 +1,285 @@
+use candid::{CandidType, Deserialize, Principal};
+use ic_cdk::api::caller as ic_caller;
+use ic_cdk::api::time;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use crate::amount::Amount;
+use crate::delegation::Delegation;
+use crate::governance::{GovernanceError, ProposalStatus, VoteChoice, VoteRecord};
+use crate::marketplace::Marketplace;
+use crate::PropertyStatus;
+
+const VOTING_PERIOD: u64 = 7 * 24 * 60 * 60 * 1_000_000_000; // 7 days in nanoseconds
+const QUORUM_PERCENTAGE: u64 = 5_000; // Half of all shares must vote
+const SALE_THRESHOLD: u64 = 6_667; // Two thirds of all shares must approve a sale
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum PropertyProposalKind {
+    Sale { price: Amount, buyer: Option<Principal> },
+    Refinance { amount: Amount, terms: String },
+    Renovation { budget: Amount, scope: String },
+    ReplaceManager { new_manager: Principal },
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct PropertyProposal {
+    pub id: u64,
+    pub property_id: u64,
+    pub proposer: Principal,
+    pub kind: PropertyProposalKind,
+    pub description: String,
+    pub status: ProposalStatus,
+    pub created_at: u64,
+    pub voting_ends_at: u64,
+    // Sum of share_percentage across the electorate, normally 10000
+    pub total_shares: u64,
+    pub votes_for: u64,
+    pub votes_against: u64,
+    pub votes_abstain: u64,
+    pub executed_at: Option<u64>,
+    pub failure_reason: Option<String>,
+}
+
+thread_local! {
+    static PROPERTY_PROPOSALS: RefCell<BTreeMap<u64, PropertyProposal>> = const { RefCell::new(BTreeMap::new()) };
+    // Shareholder weights, after delegation, frozen at proposal creation
+    static ELECTORATES: RefCell<HashMap<u64, HashMap<Principal, u64>>> = RefCell::new(HashMap::new());
+    static PROPERTY_VOTES: RefCell<HashMap<u64, HashMap<Principal, VoteRecord>>> = RefCell::new(HashMap::new());
+    static PROPERTY_PROPOSAL_COUNTER: RefCell<u64> = const { RefCell::new(0) };
+}
+
+pub struct PropertyGovernance;
+
+impl PropertyGovernance {
+    pub fn submit_proposal(
+        property_id: u64,
+        kind: PropertyProposalKind,
+        description: String,
+    ) -> Result<u64, GovernanceError> {
+        let caller = ic_caller();
+        let shares = Marketplace::get_property_shares(property_id)
+            .ok_or(GovernanceError::NoShareholders { property_id })?;
+
+        let mut holdings: HashMap<Principal, u64> = HashMap::new();
+        for share in &shares {
+            *holdings.entry(share.owner).or_default() += share.share_percentage as u64;
+        }
+
+        if !holdings.contains_key(&caller) {
+            return Err(GovernanceError::NotShareholder);
+        }
+
+        let electorate = Delegation::apply_property_delegations(property_id, holdings);
+        match &kind {
+            PropertyProposalKind::Sale { price, .. } => {
+                price.validate_positive().map_err(|e| GovernanceError::InvalidAmount { reason: format!("Invalid sale price: {}", e) })?;
+            }
+            PropertyProposalKind::Refinance { amount, .. } => {
+                amount.validate_positive().map_err(|e| GovernanceError::InvalidAmount { reason: format!("Invalid refinance amount: {}", e) })?;
+            }
+            PropertyProposalKind::Renovation { budget, .. } => {
+                budget.validate_positive().map_err(|e| GovernanceError::InvalidAmount { reason: format!("Invalid renovation budget: {}", e) })?;
+            }
+            PropertyProposalKind::ReplaceManager { .. } => {}
+        }
+
+        let id = PROPERTY_PROPOSAL_COUNTER.with(|counter| {
+            let mut counter = counter.borrow_mut();
+            *counter += 1;
+            *counter
+        });
+
+        let now = time();
+        let total_shares = electorate.values().sum();
+        PROPERTY_PROPOSALS.with(|proposals| {
+            proposals.borrow_mut().insert(id, PropertyProposal {
+                id,
+                property_id,
+                proposer: caller,
+                kind,
+                description,
+                status: ProposalStatus::Open,
+                created_at: now,
+                voting_ends_at: now + VOTING_PERIOD,
+                total_shares,
+                votes_for: 0,
+                votes_against: 0,
+                votes_abstain: 0,
+                executed_at: None,
+                failure_reason: None,
+            });
+        });
+        ELECTORATES.with(|electorates| {
+            electorates.borrow_mut().insert(id, electorate);
+        });
+
+        ic_cdk_timers::set_timer(Duration::from_nanos(VOTING_PERIOD), move || {
+            let _ = Self::finalize(id);
+        });
+
+        Ok(id)
+    }
+
+    pub fn vote(proposal_id: u64, choice: VoteChoice) -> Result<u64, GovernanceError> {
+        let caller = ic_caller();
+        let proposal = Self::get_proposal(proposal_id).ok_or(GovernanceError::ProposalNotFound { proposal_id })?;
+
+        if proposal.status != ProposalStatus::Open || time() >= proposal.voting_ends_at {
+            return Err(GovernanceError::VotingClosed);
+        }
+
+        let already_voted = PROPERTY_VOTES.with(|votes| {
+            votes.borrow()
+                .get(&proposal_id)
+                .map(|votes| votes.contains_key(&caller))
+                .unwrap_or(false)
+        });
+        if already_voted {
+            return Err(GovernanceError::AlreadyVoted);
+        }
+
+        let weight = Self::voting_power(proposal_id, caller);
+        if weight == 0 {
+            return Err(GovernanceError::NoVotingPower);
+        }
+
+        PROPERTY_VOTES.with(|votes| {
+            votes.borrow_mut()
+                .entry(proposal_id)
+                .or_default()
+                .insert(caller, VoteRecord {
+                    choice,
+                    weight,
+                    timestamp: time(),
+                });
+        });
+
+        PROPERTY_PROPOSALS.with(|proposals| {
+            if let Some(proposal) = proposals.borrow_mut().get_mut(&proposal_id) {
+                match choice {
+                    VoteChoice::For => proposal.votes_for += weight,
+                    VoteChoice::Against => proposal.votes_against += weight,
+                    VoteChoice::Abstain => proposal.votes_abstain += weight,
+                }
+            }
+        });
+
+        Ok(weight)
+    }
+
+    /// Shares held by or delegated to `voter` when the proposal was created.
+    pub fn voting_power(proposal_id: u64, voter: Principal) -> u64 {
+        ELECTORATES.with(|electorates| {
+            electorates.borrow()
+                .get(&proposal_id)
+                .and_then(|electorate| electorate.get(&voter).copied())
+                .unwrap_or(0)
+        })
+    }
+
+    /// Tallies a proposal after its voting period and applies it to the property if it passed.
+    pub fn finalize(proposal_id: u64) -> Result<ProposalStatus, GovernanceError> {
+        let proposal = Self::get_proposal(proposal_id).ok_or(GovernanceError::ProposalNotFound { proposal_id })?;
+
+        if proposal.status != ProposalStatus::Open {
+            return Err(GovernanceError::NotOpen);
+        }
+        if time() < proposal.voting_ends_at {
+            return Err(GovernanceError::VotingNotEnded { ends_at: proposal.voting_ends_at });
+        }
+
+        let total = proposal.total_shares as u128;
+        let turnout = (proposal.votes_for + proposal.votes_against + proposal.votes_abstain) as u128;
+        let passed = match proposal.kind {
+            // Selling the underlying asset needs a supermajority of all shares, not just of votes cast
+            PropertyProposalKind::Sale { .. } => {
+                proposal.votes_for as u128 * 10_000 >= total * SALE_THRESHOLD as u128
+            }
+            _ => {
+                turnout * 10_000 >= total * QUORUM_PERCENTAGE as u128
+                    && proposal.votes_for > proposal.votes_against
+            }
+        };
+
+        let (status, failure_reason) = if !passed {
+            (ProposalStatus::Rejected, None)
+        } else {
+            match Self::apply(&proposal) {
+                Ok(()) => (ProposalStatus::Executed, None),
+                Err(reason) => (ProposalStatus::Failed, Some(reason)),
+            }
+        };
+
+        PROPERTY_PROPOSALS.with(|proposals| {
+            if let Some(proposal) = proposals.borrow_mut().get_mut(&proposal_id) {
+                proposal.status = status.clone();
+                proposal.failure_reason = failure_reason;
+                if passed {
+                    proposal.executed_at = Some(time());
+                }
+            }
+        });
+
+        Ok(status)
+    }
+
+    pub fn cancel(proposal_id: u64) -> Result<bool, GovernanceError> {
+        let caller = ic_caller();
+
+        PROPERTY_PROPOSALS.with(|proposals| {
+            let mut proposals = proposals.borrow_mut();
+            let proposal = proposals.get_mut(&proposal_id)
+                .ok_or(GovernanceError::ProposalNotFound { proposal_id })?;
+
+            if proposal.proposer != caller {
+                return Err(GovernanceError::NotProposer);
+            }
+            if proposal.status != ProposalStatus::Open {
+                return Err(GovernanceError::NotOpen);
+            }
+
+            proposal.status = ProposalStatus::Cancelled;
+            Ok(true)
+        })
+    }
+
+    pub fn get_proposal(proposal_id: u64) -> Option<PropertyProposal> {
+        PROPERTY_PROPOSALS.with(|proposals| proposals.borrow().get(&proposal_id).cloned())
+    }
+
+    pub fn get_property_proposals(property_id: u64) -> Vec<PropertyProposal> {
+        PROPERTY_PROPOSALS.with(|proposals| {
+            proposals.borrow()
+                .values()
+                .filter(|proposal| proposal.property_id == property_id)
+                .cloned()
+                .collect()
+        })
+    }
+
+    pub fn get_vote(proposal_id: u64, voter: Principal) -> Option<VoteRecord> {
+        PROPERTY_VOTES.with(|votes| {
+            votes.borrow()
+                .get(&proposal_id)
+                .and_then(|votes| votes.get(&voter).cloned())
+        })
+    }
+
+    // Refinance and renovation approvals are recorded on the proposal itself;
+    // sales and manager changes update the property.
+    fn apply(proposal: &PropertyProposal) -> Result<(), String> {
+        match &proposal.kind {
+            PropertyProposalKind::Sale { .. } => {
+                crate::set_property_status(proposal.property_id, PropertyStatus::UnderContract)
+                    .map_err(|error| error.to_string())
+            }
+            PropertyProposalKind::ReplaceManager { new_manager } => {
+                crate::set_property_manager(proposal.property_id, *new_manager)
+                    .map_err(|error| error.to_string())
+            }
+            PropertyProposalKind::Refinance { .. } | PropertyProposalKind::Renovation { .. } => Ok(()),
+        }
+    }
+}
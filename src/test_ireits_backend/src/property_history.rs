@@ -0,0 +1,114 @@
+use candid::{CandidType, Deserialize, Principal};
+use ic_cdk::api::time;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::amount::Amount;
+use crate::property_details::{PropertyData, PropertyDetails};
+use crate::{Property, PropertyStatus, RentalIncome};
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct PropertyUpdate {
+    pub price: Option<Amount>,
+    pub location: Option<String>,
+    pub description: Option<String>,
+    pub rental_income: Option<RentalIncome>,
+    // Replaces `location` with its one-line form when set
+    pub details: Option<PropertyDetails>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum PropertyChangeKind {
+    Update,
+    Delist,
+    OwnershipTransfer,
+    // Applied by an executed governance proposal
+    ManagerChange,
+    // From a governance proposal or an appraiser's valuation
+    PriceChange,
+}
+
+// The property as it was at `version`, and the change that replaced it
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct PropertyRevision {
+    pub version: u64,
+    pub owner: Principal,
+    pub manager: Option<Principal>,
+    pub price: Amount,
+    pub location: String,
+    pub description: String,
+    pub rental_income: Option<RentalIncome>,
+    pub details: Option<PropertyDetails>,
+    pub status: PropertyStatus,
+    pub replaced_by: PropertyChangeKind,
+    pub changed_by: Principal,
+    pub timestamp: u64,
+}
+
+thread_local! {
+    static REVISIONS: RefCell<HashMap<u64, Vec<PropertyRevision>>> = RefCell::new(HashMap::new());
+}
+
+/// Change log of property records. Every edit, delisting, ownership transfer and
+/// governance change keeps the prior version here before bumping `Property::version`.
+pub struct PropertyHistory;
+
+impl PropertyHistory {
+    /// Snapshots `property` before a change and bumps its version. Call before mutating.
+    pub(crate) fn record(property: &mut Property, replaced_by: PropertyChangeKind, changed_by: Principal) {
+        let revision = PropertyRevision {
+            version: property.version,
+            owner: property.owner,
+            manager: property.manager,
+            price: property.price,
+            location: property.location.clone(),
+            description: property.description.clone(),
+            rental_income: property.rental_income.clone(),
+            details: property.details.clone(),
+            status: property.status,
+            replaced_by,
+            changed_by,
+            timestamp: time(),
+        };
+
+        REVISIONS.with(|revisions| {
+            revisions.borrow_mut().entry(property.id).or_default().push(revision);
+        });
+        property.version += 1;
+    }
+
+    pub fn get_revisions(property_id: u64) -> Vec<PropertyRevision> {
+        REVISIONS.with(|revisions| {
+            revisions.borrow().get(&property_id).cloned().unwrap_or_default()
+        })
+    }
+
+    pub fn get_revision(property_id: u64, version: u64) -> Option<PropertyRevision> {
+        REVISIONS.with(|revisions| {
+            revisions.borrow()
+                .get(&property_id)
+                .and_then(|revisions| revisions.iter().find(|revision| revision.version == version).cloned())
+        })
+    }
+
+    /// Checks an update and normalizes any structured details in it.
+    pub(crate) fn validate_update(mut update: PropertyUpdate) -> Result<PropertyUpdate, String> {
+        if update.price.is_none()
+            && update.location.is_none()
+            && update.description.is_none()
+            && update.rental_income.is_none()
+            && update.details.is_none()
+        {
+            return Err("Nothing to update".to_string());
+        }
+        update.price = update.price.map(Amount::validate_positive).transpose()?;
+        if let Some(income) = &update.rental_income {
+            income.monthly_amount.validate()?;
+        }
+        if update.location.as_ref().is_some_and(|location| location.trim().is_empty()) {
+            return Err("Location cannot be empty".to_string());
+        }
+        update.details = update.details.map(PropertyData::validate).transpose()?;
+        Ok(update)
+    }
+}
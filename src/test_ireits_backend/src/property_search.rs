@@ -0,0 +1,266 @@
+use candid::{CandidType, Deserialize, Principal};
+use ic_stable_structures::storable::Blob;
+use ic_stable_structures::StableBTreeMap;
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+
+use crate::memory::{
+    self, Memory, PROPERTY_CITY_INDEX, PROPERTY_INDEX_KEYS, PROPERTY_OWNER_INDEX, PROPERTY_STATUS_INDEX,
+};
+use crate::amount::Amount;
+use crate::geo_search::GeoSearch;
+use crate::property_details::PropertyType;
+use crate::{Property, PropertyMap, PropertyStatus};
+
+pub const MAX_PAGE_SIZE: u64 = 100;
+const CITY_KEY_SIZE: usize = 64;
+
+type OwnerKey = Blob<29>;
+type CityKey = Blob<CITY_KEY_SIZE>;
+// What a property is currently indexed under, so a refresh can drop stale entries
+type IndexKeys = (OwnerKey, (u8, CityKey));
+
+#[derive(CandidType, Deserialize, Clone, Debug, Default)]
+pub struct PropertyFilter {
+    // Any of these; empty matches every status
+    pub statuses: Vec<PropertyStatus>,
+    pub owner: Option<Principal>,
+    // Only properties priced in the bound's currency match
+    pub min_price: Option<Amount>,
+    pub max_price: Option<Amount>,
+    // Case-insensitive exact matches on the structured address
+    pub city: Option<String>,
+    pub country: Option<String>,
+    pub property_type: Option<PropertyType>,
+    // Annual rental income over price, in basis points
+    pub min_yield_bps: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum PropertySort {
+    // Price sorts compare units; set a price bound to keep results in one currency
+    PriceAsc,
+    PriceDesc,
+    YieldAsc,
+    YieldDesc,
+    Newest,
+    Oldest,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct PropertyPage {
+    pub items: Vec<Property>,
+    // Pass back with the same filter and sort for the following page; None on the last page
+    pub next_cursor: Option<String>,
+    pub total: u64,
+}
+
+thread_local! {
+    static OWNER_INDEX: RefCell<StableBTreeMap<(OwnerKey, u64), (), Memory>> = RefCell::new(
+        StableBTreeMap::init(memory::get(PROPERTY_OWNER_INDEX))
+    );
+    static STATUS_INDEX: RefCell<StableBTreeMap<(u8, u64), (), Memory>> = RefCell::new(
+        StableBTreeMap::init(memory::get(PROPERTY_STATUS_INDEX))
+    );
+    static CITY_INDEX: RefCell<StableBTreeMap<(CityKey, u64), (), Memory>> = RefCell::new(
+        StableBTreeMap::init(memory::get(PROPERTY_CITY_INDEX))
+    );
+    static INDEX_KEYS: RefCell<StableBTreeMap<u64, IndexKeys, Memory>> = RefCell::new(
+        StableBTreeMap::init(memory::get(PROPERTY_INDEX_KEYS))
+    );
+}
+
+/// Filtered, sorted and paginated property queries, backed by secondary indexes on
+/// owner, status and city in stable memory. Call `refresh` after any change to those fields
+/// or to the coordinates, which it passes on to the geospatial index.
+pub struct PropertySearch;
+
+impl PropertySearch {
+    pub(crate) fn refresh(property: &Property) {
+        GeoSearch::refresh(property);
+
+        let keys: IndexKeys = (
+            Self::owner_key(property.owner),
+            (Self::status_code(property.status), Self::city_key(Self::city(property))),
+        );
+        let previous = INDEX_KEYS.with(|index| index.borrow().get(&property.id));
+        if previous.as_ref() == Some(&keys) {
+            return;
+        }
+
+        let id = property.id;
+        if let Some((owner, (status, city))) = previous {
+            OWNER_INDEX.with(|index| index.borrow_mut().remove(&(owner, id)));
+            STATUS_INDEX.with(|index| index.borrow_mut().remove(&(status, id)));
+            CITY_INDEX.with(|index| index.borrow_mut().remove(&(city, id)));
+        }
+
+        let (owner, (status, city)) = keys;
+        OWNER_INDEX.with(|index| index.borrow_mut().insert((owner, id), ()));
+        STATUS_INDEX.with(|index| index.borrow_mut().insert((status, id), ()));
+        if !city.as_slice().is_empty() {
+            CITY_INDEX.with(|index| index.borrow_mut().insert((city, id), ()));
+        }
+        INDEX_KEYS.with(|index| index.borrow_mut().insert(id, keys));
+    }
+
+    pub fn owned_by(owner: Principal) -> Vec<u64> {
+        let owner = Self::owner_key(owner);
+        OWNER_INDEX.with(|index| {
+            index.borrow()
+                .range((owner, 0)..=(owner, u64::MAX))
+                .map(|((_, id), _)| id)
+                .collect()
+        })
+    }
+
+    pub fn search(
+        properties: &PropertyMap,
+        filter: PropertyFilter,
+        sort: PropertySort,
+        cursor: Option<String>,
+        limit: u64,
+    ) -> Result<PropertyPage, String> {
+        if limit == 0 || limit > MAX_PAGE_SIZE {
+            return Err(format!("Limit must be between 1 and {}", MAX_PAGE_SIZE));
+        }
+        if let (Some(min), Some(max)) = (filter.min_price, filter.max_price) {
+            if min.checked_cmp(max)?.is_gt() {
+                return Err("Minimum price exceeds maximum price".to_string());
+            }
+        }
+        let cursor = cursor.as_deref().map(Self::parse_cursor).transpose()?;
+
+        let mut matches: Vec<(u64, u64)> = Self::candidates(&filter)
+            .into_iter()
+            .filter_map(|id| properties.get(&id))
+            .filter(|property| Self::matches(property, &filter))
+            .map(|property| (Self::sort_key(&property, sort), property.id))
+            .collect();
+        let descending = matches!(sort, PropertySort::PriceDesc | PropertySort::YieldDesc | PropertySort::Newest);
+        matches.sort();
+        if descending {
+            matches.reverse();
+        }
+
+        // Resume after the cursor's position in the ordering, even if that property has since changed
+        let total = matches.len() as u64;
+        let start = cursor.map_or(0, |cursor| {
+            matches.partition_point(|key| if descending { *key >= cursor } else { *key <= cursor })
+        });
+        let page: Vec<(u64, u64)> = matches.iter().skip(start).take(limit as usize).copied().collect();
+        let next_cursor = match page.last() {
+            Some(last) if start + page.len() < matches.len() => Some(format!("{}:{}", last.0, last.1)),
+            _ => None,
+        };
+
+        Ok(PropertyPage {
+            items: page.iter().filter_map(|(_, id)| properties.get(id)).collect(),
+            next_cursor,
+            total,
+        })
+    }
+
+    // Narrowest index for the filter; a full scan only when nothing indexed is filtered on
+    fn candidates(filter: &PropertyFilter) -> BTreeSet<u64> {
+        if let Some(owner) = filter.owner {
+            return Self::owned_by(owner).into_iter().collect();
+        }
+        if let Some(city) = &filter.city {
+            let city = Self::city_key(city);
+            return CITY_INDEX.with(|index| {
+                index.borrow()
+                    .range((city, 0)..=(city, u64::MAX))
+                    .map(|((_, id), _)| id)
+                    .collect()
+            });
+        }
+        if !filter.statuses.is_empty() {
+            return STATUS_INDEX.with(|index| {
+                let index = index.borrow();
+                filter.statuses.iter()
+                    .map(|status| Self::status_code(*status))
+                    .flat_map(|code| index.range((code, 0)..=(code, u64::MAX)).map(|((_, id), _)| id).collect::<Vec<_>>())
+                    .collect()
+            });
+        }
+        INDEX_KEYS.with(|index| index.borrow().iter().map(|(id, _)| id).collect())
+    }
+
+    pub(crate) fn matches(property: &Property, filter: &PropertyFilter) -> bool {
+        let address = property.details.as_ref().map(|details| &details.address);
+
+        (filter.statuses.is_empty() || filter.statuses.contains(&property.status))
+            && filter.owner.is_none_or(|owner| property.owner == owner)
+            && filter.min_price.is_none_or(|min| property.price.checked_cmp(min).is_ok_and(|order| order.is_ge()))
+            && filter.max_price.is_none_or(|max| property.price.checked_cmp(max).is_ok_and(|order| order.is_le()))
+            && filter.city.as_ref().is_none_or(|city| {
+                address.is_some_and(|address| Self::city_key(&address.city) == Self::city_key(city))
+            })
+            && filter.country.as_ref().is_none_or(|country| {
+                address.is_some_and(|address| address.country.eq_ignore_ascii_case(country.trim()))
+            })
+            && filter.property_type.is_none_or(|property_type| {
+                property.details.as_ref().is_some_and(|details| details.property_type == Some(property_type))
+            })
+            && filter.min_yield_bps.is_none_or(|min| Self::yield_bps(property) >= min)
+    }
+
+    // Zero unless rent and price are in the same currency
+    pub fn yield_bps(property: &Property) -> u64 {
+        match &property.rental_income {
+            Some(income) if income.monthly_amount.currency == property.price.currency && !property.price.is_zero() => {
+                (income.monthly_amount.units as u128 * 12 * 10_000 / property.price.units as u128) as u64
+            }
+            _ => 0,
+        }
+    }
+
+    // Ids increase with listing time, so they stand in for recency
+    fn sort_key(property: &Property, sort: PropertySort) -> u64 {
+        match sort {
+            PropertySort::PriceAsc | PropertySort::PriceDesc => property.price.units,
+            PropertySort::YieldAsc | PropertySort::YieldDesc => Self::yield_bps(property),
+            PropertySort::Newest | PropertySort::Oldest => property.id,
+        }
+    }
+
+    fn parse_cursor(cursor: &str) -> Result<(u64, u64), String> {
+        cursor.split_once(':')
+            .and_then(|(key, id)| Some((key.parse().ok()?, id.parse().ok()?)))
+            .ok_or("Invalid cursor".to_string())
+    }
+
+    fn city(property: &Property) -> &str {
+        property.details.as_ref().map_or("", |details| details.address.city.as_str())
+    }
+
+    fn owner_key(owner: Principal) -> OwnerKey {
+        OwnerKey::try_from(owner.as_slice()).expect("principals are at most 29 bytes")
+    }
+
+    // Lowercased and cut to the key size on a character boundary
+    fn city_key(city: &str) -> CityKey {
+        let city = city.trim().to_lowercase();
+        let mut end = city.len().min(CITY_KEY_SIZE);
+        while !city.is_char_boundary(end) {
+            end -= 1;
+        }
+        CityKey::try_from(&city.as_bytes()[..end]).expect("city key fits")
+    }
+
+    // Stored in stable memory; never renumber
+    fn status_code(status: PropertyStatus) -> u8 {
+        match status {
+            PropertyStatus::Draft => 0,
+            PropertyStatus::PendingVerification => 1,
+            PropertyStatus::Verified => 2,
+            PropertyStatus::Listed => 3,
+            PropertyStatus::UnderContract => 4,
+            PropertyStatus::Sold => 5,
+            PropertyStatus::Tokenized => 6,
+            PropertyStatus::Delisted => 7,
+            PropertyStatus::Archived => 8,
+        }
+    }
+}
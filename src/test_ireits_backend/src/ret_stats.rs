@@ -0,0 +1,248 @@
+use candid::{CandidType, Deserialize, Principal};
+use ic_cdk::api::caller as ic_caller;
+use ic_cdk::api::time;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+
+use crate::access_control::{AccessControl, Role};
+use crate::ret_token::{RETToken, TokenError};
+
+const HOUR: u64 = 60 * 60 * 1_000_000_000; // 1 hour in nanoseconds
+const DAY: u64 = 24 * HOUR;
+const MAX_HOURLY_SNAPSHOTS: usize = 24 * 30; // 30 days
+const MAX_DAILY_SNAPSHOTS: usize = 365;
+const PRICE_RETENTION: u64 = 7 * DAY;
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum PriceSource {
+    MarketplaceTrade,
+    Amm,
+    Oracle,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct PricePoint {
+    pub timestamp: u64,
+    // Quote units per whole RET (10^decimals base units)
+    pub price: u64,
+    pub source: PriceSource,
+}
+
+#[derive(CandidType, Deserialize, Clone, Copy, Debug)]
+pub enum SnapshotGranularity {
+    Hourly,
+    Daily,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct StatsSnapshot {
+    pub timestamp: u64,
+    pub unique_holders: u64,
+    pub total_supply: u64,
+    pub circulating_supply: u64,
+    pub total_staked: u64,
+    pub total_transactions: u64,
+    pub volume_24h: u64,
+    pub price: u64,
+    pub market_cap: u64,
+}
+
+thread_local! {
+    // Hourly (bucket start, volume) pairs covering the last 24 hours
+    static VOLUME_BUCKETS: RefCell<VecDeque<(u64, u64)>> = const { RefCell::new(VecDeque::new()) };
+    static PRICE_HISTORY: RefCell<VecDeque<PricePoint>> = const { RefCell::new(VecDeque::new()) };
+    static PRICE_FEEDS: RefCell<HashMap<Principal, PriceSource>> = RefCell::new(HashMap::new());
+    static HOURLY_SNAPSHOTS: RefCell<VecDeque<StatsSnapshot>> = const { RefCell::new(VecDeque::new()) };
+    static DAILY_SNAPSHOTS: RefCell<VecDeque<StatsSnapshot>> = const { RefCell::new(VecDeque::new()) };
+}
+
+pub struct RETStats;
+
+impl RETStats {
+    pub(crate) fn record_volume(amount: u64) {
+        let now = time();
+        let bucket_start = now - now % HOUR;
+
+        VOLUME_BUCKETS.with(|buckets| {
+            let mut buckets = buckets.borrow_mut();
+            match buckets.back_mut() {
+                Some((start, volume)) if *start == bucket_start => *volume += amount,
+                _ => buckets.push_back((bucket_start, amount)),
+            }
+            while buckets.front().is_some_and(|(start, _)| start + DAY <= bucket_start) {
+                buckets.pop_front();
+            }
+        });
+    }
+
+    pub fn volume_24h() -> u64 {
+        let now = time();
+        VOLUME_BUCKETS.with(|buckets| {
+            buckets.borrow()
+                .iter()
+                .filter(|(start, _)| start + DAY > now)
+                .map(|(_, volume)| volume)
+                .sum()
+        })
+    }
+
+    pub(crate) fn record_price(price: u64, source: PriceSource) {
+        let now = time();
+
+        PRICE_HISTORY.with(|history| {
+            let mut history = history.borrow_mut();
+            history.push_back(PricePoint {
+                timestamp: now,
+                price,
+                source,
+            });
+            while history.len() > 1 && history.front().is_some_and(|point| point.timestamp + PRICE_RETENTION < now) {
+                history.pop_front();
+            }
+        });
+
+        Self::maybe_snapshot();
+    }
+
+    /// Registers (or with `None`, removes) a principal allowed to report prices,
+    /// e.g. the AMM pool or the marketplace settlement canister.
+    pub fn set_price_feed(feed: Principal, source: Option<PriceSource>) -> Result<bool, TokenError> {
+        AccessControl::require(Role::Admin).map_err(|_| TokenError::Unauthorized)?;
+
+        PRICE_FEEDS.with(|feeds| {
+            let mut feeds = feeds.borrow_mut();
+            match source {
+                Some(source) => feeds.insert(feed, source),
+                None => feeds.remove(&feed),
+            };
+        });
+
+        Ok(true)
+    }
+
+    pub fn report_price(price: u64) -> Result<bool, TokenError> {
+        let caller = ic_caller();
+        let source = PRICE_FEEDS.with(|feeds| feeds.borrow().get(&caller).copied())
+            .ok_or(TokenError::NotPriceFeed)?;
+
+        if price == 0 {
+            return Err(TokenError::ZeroAmount);
+        }
+
+        Self::record_price(price, source);
+        Ok(true)
+    }
+
+    pub fn current_price() -> Option<PricePoint> {
+        PRICE_HISTORY.with(|history| history.borrow().back().cloned())
+    }
+
+    pub fn market_cap(circulating_supply: u64) -> u64 {
+        let decimals = RETToken::get_metadata().map(|metadata| metadata.decimals).unwrap_or(0);
+        let price = Self::current_price().map(|point| point.price).unwrap_or(0);
+
+        (circulating_supply as u128 * price as u128 / 10u128.pow(decimals as u32)) as u64
+    }
+
+    /// Change in basis points against the last price recorded at least 24 hours ago,
+    /// or the oldest retained price if there is no such point. Rounds toward zero.
+    pub fn price_change_24h_bps() -> i64 {
+        let now = time();
+        PRICE_HISTORY.with(|history| {
+            let history = history.borrow();
+            let current = match history.back() {
+                Some(point) => point.price,
+                None => return 0,
+            };
+            let reference = history.iter()
+                .rev()
+                .find(|point| point.timestamp + DAY <= now)
+                .or_else(|| history.front())
+                .map(|point| point.price)
+                .unwrap_or(current);
+
+            if reference == 0 {
+                return 0;
+            }
+            let change = (current as i128 - reference as i128) * 10_000 / reference as i128;
+            change.clamp(i64::MIN as i128, i64::MAX as i128) as i64
+        })
+    }
+
+    /// Takes hourly and daily snapshots the first time stats change in a new period.
+    pub(crate) fn maybe_snapshot() {
+        let now = time();
+        let needs_hourly = HOURLY_SNAPSHOTS.with(|snapshots| {
+            snapshots.borrow().back().is_none_or(|last| last.timestamp / HOUR < now / HOUR)
+        });
+        let needs_daily = DAILY_SNAPSHOTS.with(|snapshots| {
+            snapshots.borrow().back().is_none_or(|last| last.timestamp / DAY < now / DAY)
+        });
+        if !needs_hourly && !needs_daily {
+            return;
+        }
+
+        let snapshot = Self::current_snapshot(now);
+        if needs_hourly {
+            HOURLY_SNAPSHOTS.with(|snapshots| {
+                let mut snapshots = snapshots.borrow_mut();
+                snapshots.push_back(snapshot.clone());
+                if snapshots.len() > MAX_HOURLY_SNAPSHOTS {
+                    snapshots.pop_front();
+                }
+            });
+        }
+        if needs_daily {
+            DAILY_SNAPSHOTS.with(|snapshots| {
+                let mut snapshots = snapshots.borrow_mut();
+                snapshots.push_back(snapshot);
+                if snapshots.len() > MAX_DAILY_SNAPSHOTS {
+                    snapshots.pop_front();
+                }
+            });
+        }
+    }
+
+    pub fn get_history(granularity: SnapshotGranularity, from: u64, to: u64) -> Vec<StatsSnapshot> {
+        let select = |snapshots: &VecDeque<StatsSnapshot>| {
+            snapshots.iter()
+                .filter(|snapshot| snapshot.timestamp >= from && snapshot.timestamp <= to)
+                .cloned()
+                .collect()
+        };
+
+        match granularity {
+            SnapshotGranularity::Hourly => HOURLY_SNAPSHOTS.with(|snapshots| select(&snapshots.borrow())),
+            SnapshotGranularity::Daily => DAILY_SNAPSHOTS.with(|snapshots| select(&snapshots.borrow())),
+        }
+    }
+
+    pub fn get_price_history(from: u64, to: u64) -> Vec<PricePoint> {
+        PRICE_HISTORY.with(|history| {
+            history.borrow()
+                .iter()
+                .filter(|point| point.timestamp >= from && point.timestamp <= to)
+                .cloned()
+                .collect()
+        })
+    }
+
+    fn current_snapshot(now: u64) -> StatsSnapshot {
+        let stats = RETToken::get_stats();
+        let (total_supply, circulating_supply) = RETToken::get_metadata()
+            .map(|metadata| (metadata.total_supply, metadata.circulating_supply))
+            .unwrap_or((0, 0));
+
+        StatsSnapshot {
+            timestamp: now,
+            unique_holders: stats.unique_holders,
+            total_supply,
+            circulating_supply,
+            total_staked: stats.total_staked,
+            total_transactions: stats.total_transactions,
+            volume_24h: stats.volume_24h,
+            price: Self::current_price().map(|point| point.price).unwrap_or(0),
+            market_cap: stats.market_cap,
+        }
+    }
+}
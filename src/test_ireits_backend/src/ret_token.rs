@@ -4,11 +4,21 @@ use ic_cdk::api::time;
 use std::cell::RefCell;
 use std::collections::HashMap;
 
+use crate::checkpoints::Checkpoints;
+use crate::compliance::{Compliance, ComplianceAction, ComplianceRejection};
+use crate::enforcement::{AssetClass, Enforcement, Frozen};
+use crate::pause::{CircuitBreaker, Paused, Subsystem};
+use crate::ret_stats::RETStats;
+
 const INITIAL_SUPPLY: u64 = 10_000_000;
 const MAX_SUPPLY: u64 = 20_000_000;
 const AIRDROP_ALLOCATION: u64 = INITIAL_SUPPLY / 2; // 50% for testing
 const MIN_STAKE_DURATION: u64 = 30 * 24 * 60 * 60 * 1_000_000_000; // 30 days in nanoseconds
-const STAKE_APR: u64 = 10; // 10% APR for staking
+const MAX_STAKE_DURATION: u64 = 4 * 365 * 24 * 60 * 60 * 1_000_000_000; // 4 years in nanoseconds
+const DEFAULT_STAKE_APR: u64 = 10; // 10% APR for staking
+const TX_WINDOW: u64 = 24 * 60 * 60 * 1_000_000_000; // 24 hours in nanoseconds
+const PERMITTED_DRIFT: u64 = 60 * 1_000_000_000; // 60 seconds in nanoseconds
+const MAX_MEMO_LENGTH: usize = 32;
 
 #[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct TokenMetadata {
@@ -40,28 +50,197 @@ pub struct TokenStats {
     pub unique_holders: u64,
     pub market_cap: u64,
     pub volume_24h: u64,
-    pub price_change_24h: f64,
+    pub price_change_24h_bps: i64,
     pub total_staked: u64,
     pub total_airdropped: u64,
 }
 
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum TransactionKind {
+    Mint,
+    Burn,
+    Transfer,
+    Airdrop,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Transaction {
+    pub id: u64,
+    pub kind: TransactionKind,
+    pub from: Option<Principal>,
+    pub to: Option<Principal>,
+    pub amount: u64,
+    pub fee: Option<u64>,
+    pub memo: Option<Vec<u8>>,
+    pub created_at_time: Option<u64>,
+    pub timestamp: u64,
+}
+
 #[derive(CandidType, Deserialize, Clone, Debug)]
 pub struct TransferArgs {
     pub from: Principal,
     pub to: Principal,
     pub amount: u64,
+    pub fee: Option<u64>,
     pub memo: Option<Vec<u8>>,
+    pub created_at_time: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum TransferError {
+    BadFee { expected_fee: u64 },
+    InsufficientFunds { balance: u64 },
+    TooOld,
+    CreatedInFuture { ledger_time: u64 },
+    Duplicate { duplicate_of: u64 },
+    Unauthorized,
+    ComplianceRejected { reason: ComplianceRejection },
+    Paused { subsystem: Subsystem },
+    Frozen { asset: AssetClass },
+    GenericError { message: String },
+}
+
+impl std::fmt::Display for TransferError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TransferError::BadFee { expected_fee } => write!(f, "Bad fee, expected {}", expected_fee),
+            TransferError::InsufficientFunds { balance } => write!(f, "Insufficient balance ({})", balance),
+            TransferError::TooOld => write!(f, "Transaction too old"),
+            TransferError::CreatedInFuture { .. } => write!(f, "Transaction created in the future"),
+            TransferError::Duplicate { duplicate_of } => write!(f, "Duplicate of transaction {}", duplicate_of),
+            TransferError::Unauthorized => write!(f, "Not authorized"),
+            TransferError::ComplianceRejected { reason } => write!(f, "{}", reason),
+            TransferError::Paused { subsystem } => write!(f, "{}", Paused { subsystem: *subsystem }),
+            TransferError::Frozen { asset } => write!(f, "{:?} holdings are frozen", asset),
+            TransferError::GenericError { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum TokenError {
+    NotInitialized,
+    ZeroAmount,
+    InsufficientBalance { required: u64, available: u64 },
+    // `available` is the headroom left under the maximum supply
+    ExceedsMaxSupply { requested: u64, available: u64 },
+    Unauthorized,
+    Paused { subsystem: Subsystem },
+    Frozen { asset: AssetClass },
+    DuplicateRecipient { recipient: Principal },
+    NotPriceFeed,
+}
+
+impl std::fmt::Display for TokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TokenError::NotInitialized => write!(f, "Token not initialized"),
+            TokenError::ZeroAmount => write!(f, "Amount must be greater than zero"),
+            TokenError::InsufficientBalance { required, available } => {
+                write!(f, "Insufficient balance: {} required, {} available", required, available)
+            }
+            TokenError::ExceedsMaxSupply { requested, available } => {
+                write!(f, "Exceeds maximum supply: {} requested, {} available", requested, available)
+            }
+            TokenError::Unauthorized => write!(f, "Not authorized"),
+            TokenError::Paused { subsystem } => write!(f, "{}", Paused { subsystem: *subsystem }),
+            TokenError::Frozen { asset } => write!(f, "{:?} holdings are frozen", asset),
+            TokenError::DuplicateRecipient { recipient } => write!(f, "Duplicate recipient {}", recipient),
+            TokenError::NotPriceFeed => write!(f, "Caller is not a registered price feed"),
+        }
+    }
 }
 
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum StakingError {
+    InsufficientBalance { required: u64, available: u64 },
+    // Durations in nanoseconds
+    DurationTooShort { minimum: u64, requested: u64 },
+    DurationTooLong { maximum: u64, requested: u64 },
+    NothingStaked,
+    StillLocked { unlocks_at: u64 },
+    // The unlock time or reward does not fit in 64 bits
+    Overflow,
+    Paused { subsystem: Subsystem },
+    Frozen { asset: AssetClass },
+    // Minting the staking reward failed
+    Token { error: TokenError },
+}
+
+impl std::fmt::Display for StakingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StakingError::InsufficientBalance { required, available } => {
+                write!(f, "Insufficient balance: {} required, {} available", required, available)
+            }
+            StakingError::DurationTooShort { minimum, .. } => write!(f, "Minimum staking duration is {} ns", minimum),
+            StakingError::DurationTooLong { maximum, .. } => write!(f, "Maximum staking duration is {} ns", maximum),
+            StakingError::NothingStaked => write!(f, "No staked balance"),
+            StakingError::StillLocked { unlocks_at } => write!(f, "Stake is locked until {}", unlocks_at),
+            StakingError::Overflow => write!(f, "Staking amounts overflow"),
+            StakingError::Paused { subsystem } => write!(f, "{}", Paused { subsystem: *subsystem }),
+            StakingError::Frozen { asset } => write!(f, "{:?} holdings are frozen", asset),
+            StakingError::Token { error } => write!(f, "{}", error),
+        }
+    }
+}
+
+impl From<Paused> for TokenError {
+    fn from(paused: Paused) -> Self {
+        TokenError::Paused { subsystem: paused.subsystem }
+    }
+}
+
+impl From<Frozen> for TokenError {
+    fn from(frozen: Frozen) -> Self {
+        TokenError::Frozen { asset: frozen.asset }
+    }
+}
+
+impl From<Paused> for StakingError {
+    fn from(paused: Paused) -> Self {
+        StakingError::Paused { subsystem: paused.subsystem }
+    }
+}
+
+impl From<Frozen> for StakingError {
+    fn from(frozen: Frozen) -> Self {
+        StakingError::Frozen { asset: frozen.asset }
+    }
+}
+
+impl From<TokenError> for StakingError {
+    fn from(error: TokenError) -> Self {
+        StakingError::Token { error }
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct FeeConfig {
+    pub transfer_fee: u64,
+    // Fees are burned when no collector is set
+    pub fee_collector: Option<Principal>,
+}
+
+// (created_at_time, memo, from, to, amount)
+type DedupKey = (u64, Option<Vec<u8>>, Principal, Principal, u64);
+
 thread_local! {
     static METADATA: RefCell<Option<TokenMetadata>> = RefCell::new(None);
     static BALANCES: RefCell<HashMap<Principal, TokenHolder>> = RefCell::new(HashMap::new());
+    static TRANSACTIONS: RefCell<Vec<Transaction>> = const { RefCell::new(Vec::new()) };
+    static FEE_CONFIG: RefCell<FeeConfig> = const { RefCell::new(FeeConfig {
+        transfer_fee: 0,
+        fee_collector: None,
+    }) };
+    static STAKE_APR: RefCell<u64> = const { RefCell::new(DEFAULT_STAKE_APR) };
+    static RECENT_TRANSFERS: RefCell<HashMap<DedupKey, u64>> = RefCell::new(HashMap::new());
     static STATS: RefCell<TokenStats> = RefCell::new(TokenStats {
         total_transactions: 0,
         unique_holders: 0,
         market_cap: 0,
         volume_24h: 0,
-        price_change_24h: 0.0,
+        price_change_24h_bps: 0,
         total_staked: 0,
         total_airdropped: 0,
     });
@@ -92,162 +271,458 @@ impl RETToken {
 
             // Initialize owner balance
             BALANCES.with(|balances| {
-                balances.borrow_mut().insert(owner, TokenHolder {
-                    balance: INITIAL_SUPPLY - AIRDROP_ALLOCATION,
-                    allowances: HashMap::new(),
-                    staked_balance: 0,
-                    last_stake_time: None,
-                    stake_duration: None,
+                Self::update_holder(&mut balances.borrow_mut(), owner, |holder| {
+                    holder.balance += INITIAL_SUPPLY - AIRDROP_ALLOCATION;
                 });
             });
 
+            Self::record_transaction(TransactionKind::Mint, None, Some(owner), INITIAL_SUPPLY - AIRDROP_ALLOCATION, None);
+
             true
         })
     }
 
-    pub fn transfer(args: TransferArgs) -> Result<bool, String> {
+    pub fn burn(amount: u64, memo: Option<Vec<u8>>) -> Result<u64, TokenError> {
+        let caller = ic_caller();
+        Enforcement::ensure_not_frozen(caller, AssetClass::Ret)?;
+
+        if amount == 0 {
+            return Err(TokenError::ZeroAmount);
+        }
+
+        BALANCES.with(|balances| {
+            let mut balances = balances.borrow_mut();
+            let available = balances.get(&caller).map_or(0, |holder| holder.balance);
+
+            if available < amount {
+                return Err(TokenError::InsufficientBalance { required: amount, available });
+            }
+
+            Self::update_holder(&mut balances, caller, |holder| holder.balance -= amount);
+            Ok(())
+        })?;
+
+        METADATA.with(|metadata| {
+            if let Some(metadata) = metadata.borrow_mut().as_mut() {
+                metadata.total_supply -= amount;
+                metadata.circulating_supply -= amount;
+            }
+        });
+
+        Ok(Self::record_transaction(TransactionKind::Burn, Some(caller), None, amount, memo))
+    }
+
+    // Issues new tokens without checking the caller; used for council-approved mints and staking rewards
+    pub(crate) fn mint_internal(to: Principal, amount: u64, memo: Option<Vec<u8>>) -> Result<u64, TokenError> {
+        if amount == 0 {
+            return Err(TokenError::ZeroAmount);
+        }
+
+        METADATA.with(|metadata| {
+            let mut metadata = metadata.borrow_mut();
+            let metadata = metadata.as_mut().ok_or(TokenError::NotInitialized)?;
+
+            let available = MAX_SUPPLY.saturating_sub(metadata.total_supply);
+            if amount > available {
+                return Err(TokenError::ExceedsMaxSupply { requested: amount, available });
+            }
+
+            metadata.total_supply += amount;
+            metadata.circulating_supply += amount;
+            Ok(())
+        })?;
+
+        BALANCES.with(|balances| {
+            Self::update_holder(&mut balances.borrow_mut(), to, |holder| holder.balance += amount);
+        });
+
+        Ok(Self::record_transaction(TransactionKind::Mint, None, Some(to), amount, memo))
+    }
+
+    fn record_transaction(
+        kind: TransactionKind,
+        from: Option<Principal>,
+        to: Option<Principal>,
+        amount: u64,
+        memo: Option<Vec<u8>>,
+    ) -> u64 {
+        Self::append_transaction(Transaction {
+            id: 0,
+            kind,
+            from,
+            to,
+            amount,
+            fee: None,
+            memo,
+            created_at_time: None,
+            timestamp: time(),
+        })
+    }
+
+    // Assigns the next transaction id and appends to the log
+    fn append_transaction(mut transaction: Transaction) -> u64 {
+        let id = TRANSACTIONS.with(|transactions| {
+            let mut transactions = transactions.borrow_mut();
+            let id = transactions.len() as u64;
+            transaction.id = id;
+            transactions.push(transaction);
+            id
+        });
+
+        STATS.with(|stats| {
+            stats.borrow_mut().total_transactions += 1;
+        });
+
+        Self::checkpoint_supply();
+        RETStats::maybe_snapshot();
+
+        id
+    }
+
+    fn checkpoint_supply() {
+        let total_supply = Self::get_metadata().map(|metadata| metadata.total_supply).unwrap_or(0);
+        let total_staked = STATS.with(|stats| stats.borrow().total_staked);
+        Checkpoints::write_supply(total_supply, total_staked);
+    }
+
+    fn new_holder() -> TokenHolder {
+        TokenHolder {
+            balance: 0,
+            allowances: HashMap::new(),
+            staked_balance: 0,
+            last_stake_time: None,
+            stake_duration: None,
+        }
+    }
+
+    // Every balance mutation goes through here so holder statistics stay in sync
+    fn update_holder<R>(
+        balances: &mut HashMap<Principal, TokenHolder>,
+        account: Principal,
+        update: impl FnOnce(&mut TokenHolder) -> R,
+    ) -> R {
+        let holder = balances.entry(account).or_insert_with(Self::new_holder);
+        let before = holder.balance + holder.staked_balance;
+        let result = update(holder);
+        let after = holder.balance + holder.staked_balance;
+        Checkpoints::write_account(account, holder.balance, holder.staked_balance);
+
+        STATS.with(|stats| {
+            let mut stats = stats.borrow_mut();
+            if before == 0 && after > 0 {
+                stats.unique_holders += 1;
+            } else if before > 0 && after == 0 {
+                stats.unique_holders -= 1;
+            }
+        });
+
+        result
+    }
+
+    pub fn get_fee_config() -> FeeConfig {
+        FEE_CONFIG.with(|config| config.borrow().clone())
+    }
+
+    // Changed through the admin council only
+    pub(crate) fn set_fee_config(transfer_fee: u64, fee_collector: Option<Principal>) -> Result<(), String> {
+        Self::get_metadata().ok_or("Token not initialized")?;
+
+        FEE_CONFIG.with(|config| {
+            *config.borrow_mut() = FeeConfig {
+                transfer_fee,
+                fee_collector,
+            };
+        });
+
+        Ok(())
+    }
+
+    pub fn stake_apr() -> u64 {
+        STAKE_APR.with(|apr| *apr.borrow())
+    }
+
+    // Changed through governance only
+    pub(crate) fn set_stake_apr(percentage: u64) {
+        STAKE_APR.with(|apr| *apr.borrow_mut() = percentage);
+    }
+
+    pub fn transfer(args: TransferArgs) -> Result<u64, TransferError> {
         let caller = ic_caller();
         
         if args.from != caller {
-            return Err("Not authorized".to_string());
+            return Err(TransferError::Unauthorized);
+        }
+
+        Self::execute_transfer(args)
+    }
+
+    /// Moves funds out of an account the canister controls, e.g. the governance treasury.
+    pub(crate) fn transfer_internal(from: Principal, to: Principal, amount: u64, memo: Option<Vec<u8>>) -> Result<u64, TransferError> {
+        Self::execute_transfer(TransferArgs {
+            from,
+            to,
+            amount,
+            fee: None,
+            memo,
+            created_at_time: None,
+        })
+    }
+
+    fn execute_transfer(args: TransferArgs) -> Result<u64, TransferError> {
+        CircuitBreaker::ensure_active(Subsystem::RetTransfers)
+            .map_err(|paused| TransferError::Paused { subsystem: paused.subsystem })?;
+        Compliance::check(ComplianceAction::RetTransfer, &[args.from, args.to])
+            .map_err(|reason| TransferError::ComplianceRejected { reason })?;
+        Enforcement::ensure_not_frozen(args.from, AssetClass::Ret)
+            .map_err(|frozen| TransferError::Frozen { asset: frozen.asset })?;
+
+        let fee_config = Self::get_fee_config();
+        let fee = fee_config.transfer_fee;
+        if args.fee.is_some_and(|requested| requested != fee) {
+            return Err(TransferError::BadFee { expected_fee: fee });
+        }
+
+        if args.memo.as_ref().is_some_and(|memo| memo.len() > MAX_MEMO_LENGTH) {
+            return Err(TransferError::GenericError {
+                message: format!("Memo exceeds {} bytes", MAX_MEMO_LENGTH),
+            });
         }
 
+        // Transfers that carry created_at_time are deduplicated within the window
+        let now = time();
+        let dedup_key = match args.created_at_time {
+            Some(created_at_time) => {
+                if created_at_time.saturating_add(TX_WINDOW + PERMITTED_DRIFT) < now {
+                    return Err(TransferError::TooOld);
+                }
+                if created_at_time > now + PERMITTED_DRIFT {
+                    return Err(TransferError::CreatedInFuture { ledger_time: now });
+                }
+
+                let key: DedupKey = (created_at_time, args.memo.clone(), args.from, args.to, args.amount);
+                let duplicate_of = RECENT_TRANSFERS.with(|recent| {
+                    let mut recent = recent.borrow_mut();
+                    recent.retain(|(created_at_time, ..), _| created_at_time.saturating_add(TX_WINDOW + PERMITTED_DRIFT) >= now);
+                    recent.get(&key).copied()
+                });
+                if let Some(duplicate_of) = duplicate_of {
+                    return Err(TransferError::Duplicate { duplicate_of });
+                }
+
+                Some(key)
+            }
+            None => None,
+        };
+
         BALANCES.with(|balances| {
             let mut balances = balances.borrow_mut();
-            let from_holder = balances.get(&args.from)
-                .ok_or_else(|| "Sender has no balance".to_string())?;
+            let balance = balances.get(&args.from)
+                .map(|holder| holder.balance)
+                .unwrap_or(0);
             
-            if from_holder.balance < args.amount {
-                return Err("Insufficient balance".to_string());
+            let debit = args.amount.checked_add(fee).ok_or(TransferError::InsufficientFunds { balance })?;
+            if balance < debit {
+                return Err(TransferError::InsufficientFunds { balance });
             }
 
-            // Update sender balance
-            let mut new_from_holder = from_holder.clone();
-            new_from_holder.balance -= args.amount;
-            balances.insert(args.from, new_from_holder);
-            
-            // Update recipient balance
-            let mut to_holder = balances.get(&args.to)
-                .cloned()
-                .unwrap_or_else(|| TokenHolder {
-                    balance: 0,
-                    allowances: HashMap::new(),
-                    staked_balance: 0,
-                    last_stake_time: None,
-                    stake_duration: None,
-                });
-            
-            to_holder.balance += args.amount;
-            balances.insert(args.to, to_holder);
-            
-            // Update stats
-            STATS.with(|stats| {
-                let mut stats = stats.borrow_mut();
-                stats.total_transactions += 1;
-                stats.volume_24h += args.amount;
+            // Update sender and recipient balances
+            Self::update_holder(&mut balances, args.from, |holder| holder.balance -= debit);
+            Self::update_holder(&mut balances, args.to, |holder| holder.balance += args.amount);
+
+            // Route the fee to the collector, if any
+            if let (Some(collector), true) = (fee_config.fee_collector, fee > 0) {
+                Self::update_holder(&mut balances, collector, |holder| holder.balance += fee);
+            }
+
+            Ok(())
+        })?;
+
+        RETStats::record_volume(args.amount);
+
+        // Without a collector the fee leaves the supply
+        if fee > 0 && fee_config.fee_collector.is_none() {
+            METADATA.with(|metadata| {
+                if let Some(metadata) = metadata.borrow_mut().as_mut() {
+                    metadata.total_supply -= fee;
+                    metadata.circulating_supply -= fee;
+                }
             });
+        }
 
-            Ok(true)
-        })
+        let tx_id = Self::append_transaction(Transaction {
+            id: 0,
+            kind: TransactionKind::Transfer,
+            from: Some(args.from),
+            to: Some(args.to),
+            amount: args.amount,
+            fee: Some(fee),
+            memo: args.memo,
+            created_at_time: args.created_at_time,
+            timestamp: now,
+        });
+
+        if let Some(key) = dedup_key {
+            RECENT_TRANSFERS.with(|recent| {
+                recent.borrow_mut().insert(key, tx_id);
+            });
+        }
+
+        Ok(tx_id)
     }
 
-    pub fn stake(amount: u64, duration: u64) -> Result<bool, String> {
+    /// Moves liquid RET with no fee and no caller, pause, compliance or freeze checks.
+    /// Only reachable through an approved admin council action.
+    pub(crate) fn force_transfer(from: Principal, to: Principal, amount: u64, memo: Option<Vec<u8>>) -> Result<u64, String> {
+        if amount == 0 {
+            return Err("Transfer amount must be greater than zero".to_string());
+        }
+
+        BALANCES.with(|balances| {
+            let mut balances = balances.borrow_mut();
+            let balance = balances.get(&from)
+                .map(|holder| holder.balance)
+                .unwrap_or(0);
+
+            if balance < amount {
+                return Err("Insufficient balance".to_string());
+            }
+
+            Self::update_holder(&mut balances, from, |holder| holder.balance -= amount);
+            Self::update_holder(&mut balances, to, |holder| holder.balance += amount);
+            Ok(())
+        })?;
+
+        Ok(Self::record_transaction(TransactionKind::Transfer, Some(from), Some(to), amount, memo))
+    }
+
+    pub fn stake(amount: u64, duration: u64) -> Result<bool, StakingError> {
+        CircuitBreaker::ensure_active(Subsystem::Staking)?;
         let caller = ic_caller();
+        Enforcement::ensure_not_frozen(caller, AssetClass::Ret)?;
         
         if duration < MIN_STAKE_DURATION {
-            return Err("Minimum staking duration is 30 days".to_string());
+            return Err(StakingError::DurationTooShort { minimum: MIN_STAKE_DURATION, requested: duration });
+        }
+        if duration > MAX_STAKE_DURATION {
+            return Err(StakingError::DurationTooLong { maximum: MAX_STAKE_DURATION, requested: duration });
         }
 
         BALANCES.with(|balances| {
             let mut balances = balances.borrow_mut();
-            let holder = balances.get_mut(&caller)
-                .ok_or("No balance found")?;
+            let available = balances.get(&caller).map_or(0, |holder| holder.balance);
 
-            if holder.balance < amount {
-                return Err("Insufficient balance".to_string());
+            if available < amount {
+                return Err(StakingError::InsufficientBalance { required: amount, available });
             }
 
-            holder.balance -= amount;
-            holder.staked_balance += amount;
-            holder.last_stake_time = Some(time());
-            holder.stake_duration = Some(duration);
+            Self::update_holder(&mut balances, caller, |holder| {
+                holder.balance -= amount;
+                holder.staked_balance += amount;
+                holder.last_stake_time = Some(time());
+                holder.stake_duration = Some(duration);
+            });
 
             STATS.with(|stats| {
                 let mut stats = stats.borrow_mut();
                 stats.total_staked += amount;
             });
 
-            Ok(true)
-        })
+            Ok(())
+        })?;
+
+        Self::checkpoint_supply();
+
+        Ok(true)
     }
 
-    pub fn unstake() -> Result<u64, String> {
+    pub fn unstake() -> Result<u64, StakingError> {
+        CircuitBreaker::ensure_active(Subsystem::Staking)?;
         let caller = ic_caller();
+        Enforcement::ensure_not_frozen(caller, AssetClass::Ret)?;
         
-        BALANCES.with(|balances| {
+        let (staked, reward) = BALANCES.with(|balances| {
             let mut balances = balances.borrow_mut();
-            let holder = balances.get_mut(&caller)
-                .ok_or("No balance found")?;
+            let holder = balances.get(&caller)
+                .filter(|holder| holder.staked_balance > 0)
+                .ok_or(StakingError::NothingStaked)?;
 
-            if holder.staked_balance == 0 {
-                return Err("No staked balance".to_string());
-            }
-
-            let stake_time = holder.last_stake_time
-                .ok_or("No stake timestamp found")?;
-            let duration = holder.stake_duration
-                .ok_or("No stake duration found")?;
+            let stake_time = holder.last_stake_time.ok_or(StakingError::NothingStaked)?;
+            let duration = holder.stake_duration.ok_or(StakingError::NothingStaked)?;
 
-            let current_time = time();
-            if current_time < stake_time + duration {
-                return Err("Stake duration not met".to_string());
+            let unlocks_at = stake_time.checked_add(duration).ok_or(StakingError::Overflow)?;
+            if time() < unlocks_at {
+                return Err(StakingError::StillLocked { unlocks_at });
             }
 
             // Calculate rewards
-            let stake_duration_days = (duration / (24 * 60 * 60 * 1_000_000_000)) as u64;
-            let reward = (holder.staked_balance * STAKE_APR * stake_duration_days) / (365 * 100);
-
-            // Return staked amount plus rewards
-            let total_return = holder.staked_balance + reward;
-            holder.balance += total_return;
-            holder.staked_balance = 0;
-            holder.last_stake_time = None;
-            holder.stake_duration = None;
+            let stake_duration_days = (duration / (24 * 60 * 60 * 1_000_000_000)) as u128;
+            let reward = (holder.staked_balance as u128)
+                .checked_mul(Self::stake_apr() as u128)
+                .and_then(|value| value.checked_mul(stake_duration_days))
+                .map(|value| value / (365 * 100))
+                .and_then(|reward| u64::try_from(reward).ok())
+                .ok_or(StakingError::Overflow)?;
+
+            // Return staked amount; rewards are minted separately below
+            let staked = holder.staked_balance;
+            Self::update_holder(&mut balances, caller, |holder| {
+                holder.balance += staked;
+                holder.staked_balance = 0;
+                holder.last_stake_time = None;
+                holder.stake_duration = None;
+            });
 
             STATS.with(|stats| {
                 let mut stats = stats.borrow_mut();
-                stats.total_staked -= holder.staked_balance;
+                stats.total_staked -= staked;
             });
 
-            Ok(total_return)
-        })
+            Ok((staked, reward))
+        })?;
+
+        Self::checkpoint_supply();
+
+        // Rewards are new issuance, so they are capped by the remaining supply headroom
+        let headroom = Self::get_metadata()
+            .map(|metadata| MAX_SUPPLY.saturating_sub(metadata.total_supply))
+            .unwrap_or(0);
+        let reward = reward.min(headroom);
+        if reward > 0 {
+            Self::mint_internal(caller, reward, Some(b"staking reward".to_vec()))?;
+        }
+
+        Ok(staked + reward)
     }
 
-    pub fn airdrop(recipients: Vec<(Principal, u64)>) -> Result<bool, String> {
-        let total_amount: u64 = recipients.iter().map(|(_, amount)| amount).sum();
-        
+    /// Unallocated part of the airdrop allocation.
+    pub fn airdrop_pool_remaining() -> u64 {
+        STATS.with(|stats| AIRDROP_ALLOCATION - stats.borrow().total_airdropped)
+    }
+
+    /// Credits `amount` from the airdrop allocation to `recipient`. Authorization and
+    /// per-campaign budgets are enforced by the caller.
+    pub(crate) fn credit_airdrop(recipient: Principal, amount: u64, memo: Option<Vec<u8>>) -> Result<u64, TokenError> {
+        let available = Self::airdrop_pool_remaining();
+        if amount > available {
+            return Err(TokenError::InsufficientBalance { required: amount, available });
+        }
+
+        BALANCES.with(|balances| {
+            Self::update_holder(&mut balances.borrow_mut(), recipient, |holder| holder.balance += amount);
+        });
+
+        METADATA.with(|metadata| {
+            if let Some(metadata) = metadata.borrow_mut().as_mut() {
+                metadata.circulating_supply += amount;
+            }
+        });
+
         STATS.with(|stats| {
             let mut stats = stats.borrow_mut();
-            if stats.total_airdropped + total_amount > AIRDROP_ALLOCATION {
-                return Err("Exceeds airdrop allocation".to_string());
-            }
-            stats.total_airdropped += total_amount;
-            
-            for (recipient, amount) in recipients {
-                BALANCES.with(|balances| {
-                    let mut balances = balances.borrow_mut();
-                    let holder = balances.entry(recipient).or_insert(TokenHolder {
-                        balance: 0,
-                        allowances: HashMap::new(),
-                        staked_balance: 0,
-                        last_stake_time: None,
-                        stake_duration: None,
-                    });
-                    holder.balance += amount;
-                });
-            }
-            Ok(true)
-        })
+            stats.total_airdropped += amount;
+        });
+
+        Ok(Self::record_transaction(TransactionKind::Airdrop, None, Some(recipient), amount, memo))
     }
 
     pub fn balance_of(owner: Principal) -> u64 {
@@ -273,6 +748,25 @@ impl RETToken {
     }
 
     pub fn get_stats() -> TokenStats {
-        STATS.with(|stats| stats.borrow().clone())
+        let mut stats = STATS.with(|stats| stats.borrow().clone());
+        let circulating_supply = Self::get_metadata()
+            .map(|metadata| metadata.circulating_supply)
+            .unwrap_or(0);
+
+        stats.volume_24h = RETStats::volume_24h();
+        stats.market_cap = RETStats::market_cap(circulating_supply);
+        stats.price_change_24h_bps = RETStats::price_change_24h_bps();
+        stats
+    }
+
+    pub fn get_transactions(start: u64, length: u64) -> Vec<Transaction> {
+        TRANSACTIONS.with(|transactions| {
+            transactions.borrow()
+                .iter()
+                .skip(start as usize)
+                .take(length as usize)
+                .cloned()
+                .collect()
+        })
     }
 } 
\ No newline at end of file
@@ -0,0 +1,269 @@
+use candid::{CandidType, Deserialize, Principal};
+use ic_cdk::api::time;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::compliance::Compliance;
+use crate::PropertyError;
+
+const MAX_LOCKUP_PERIOD: u64 = 10 * 365 * 24 * 60 * 60 * 1_000_000_000; // 10 years in nanoseconds
+const MAX_ROFR_WINDOW: u64 = 90 * 24 * 60 * 60 * 1_000_000_000; // 90 days in nanoseconds
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct TransferPolicy {
+    // Nanoseconds after issuance during which nothing can move
+    pub lockup_period: u64,
+    // Recipient jurisdictions allowed to hold; empty allows any
+    pub allowed_jurisdictions: Vec<String>,
+    pub max_holders: Option<u64>,
+    // Holding bounds in basis points of the property
+    pub min_holding: Option<u16>,
+    pub max_holding: Option<u16>,
+    // Nanoseconds existing shareholders have to match a sale to a new holder
+    pub rofr_window: Option<u64>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq)]
+pub enum PolicyViolation {
+    // The token is restricted but no policy was registered for it
+    Unregistered { token_id: u64 },
+    LockedUp { unlocks_at: u64 },
+    NoJurisdiction { holder: Principal },
+    JurisdictionNotAllowed { holder: Principal, jurisdiction: String },
+    TooManyHolders { max_holders: u64 },
+    // Holding bounds in basis points
+    BelowMinHolding { min_holding: u16 },
+    AboveMaxHolding { max_holding: u16 },
+}
+
+impl std::fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolicyViolation::Unregistered { .. } => write!(f, "Token transfers are restricted"),
+            PolicyViolation::LockedUp { unlocks_at } => write!(f, "Property is locked up until {}", unlocks_at),
+            PolicyViolation::NoJurisdiction { holder } => write!(f, "{} has no registered jurisdiction", holder),
+            PolicyViolation::JurisdictionNotAllowed { jurisdiction, .. } => {
+                write!(f, "Jurisdiction {} is not eligible for this property", jurisdiction)
+            }
+            PolicyViolation::TooManyHolders { max_holders } => {
+                write!(f, "Property cannot have more than {} holders", max_holders)
+            }
+            PolicyViolation::BelowMinHolding { min_holding } => {
+                write!(f, "Holdings must be at least {} basis points", min_holding)
+            }
+            PolicyViolation::AboveMaxHolding { max_holding } => {
+                write!(f, "Holdings cannot exceed {} basis points", max_holding)
+            }
+        }
+    }
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct PropertyPolicy {
+    pub property_id: u64,
+    pub token_id: Option<u64>,
+    pub policy: TransferPolicy,
+    pub issued_at: u64,
+}
+
+thread_local! {
+    static POLICIES: RefCell<HashMap<u64, PropertyPolicy>> = RefCell::new(HashMap::new());
+    // ICRC-7 token id to property id, for checks that start from a token transfer
+    static TOKEN_PROPERTIES: RefCell<HashMap<u64, u64>> = RefCell::new(HashMap::new());
+}
+
+/// Per-property transfer restrictions, enforced on ICRC-7 transfers of restricted
+/// tokens and on every movement of fractional shares.
+pub struct TransferPolicies;
+
+impl TransferPolicies {
+    /// Binds the policy set for a property to its newly minted token; the lock-up runs from here.
+    /// Returns false when the property has no policy, leaving the token unrestricted.
+    pub(crate) fn register_issuance(property_id: u64, token_id: u64) -> bool {
+        let registered = POLICIES.with(|policies| {
+            policies.borrow_mut().get_mut(&property_id).map(|record| {
+                record.token_id = Some(token_id);
+                record.issued_at = time();
+            })
+        });
+        if registered.is_none() {
+            return false;
+        }
+
+        TOKEN_PROPERTIES.with(|tokens| {
+            tokens.borrow_mut().insert(token_id, property_id);
+        });
+        true
+    }
+
+    /// Replaces the policy while keeping the original issuance time. Once a policy has been
+    /// registered at issuance, only `can_loosen` callers may relax any of its restrictions.
+    pub(crate) fn set_policy(property_id: u64, policy: TransferPolicy, can_loosen: bool) -> Result<(), PropertyError> {
+        let policy = Self::validate(policy).map_err(|reason| PropertyError::InvalidInput { reason })?;
+
+        let issued = Self::get_policy(property_id).filter(|record| record.token_id.is_some());
+        if !can_loosen && issued.is_some_and(|record| Self::loosens(&record.policy, &policy)) {
+            return Err(PropertyError::Unauthorized);
+        }
+
+        POLICIES.with(|policies| {
+            policies.borrow_mut()
+                .entry(property_id)
+                .and_modify(|existing| existing.policy = policy.clone())
+                .or_insert(PropertyPolicy {
+                    property_id,
+                    token_id: None,
+                    policy,
+                    issued_at: time(),
+                });
+        });
+
+        Ok(())
+    }
+
+    pub fn get_policy(property_id: u64) -> Option<PropertyPolicy> {
+        POLICIES.with(|policies| policies.borrow().get(&property_id).cloned())
+    }
+
+    pub fn rofr_window(property_id: u64) -> Option<u64> {
+        Self::get_policy(property_id).and_then(|record| record.policy.rofr_window)
+    }
+
+    /// Checks a whole-token ICRC-7 transfer. Returns Err when the token is restricted
+    /// but no policy was registered for it.
+    pub fn check_token_transfer(token_id: u64, to: Principal) -> Result<(), PolicyViolation> {
+        let record = TOKEN_PROPERTIES.with(|tokens| tokens.borrow().get(&token_id).copied())
+            .and_then(Self::get_policy)
+            .ok_or(PolicyViolation::Unregistered { token_id })?;
+
+        Self::check_lockup(&record)?;
+        Self::check_jurisdiction(&record.policy, to)
+    }
+
+    /// Checks an initial allocation of shares (in basis points) against the policy.
+    pub fn check_allocation(property_id: u64, holdings: &HashMap<Principal, u64>) -> Result<(), PolicyViolation> {
+        let record = match Self::get_policy(property_id) {
+            Some(record) => record,
+            None => return Ok(()),
+        };
+
+        for holder in holdings.keys() {
+            Self::check_jurisdiction(&record.policy, *holder)?;
+        }
+        Self::check_holdings(&record.policy, holdings)
+    }
+
+    /// Checks moving `amount` basis points from `from` to `to`, given holdings before the move.
+    pub fn check_share_transfer(
+        property_id: u64,
+        holdings: &HashMap<Principal, u64>,
+        from: Principal,
+        to: Principal,
+        amount: u64,
+    ) -> Result<(), PolicyViolation> {
+        let record = match Self::get_policy(property_id) {
+            Some(record) => record,
+            None => return Ok(()),
+        };
+
+        Self::check_lockup(&record)?;
+        Self::check_jurisdiction(&record.policy, to)?;
+
+        let mut after = holdings.clone();
+        if let Some(balance) = after.get_mut(&from) {
+            *balance -= amount;
+        }
+        after.retain(|_, balance| *balance > 0);
+        *after.entry(to).or_default() += amount;
+
+        Self::check_holdings(&record.policy, &after)
+    }
+
+    fn check_lockup(record: &PropertyPolicy) -> Result<(), PolicyViolation> {
+        let unlocks_at = record.issued_at.saturating_add(record.policy.lockup_period);
+        if time() < unlocks_at {
+            return Err(PolicyViolation::LockedUp { unlocks_at });
+        }
+        Ok(())
+    }
+
+    fn check_jurisdiction(policy: &TransferPolicy, holder: Principal) -> Result<(), PolicyViolation> {
+        if policy.allowed_jurisdictions.is_empty() {
+            return Ok(());
+        }
+
+        let jurisdiction = Compliance::get_investor(holder)
+            .map(|record| record.jurisdiction)
+            .ok_or(PolicyViolation::NoJurisdiction { holder })?;
+        if !policy.allowed_jurisdictions.contains(&jurisdiction) {
+            return Err(PolicyViolation::JurisdictionNotAllowed { holder, jurisdiction });
+        }
+        Ok(())
+    }
+
+    fn check_holdings(policy: &TransferPolicy, holdings: &HashMap<Principal, u64>) -> Result<(), PolicyViolation> {
+        if let Some(max_holders) = policy.max_holders {
+            if holdings.len() as u64 > max_holders {
+                return Err(PolicyViolation::TooManyHolders { max_holders });
+            }
+        }
+        for balance in holdings.values() {
+            if let Some(min_holding) = policy.min_holding {
+                if *balance < min_holding as u64 {
+                    return Err(PolicyViolation::BelowMinHolding { min_holding });
+                }
+            }
+            if let Some(max_holding) = policy.max_holding {
+                if *balance > max_holding as u64 {
+                    return Err(PolicyViolation::AboveMaxHolding { max_holding });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Whether `new` allows anything `current` forbids
+    fn loosens(current: &TransferPolicy, new: &TransferPolicy) -> bool {
+        let raised = |current: Option<u64>, new: Option<u64>| {
+            current.is_some_and(|current| new.is_none_or(|new| new > current))
+        };
+        let lowered = |current: Option<u64>, new: Option<u64>| {
+            current.is_some_and(|current| new.is_none_or(|new| new < current))
+        };
+
+        new.lockup_period < current.lockup_period
+            || (!current.allowed_jurisdictions.is_empty()
+                && (new.allowed_jurisdictions.is_empty()
+                    || new.allowed_jurisdictions.iter().any(|code| !current.allowed_jurisdictions.contains(code))))
+            || raised(current.max_holders, new.max_holders)
+            || lowered(current.min_holding.map(u64::from), new.min_holding.map(u64::from))
+            || raised(current.max_holding.map(u64::from), new.max_holding.map(u64::from))
+            || lowered(current.rofr_window, new.rofr_window)
+    }
+
+    /// Checks a policy is coherent and normalizes its jurisdiction codes.
+    pub(crate) fn validate(mut policy: TransferPolicy) -> Result<TransferPolicy, String> {
+        if policy.lockup_period > MAX_LOCKUP_PERIOD {
+            return Err(format!("Lock-up period cannot exceed {} ns", MAX_LOCKUP_PERIOD));
+        }
+        if policy.rofr_window.is_some_and(|window| window > MAX_ROFR_WINDOW) {
+            return Err(format!("ROFR window cannot exceed {} ns", MAX_ROFR_WINDOW));
+        }
+        if policy.max_holders == Some(0) {
+            return Err("Maximum holders must be at least one".to_string());
+        }
+        if let (Some(min), Some(max)) = (policy.min_holding, policy.max_holding) {
+            if min > max {
+                return Err("Minimum holding cannot exceed maximum holding".to_string());
+            }
+        }
+        if policy.max_holding.is_some_and(|max| max > 10_000) {
+            return Err("Maximum holding cannot exceed 10000 basis points".to_string());
+        }
+
+        policy.allowed_jurisdictions = policy.allowed_jurisdictions.iter()
+            .map(|code| code.trim().to_uppercase())
+            .collect();
+        Ok(policy)
+    }
+}
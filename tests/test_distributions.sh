@@ -57,9 +57,23 @@ check_success "Switching to property owner identity"
 # List and tokenize property
 echo -e "\n4. Creating property token..."
 dfx canister call test_ireits_backend list_property \
-  "(500000.0, \"456 Oak St\", \"Rental Property\", opt record { monthly_amount = 5000:nat64; last_distribution = 0:nat64; distribution_frequency = 2592000:nat64 })"
+  "(record { units = 50000000:nat64; currency = variant { USD }; decimals = 2:nat8 }, \"456 Oak St\", \"Rental Property\", opt record { monthly_amount = record { units = 500000:nat64; currency = variant { USD }; decimals = 2:nat8 }; last_distribution = 0:nat64; distribution_frequency = 2592000:nat64 })"
 check_success "Property listing"
 
+dfx canister call test_ireits_backend add_document "(1:nat64, variant { Deed }, \"QmDeedHash\")"
+dfx canister call test_ireits_backend add_document "(1:nat64, variant { Title }, \"QmTitleHash\")"
+dfx canister call test_ireits_backend add_document "(1:nat64, variant { Inspection }, \"QmInspectionHash\")"
+dfx canister call test_ireits_backend transition_property "(1:nat64, variant { PendingVerification }, null)"
+check_success "Submitting property for verification"
+dfx identity use default
+dfx canister call test_ireits_backend transition_property "(1:nat64, variant { Verified }, null)"
+check_success "Property verification"
+dfx canister call test_ireits_backend attest_document "(1:nat64, 1:nat64, variant { Approved }, null)"
+dfx canister call test_ireits_backend attest_document "(1:nat64, 2:nat64, variant { Approved }, null)"
+dfx canister call test_ireits_backend attest_document "(1:nat64, 3:nat64, variant { Approved }, null)"
+check_success "Document attestations"
+dfx identity use property_owner
+
 dfx canister call test_ireits_backend tokenize_property \
   "(1:nat64, \"Oak St Property\", \"OAK\", opt \"Rental Property with Monthly Income\", 10000:nat64, 50:nat64, opt (250:nat16))"
 check_success "Property tokenization"
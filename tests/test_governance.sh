@@ -0,0 +1,123 @@
+#!/bin/bash
+
+# Function to check command success
+check_success() {
+    if [ $? -eq 0 ]; then
+        echo "✅ Success: $1"
+    else
+        echo "❌ Failed: $1"
+        exit 1
+    fi
+}
+
+# Function to check that a canister call returned the expected result variant
+check_result() {
+    if echo "$OUTPUT" | grep -q "variant { $1"; then
+        echo "✅ Success: $2"
+    else
+        echo "❌ Failed: $2 (expected $1)"
+        exit 1
+    fi
+}
+
+# Start local replica if not running
+dfx start --background --clean
+check_success "Starting local replica"
+
+# Deploy the canister
+dfx deploy
+check_success "Deploying canister"
+
+# Store identity principal
+PRINCIPAL=$(dfx identity get-principal)
+echo "Using principal: $PRINCIPAL"
+
+# Initialize RET token
+echo -e "\n1. Initializing RET token..."
+OUTPUT=$(dfx canister call test_ireits_backend initialize_ret \
+  "(principal \"$PRINCIPAL\", opt \"https://ireit.com\", null)")
+echo "$OUTPUT"
+check_result Ok "RET token initialization"
+
+# Stake RET to obtain voting power
+echo -e "\n2. Staking tokens for voting power..."
+OUTPUT=$(dfx canister call test_ireits_backend stake \
+  "(100_000:nat64, 2_592_000_000_000_000:nat64)")
+echo "$OUTPUT"
+check_result Ok "Token staking"
+
+# Submit a parameter change proposal
+echo -e "\n3. Submitting listing fee proposal..."
+OUTPUT=$(dfx canister call test_ireits_backend submit_proposal \
+  "(\"Lower listing fee\", \"Reduce the marketplace listing fee to 0.5%\", variant { SetListingFee = record { basis_points = 50:nat64 } })")
+echo "$OUTPUT"
+check_result Ok "Proposal submission"
+
+# Vote on the proposal
+echo -e "\n4. Voting on proposal..."
+OUTPUT=$(dfx canister call test_ireits_backend vote_on_proposal "(1:nat64, variant { For })")
+echo "$OUTPUT"
+check_result Ok "Proposal vote"
+
+# Inspect proposal and vote
+echo -e "\n5. Verifying proposal state..."
+dfx canister call test_ireits_backend get_proposal "(1:nat64)"
+check_success "Proposal retrieval"
+dfx canister call test_ireits_backend get_proposal_vote "(1:nat64, principal \"$PRINCIPAL\")"
+check_success "Vote retrieval"
+
+# Finalizing before the voting period ends is rejected
+echo -e "\n6. Attempting early finalization..."
+OUTPUT=$(dfx canister call test_ireits_backend finalize_proposal "(1:nat64)")
+echo "$OUTPUT"
+check_result Err "Early finalization rejected"
+
+# Governance configuration
+echo -e "\n7. Getting governance config..."
+dfx canister call test_ireits_backend get_governance_config
+check_success "Governance config retrieval"
+
+# Set up a fractionalized property for shareholder voting
+echo -e "\n8. Setting up fractionalized property..."
+dfx canister call test_ireits_backend initialize_collection \
+  "(\"Real Estate Properties\", \"REP\", \"Tokenized Real Estate Properties\", 250:nat16, principal \"$PRINCIPAL\", null, null, null, null)"
+dfx canister call test_ireits_backend list_property \
+  "(record { units = 50000000:nat64; currency = variant { USD }; decimals = 2:nat8 }, \"123 Main St\", \"Beautiful property\", null)"
+dfx canister call test_ireits_backend add_document "(1:nat64, variant { Deed }, \"QmDeedHash\")"
+dfx canister call test_ireits_backend add_document "(1:nat64, variant { Title }, \"QmTitleHash\")"
+dfx canister call test_ireits_backend add_document "(1:nat64, variant { Inspection }, \"QmInspectionHash\")"
+dfx canister call test_ireits_backend transition_property "(1:nat64, variant { PendingVerification }, null)"
+dfx canister call test_ireits_backend transition_property "(1:nat64, variant { Verified }, null)"
+dfx canister call test_ireits_backend attest_document "(1:nat64, 1:nat64, variant { Approved }, null)"
+dfx canister call test_ireits_backend attest_document "(1:nat64, 2:nat64, variant { Approved }, null)"
+dfx canister call test_ireits_backend attest_document "(1:nat64, 3:nat64, variant { Approved }, null)"
+dfx canister call test_ireits_backend tokenize_property \
+  "(1:nat64, \"123 Main St Token\", \"MAIN\", null, 1000:nat64, 100:nat64, null)"
+dfx canister call test_ireits_backend fractionalize_property \
+  "(1:nat64, vec { record { principal \"$PRINCIPAL\"; 10000:nat16 } })"
+check_success "Fractionalized property setup"
+
+# Shareholders vote on selling the property
+echo -e "\n9. Submitting property sale proposal..."
+dfx canister call test_ireits_backend submit_property_proposal \
+  "(1:nat64, variant { Sale = record { price = record { units = 55000000:nat64; currency = variant { USD }; decimals = 2:nat8 }; buyer = null } }, \"Accept offer from buyer\")"
+check_success "Property proposal submission"
+
+echo -e "\n10. Voting on property proposal..."
+dfx canister call test_ireits_backend vote_on_property_proposal "(1:nat64, variant { For })"
+check_success "Property proposal vote"
+dfx canister call test_ireits_backend get_property_proposals "(1:nat64)"
+check_success "Property proposals retrieval"
+
+# Delegation
+echo -e "\n11. Delegating RET voting power..."
+dfx canister call test_ireits_backend delegate_votes "(variant { Ret }, principal \"aaaaa-aa\")"
+check_success "RET delegation"
+dfx canister call test_ireits_backend get_delegate_leaderboard "(variant { Ret }, 10:nat64)"
+check_success "Delegate leaderboard retrieval"
+dfx canister call test_ireits_backend revoke_delegation "(variant { Ret })"
+check_success "Delegation revocation"
+dfx canister call test_ireits_backend get_delegation_history "(variant { Ret }, principal \"$PRINCIPAL\")"
+check_success "Delegation history retrieval"
+
+echo -e "\n✅ Governance test sequence completed successfully!"
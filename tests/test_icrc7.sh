@@ -22,11 +22,21 @@ dfx canister call test_ireits_backend get_collection_info
 # List a property
 echo -e "\n3. Listing a property..."
 dfx canister call test_ireits_backend list_property \
-  "(100000.0, \"123 Main St\", \"Beautiful property\", null)"
+  "(record { units = 10000000:nat64; currency = variant { USD }; decimals = 2:nat8 }, \"123 Main St\", \"Beautiful property\", null)"
+
+# Verify the property
+dfx canister call test_ireits_backend add_document "(1:nat64, variant { Deed }, \"QmDeedHash\")"
+dfx canister call test_ireits_backend add_document "(1:nat64, variant { Title }, \"QmTitleHash\")"
+dfx canister call test_ireits_backend add_document "(1:nat64, variant { Inspection }, \"QmInspectionHash\")"
+dfx canister call test_ireits_backend transition_property "(1:nat64, variant { PendingVerification }, null)"
+dfx canister call test_ireits_backend transition_property "(1:nat64, variant { Verified }, null)"
+dfx canister call test_ireits_backend attest_document "(1:nat64, 1:nat64, variant { Approved }, null)"
+dfx canister call test_ireits_backend attest_document "(1:nat64, 2:nat64, variant { Approved }, null)"
+dfx canister call test_ireits_backend attest_document "(1:nat64, 3:nat64, variant { Approved }, null)"
 
 # Get all properties
 echo -e "\n4. Getting all properties..."
-dfx canister call test_ireits_backend get_all_properties
+dfx canister call test_ireits_backend get_all_properties "(null, 100:nat64)"
 
 # Tokenize the property
 echo -e "\n5. Tokenizing property..."
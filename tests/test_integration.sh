@@ -49,7 +49,7 @@ check_success "ICRC7 collection initialization"
 echo -e "\n=== 3. Property Management ==="
 echo "3.1 Listing property..."
 dfx canister call test_ireits_backend list_property \
-  "(500000.0, \"123 Main St\", \"Beautiful property\", opt record { monthly_amount = 5000:nat64; last_distribution = 0:nat64; distribution_frequency = 2592000:nat64 })"
+  "(record { units = 50000000:nat64; currency = variant { USD }; decimals = 2:nat8 }, \"123 Main St\", \"Beautiful property\", opt record { monthly_amount = record { units = 500000:nat64; currency = variant { USD }; decimals = 2:nat8 }; last_distribution = 0:nat64; distribution_frequency = 2592000:nat64 })"
 check_success "Property listing"
 
 # Add property document
@@ -57,12 +57,34 @@ echo "3.2 Adding property document..."
 dfx canister call test_ireits_backend add_document \
   "(1:nat64, variant { Deed }, \"QmHash123\")"
 check_success "Document addition"
+dfx canister call test_ireits_backend add_document \
+  "(1:nat64, variant { Title }, \"QmHash456\")"
+check_success "Title document addition"
+dfx canister call test_ireits_backend add_document \
+  "(1:nat64, variant { Inspection }, \"QmHash789\")"
+check_success "Inspection document addition"
 
-# Verify property listing
+# Move the property through verification
 echo "3.3 Verifying property..."
-dfx canister call test_ireits_backend get_property "(1:nat64)"
+dfx canister call test_ireits_backend transition_property "(1:nat64, variant { PendingVerification }, null)"
+check_success "Submitting for verification"
+dfx canister call test_ireits_backend get_property_transitions "(1:nat64)"
+check_success "Listing allowed transitions"
+dfx canister call test_ireits_backend transition_property "(1:nat64, variant { Verified }, null)"
 check_success "Property verification"
 
+echo "3.4 Attesting documents..."
+dfx canister call test_ireits_backend attest_document "(1:nat64, 1:nat64, variant { Approved }, opt \"Deed matches registry\")"
+check_success "Deed attestation"
+dfx canister call test_ireits_backend attest_document "(1:nat64, 2:nat64, variant { Approved }, null)"
+check_success "Title attestation"
+dfx canister call test_ireits_backend attest_document "(1:nat64, 3:nat64, variant { Approved }, null)"
+check_success "Inspection attestation"
+dfx canister call test_ireits_backend get_document_status "(1:nat64, 1:nat64)"
+check_success "Document status"
+dfx canister call test_ireits_backend get_property "(1:nat64)"
+check_success "Property status history"
+
 # Tokenize property
 echo -e "\n=== 4. Property Tokenization ==="
 echo "4.1 Tokenizing property..."
@@ -89,5 +111,157 @@ echo "5.2 Getting RET token stats..."
 dfx canister call test_ireits_backend get_ret_stats
 check_success "RET token stats"
 
+# Access control
+echo -e "\n=== 6. Access Control ==="
+echo "6.1 Checking deployer roles..."
+dfx canister call test_ireits_backend get_roles "(principal \"$PRINCIPAL\")"
+check_success "Role retrieval"
+
+echo "6.2 Granting and revoking a role..."
+dfx canister call test_ireits_backend grant_role "(principal \"aaaaa-aa\", variant { Appraiser })"
+check_success "Role grant"
+dfx canister call test_ireits_backend revoke_role "(principal \"aaaaa-aa\", variant { Appraiser })"
+check_success "Role revocation"
+dfx canister call test_ireits_backend get_role_audit_log "(0:nat64, 10:nat64)"
+check_success "Role audit log"
+
+# Emergency pause
+echo -e "\n=== 7. Emergency Pause ==="
+echo "7.1 Pausing RET transfers..."
+dfx canister call test_ireits_backend pause_subsystem "(variant { RetTransfers }, \"Incident drill\")"
+check_success "Pause RET transfers"
+dfx canister call test_ireits_backend get_paused_subsystems
+check_success "Paused subsystems retrieval"
+
+echo "7.2 Resuming RET transfers..."
+dfx canister call test_ireits_backend unpause_subsystem "(variant { RetTransfers }, \"Drill complete\")"
+check_success "Unpause RET transfers"
+dfx canister call test_ireits_backend get_pause_log "(0:nat64, 10:nat64)"
+check_success "Pause log retrieval"
+
+# Compliance registry
+echo -e "\n=== 8. Compliance ==="
+echo "8.1 Registering investor..."
+dfx canister call test_ireits_backend set_investor_record \
+  "(principal \"$PRINCIPAL\", variant { Verified }, \"US\", variant { Accredited }, 9999999999999999999:nat64)"
+check_success "Investor registration"
+
+echo "8.2 Adding KYC rule and checking compliance..."
+dfx canister call test_ireits_backend add_compliance_rule "(variant { RequireKyc }, vec { variant { Purchase } })"
+check_success "Compliance rule creation"
+dfx canister call test_ireits_backend check_compliance "(variant { Purchase }, vec { principal \"$PRINCIPAL\" })"
+check_success "Compliance check"
+
+# Freeze and unfreeze through the admin council
+echo -e "\n=== 9. Account Freeze ==="
+echo "9.1 Freezing an account..."
+dfx canister call test_ireits_backend propose_admin_action \
+  "(variant { FreezeAccount = record { account = principal \"aaaaa-aa\"; assets = vec { variant { Ret } }; reason = \"Court order 2024-001\" } })"
+check_success "Freeze proposal"
+dfx canister call test_ireits_backend get_frozen_assets "(principal \"aaaaa-aa\")"
+check_success "Frozen assets retrieval"
+
+echo "9.2 Unfreezing the account..."
+dfx canister call test_ireits_backend propose_admin_action \
+  "(variant { UnfreezeAccount = record { account = principal \"aaaaa-aa\"; assets = vec { variant { Ret } }; reason = \"Order lifted\" } })"
+check_success "Unfreeze proposal"
+dfx canister call test_ireits_backend get_enforcement_log "(0:nat64, 10:nat64)"
+check_success "Enforcement log retrieval"
+
+# Edit, delist and transfer a second property
+echo -e "\n=== 10. Property Editing ==="
+echo "10.1 Creating and listing a property..."
+dfx canister call test_ireits_backend list_property \
+  "(record { units = 25000000:nat64; currency = variant { USD }; decimals = 2:nat8 }, \"9 Elm St\", \"Townhouse\", null)"
+check_success "Property creation"
+dfx canister call test_ireits_backend add_document "(2:nat64, variant { Deed }, \"QmElmDeed\")"
+dfx canister call test_ireits_backend add_document "(2:nat64, variant { Title }, \"QmElmTitle\")"
+dfx canister call test_ireits_backend transition_property "(2:nat64, variant { PendingVerification }, null)"
+dfx canister call test_ireits_backend transition_property "(2:nat64, variant { Verified }, null)"
+dfx canister call test_ireits_backend transition_property "(2:nat64, variant { Listed }, null)"
+check_success "Property listed"
+
+echo "10.2 Updating the property..."
+dfx canister call test_ireits_backend update_property \
+  "(2:nat64, record { price = opt record { units = 24000000:nat64; currency = variant { USD }; decimals = 2:nat8 }; location = null; description = opt \"Renovated townhouse\"; rental_income = null })"
+check_success "Property update"
+dfx canister call test_ireits_backend appraise_property \
+  "(2:nat64, record { units = 23500000:nat64; currency = variant { USD }; decimals = 2:nat8 })"
+check_success "Property appraisal"
+
+echo "10.3 Delisting and transferring ownership..."
+dfx canister call test_ireits_backend delist_property "(2:nat64, opt \"Owner withdrew listing\")"
+check_success "Property delisting"
+dfx canister call test_ireits_backend transfer_property_ownership "(2:nat64, principal \"aaaaa-aa\")"
+check_success "Ownership transfer"
+dfx canister call test_ireits_backend get_property_revisions "(2:nat64)"
+check_success "Revision history retrieval"
+
+# Upload a document file and serve it over HTTP
+echo -e "\n=== 11. Document Storage ==="
+echo "11.1 Declaring and uploading a document..."
+dfx canister call test_ireits_backend add_document \
+  "(1:nat64, variant { Other }, \"2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824\")"
+check_success "Document declaration"
+dfx canister call test_ireits_backend begin_document_upload \
+  "(1:nat64, 4:nat64, \"text/plain\", 5:nat64, variant { Public })"
+check_success "Upload start"
+dfx canister call test_ireits_backend upload_document_chunk "(1:nat64, 0:nat64, blob \"hello\")"
+check_success "Chunk upload"
+dfx canister call test_ireits_backend finish_document_upload "(1:nat64)"
+check_success "Upload verification"
+
+echo "11.2 Serving the document..."
+dfx canister call test_ireits_backend http_request \
+  "(record { method = \"GET\"; url = \"/documents/1/4\"; headers = vec {}; body = blob \"\" })"
+check_success "Document served over HTTP"
+
+# Renew and revoke documents
+echo -e "\n=== 12. Document Versioning ==="
+echo "12.1 Superseding the inspection..."
+dfx canister call test_ireits_backend supersede_document "(1:nat64, 3:nat64, \"QmHashInspection2\", null)"
+check_success "Inspection renewal"
+dfx canister call test_ireits_backend get_document_versions "(1:nat64, 3:nat64)"
+check_success "Version chain retrieval"
+
+echo "12.2 Revoking a document..."
+dfx canister call test_ireits_backend revoke_document "(1:nat64, 4:nat64, \"Uploaded in error\")"
+check_success "Document revocation"
+dfx canister call test_ireits_backend get_current_documents "(1:nat64, null)"
+check_success "Current documents retrieval"
+
+# Structured property data
+echo -e "\n=== 13. Property Details ==="
+echo "13.1 Migrating legacy locations..."
+dfx canister call test_ireits_backend migrate_property_details "(0:nat64, 100:nat64)"
+check_success "Details migration"
+
+echo "13.2 Setting structured details..."
+dfx canister call test_ireits_backend update_property \
+  "(1:nat64, record { price = null; location = null; description = null; rental_income = null; details = opt record { address = record { street = \"123 Main St\"; unit = null; city = \"Springfield\"; region = \"IL\"; postal_code = \"62701\"; country = \"us\" }; coordinates = opt record { latitude = 39.78; longitude = -89.65 }; property_type = opt variant { Residential }; square_footage = opt 1800:nat64; year_built = opt 1998:nat16; units = opt 1:nat32; occupancy_rate = opt 10000:nat16; amenities = vec { \"Garage\" }; photos = vec {} } })"
+check_success "Details update"
+dfx canister call test_ireits_backend get_property "(1:nat64)"
+check_success "Property retrieval"
+
+# Filtered, sorted and paginated property search
+echo -e "\n=== 14. Property Search ==="
+echo "14.1 Searching by city and status..."
+dfx canister call test_ireits_backend search_properties \
+  "(record { statuses = vec { variant { Tokenized } }; owner = null; min_price = null; max_price = null; city = opt \"springfield\"; country = null; property_type = opt variant { Residential }; min_yield_bps = null }, variant { PriceDesc }, null, 10:nat64)"
+check_success "Property search"
+
+echo "14.2 Paging through all properties..."
+dfx canister call test_ireits_backend search_properties \
+  "(record { statuses = vec {}; owner = null; min_price = null; max_price = null; city = null; country = null; property_type = null; min_yield_bps = null }, variant { Newest }, null, 1:nat64)"
+check_success "First page"
+
+echo "14.3 Searching around a point and inside a box..."
+dfx canister call test_ireits_backend search_properties_near \
+  "(record { latitude = 39.8; longitude = -89.6 }, 25.0, record { statuses = vec { variant { Tokenized } }; owner = null; min_price = null; max_price = null; city = null; country = null; property_type = null; min_yield_bps = null }, 0:nat64, 10:nat64)"
+check_success "Radius search"
+dfx canister call test_ireits_backend search_properties_in_box \
+  "(record { min_latitude = 39.0; min_longitude = -90.0; max_latitude = 40.0; max_longitude = -89.0 }, record { statuses = vec {}; owner = null; min_price = null; max_price = null; city = null; country = null; property_type = null; min_yield_bps = null }, 0:nat64, 10:nat64)"
+check_success "Bounding box search"
+
 echo -e "\n✅ Integration test completed successfully!"
 echo "All core components verified and working together." 
\ No newline at end of file
@@ -56,9 +56,24 @@ check_success "Switching to property owner identity"
 # List a property
 echo -e "\n4. Listing a property..."
 dfx canister call test_ireits_backend list_property \
-  "(100000.0, \"123 Main St\", \"Beautiful property\", null)"
+  "(record { units = 10000000:nat64; currency = variant { USD }; decimals = 2:nat8 }, \"123 Main St\", \"Beautiful property\", null)"
 check_success "Property listing"
 
+# Submit documents and get the property verified
+dfx canister call test_ireits_backend add_document "(1:nat64, variant { Deed }, \"QmDeedHash\")"
+dfx canister call test_ireits_backend add_document "(1:nat64, variant { Title }, \"QmTitleHash\")"
+dfx canister call test_ireits_backend add_document "(1:nat64, variant { Inspection }, \"QmInspectionHash\")"
+dfx canister call test_ireits_backend transition_property "(1:nat64, variant { PendingVerification }, null)"
+check_success "Submitting property for verification"
+dfx identity use default
+dfx canister call test_ireits_backend transition_property "(1:nat64, variant { Verified }, null)"
+check_success "Property verification"
+dfx canister call test_ireits_backend attest_document "(1:nat64, 1:nat64, variant { Approved }, null)"
+dfx canister call test_ireits_backend attest_document "(1:nat64, 2:nat64, variant { Approved }, null)"
+dfx canister call test_ireits_backend attest_document "(1:nat64, 3:nat64, variant { Approved }, null)"
+check_success "Document attestations"
+dfx identity use property_owner
+
 # Tokenize the property
 echo -e "\n5. Tokenizing property..."
 dfx canister call test_ireits_backend tokenize_property \
@@ -77,7 +92,7 @@ check_success "Property fractionalization"
 # List property on marketplace
 echo -e "\n7. Listing property on marketplace..."
 dfx canister call test_ireits_backend list_property_marketplace \
-  "(1:nat64, record { amount = 1000:nat64; token_type = variant { RET } }, 250:nat16)"
+  "(1:nat64, record { amount = record { units = 1000:nat64; currency = variant { RET }; decimals = 8:nat8 } }, 250:nat16)"
 check_success "Marketplace listing"
 
 # Switch to buyer1
@@ -87,7 +102,7 @@ check_success "Switching to buyer1 identity"
 # Place bid
 echo -e "\n8. Placing bid..."
 dfx canister call test_ireits_backend place_bid \
-  "(1:nat64, 1000:nat64, variant { RET })"
+  "(1:nat64, record { units = 1000:nat64; currency = variant { RET }; decimals = 8:nat8 })"
 check_success "Placing first bid"
 
 # Switch to buyer2
@@ -97,7 +112,7 @@ check_success "Switching to buyer2 identity"
 # Place higher bid
 echo -e "\n9. Placing higher bid..."
 dfx canister call test_ireits_backend place_bid \
-  "(1:nat64, 1200:nat64, variant { RET })"
+  "(1:nat64, record { units = 1200:nat64; currency = variant { RET }; decimals = 8:nat8 })"
 check_success "Placing second bid"
 
 # Switch back to owner
@@ -124,8 +139,37 @@ echo -e "\n13. Verifying property shares..."
 dfx canister call test_ireits_backend get_property_shares "(1:nat64)"
 check_success "Verifying property shares"
 
+# Set a transfer policy with a holder cap
+echo -e "\n14. Setting property transfer policy..."
+dfx canister call test_ireits_backend set_transfer_policy \
+  "(1:nat64, record { lockup_period = 0:nat64; allowed_jurisdictions = vec {}; max_holders = opt (2:nat64); min_holding = opt (1000:nat16); max_holding = null; rofr_window = null })"
+check_success "Setting transfer policy"
+
+# Move shares between existing holders
+echo -e "\n15. Transferring shares between holders..."
+dfx identity use share_buyer1
+dfx canister call test_ireits_backend transfer_property_shares \
+  "(1:nat64, principal \"$BUYER2_PRINCIPAL\", 2000:nat16)"
+check_success "Share transfer"
+dfx canister call test_ireits_backend get_property_shares "(1:nat64)"
+check_success "Verifying shares after transfer"
+
 # Switch back to default identity
 dfx identity use default
 check_success "Switching back to default identity"
 
+# Cap table and exports, readable by the property owner
+echo -e "\n16. Checking cap table and exports..."
+dfx canister call test_ireits_backend get_cap_table "(1:nat64, null, 0:nat64, 10:nat64)"
+check_success "Retrieving cap table"
+dfx canister call test_ireits_backend get_share_movements "(1:nat64, 0:nat64, 10:nat64)"
+check_success "Retrieving share movements"
+dfx canister call test_ireits_backend export_cap_table "(1:nat64, null, variant { Csv })"
+check_success "Exporting cap table as CSV"
+LINK=$(dfx canister call test_ireits_backend create_cap_table_link "(1:nat64)" | grep -o '[0-9a-f]\{64\}')
+check_success "Creating cap table export link"
+dfx canister call test_ireits_backend http_request \
+  "(record { method = \"GET\"; url = \"/cap-table/1.json?link=$LINK\"; headers = vec {}; body = blob \"\" })"
+check_success "Fetching cap table over HTTP"
+
 echo -e "\n✅ Marketplace test sequence completed successfully!" 
\ No newline at end of file
@@ -10,6 +10,16 @@ check_success() {
     fi
 }
 
+# Function to check that a canister call returned the expected result variant
+check_result() {
+    if echo "$OUTPUT" | grep -q "variant { $1"; then
+        echo "✅ Success: $2"
+    else
+        echo "❌ Failed: $2 (expected $1)"
+        exit 1
+    fi
+}
+
 # Function to check command with allowed failure
 check_with_warning() {
     if [ $? -eq 0 ]; then
@@ -117,9 +127,93 @@ check_success "User 2 post-transfer balance"
 echo -e "\n13. Getting token stats..."
 dfx canister call test_ireits_backend get_ret_stats
 check_success "Token stats retrieval"
+dfx canister call test_ireits_backend get_ret_stats_history "(variant { Hourly }, 0:nat64, 18_446_744_073_709_551_615:nat64)"
+check_success "Hourly stats history retrieval"
 
 # Switch back to default identity
 dfx identity use default
 check_success "Switching back to default identity"
 
-echo -e "\n✅ RET Token test sequence completed successfully!" 
\ No newline at end of file
+# Mint new supply through the admin council
+echo -e "\n14. Minting tokens..."
+dfx canister call test_ireits_backend propose_admin_action \
+  "(variant { MintRet = record { to = principal \"$USER2_PRINCIPAL\"; amount = 1_000:nat64; memo = null } })"
+check_success "Token minting"
+
+# Burn tokens from the caller's own balance
+echo -e "\n15. Burning tokens..."
+dfx canister call test_ireits_backend burn_ret "(500:nat64, null)"
+check_success "Token burning"
+
+# Verify supply changes and transaction history
+echo -e "\n16. Verifying supply and transaction log..."
+dfx canister call test_ireits_backend get_ret_metadata
+check_success "Post-mint/burn metadata verification"
+dfx canister call test_ireits_backend get_ret_transactions "(0:nat64, 20:nat64)"
+check_success "Transaction log retrieval"
+
+# Run a push airdrop campaign
+echo -e "\n17. Creating airdrop campaign..."
+EXPIRES_AT=$(( ($(date +%s) + 86400) * 1000000000 ))
+dfx canister call test_ireits_backend create_airdrop_campaign \
+  "(\"Early investors\", variant { Push }, null, 20_000:nat64, $EXPIRES_AT:nat64)"
+check_success "Airdrop campaign creation"
+
+echo -e "\n18. Distributing airdrop batch..."
+dfx canister call test_ireits_backend distribute_airdrop_batch \
+  "(1:nat64, vec {
+    record { principal \"$USER1_PRINCIPAL\"; 5_000:nat64 };
+    record { principal \"$USER1_PRINCIPAL\"; 5_000:nat64 }
+  })"
+check_success "Airdrop batch distribution (duplicate skipped)"
+
+echo -e "\n19. Getting airdrop campaign report..."
+dfx canister call test_ireits_backend get_airdrop_report "(1:nat64)"
+check_success "Airdrop campaign report"
+
+# Configure a transfer fee routed to the owner
+echo -e "\n20. Configuring transfer fee..."
+dfx canister call test_ireits_backend propose_admin_action \
+  "(variant { SetRetFeeConfig = record { transfer_fee = 10:nat64; fee_collector = opt principal \"$PRINCIPAL\" } })"
+check_success "Fee configuration"
+dfx canister call test_ireits_backend get_admin_action "(2:nat64)"
+check_success "Admin action retrieval"
+
+# Retried transfers with the same created_at_time are rejected as duplicates
+echo -e "\n21. Testing transfer deduplication..."
+CREATED_AT=$(( $(date +%s) * 1000000000 ))
+dfx identity use test_user2
+OUTPUT=$(dfx canister call test_ireits_backend transfer \
+  "(record {
+    from = principal \"$USER2_PRINCIPAL\";
+    to = principal \"$USER1_PRINCIPAL\";
+    amount = 1_000:nat64;
+    fee = opt (10:nat64);
+    memo = opt blob \"order-1\";
+    created_at_time = opt ($CREATED_AT:nat64)
+  })")
+echo "$OUTPUT"
+check_result Ok "First transfer"
+OUTPUT=$(dfx canister call test_ireits_backend transfer \
+  "(record {
+    from = principal \"$USER2_PRINCIPAL\";
+    to = principal \"$USER1_PRINCIPAL\";
+    amount = 1_000:nat64;
+    fee = opt (10:nat64);
+    memo = opt blob \"order-1\";
+    created_at_time = opt ($CREATED_AT:nat64)
+  })")
+echo "$OUTPUT"
+check_result "Err = variant { Duplicate" "Retried transfer rejected as duplicate"
+dfx identity use default
+
+# Take a named snapshot and read balances as of that point
+echo -e "\n22. Taking balance snapshot..."
+dfx canister call test_ireits_backend take_snapshot "(\"post-transfers\")"
+check_success "Snapshot creation"
+dfx canister call test_ireits_backend balance_at_snapshot "(1:nat64, principal \"$USER1_PRINCIPAL\")"
+check_success "Balance at snapshot"
+dfx canister call test_ireits_backend total_supply_at_snapshot "(1:nat64)"
+check_success "Total supply at snapshot"
+
+echo -e "\n✅ RET Token test sequence completed successfully!"
\ No newline at end of file